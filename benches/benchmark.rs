@@ -1,22 +1,339 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use safer_bytes::{BytesMut, SafeBuf};
+use criterion::{
+    black_box, criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup,
+    BenchmarkId, Criterion, Throughput,
+};
+use paste::paste;
+use safer_bytes::{unchecked::Buf, Buffered, Bytes, BytesMut, FromBuf, SafeBuf, SliceReader};
 
-fn copy_to_slice(_dummy: usize) {
-    let mut buffer = BytesMut::new();
-    buffer.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-    let output = &mut [0_u8; 6];
-    let _output = buffer.try_copy_to_slice(output).unwrap();
+const DATA: [u8; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+/// The read sizes used by the parameterised benchmarks
+const READ_SIZES: [usize; 4] = [1, 16, 256, 4096];
+
+/// The buffer types used by the parameterised benchmarks, each constructed
+/// over the given bytes
+///
+/// The chained buffer is split in the middle, so that reads which straddle a
+/// chunk boundary are exercised.
+macro_rules! for_each_source {
+    ($data:expr, |$name:ident, $setup:ident| $body:block) => {{
+        let data: &'static [u8] = $data;
+        let mid = data.len() / 2;
+        {
+            let $name = "slice";
+            let $setup = || data;
+            $body
+        }
+        {
+            let $name = "bytes";
+            let $setup = || Bytes::from_static(data);
+            $body
+        }
+        {
+            let $name = "bytes_mut";
+            let $setup = || BytesMut::from(data);
+            $body
+        }
+        {
+            let $name = "chain";
+            let $setup = || (&data[..mid]).chain(&data[mid..]);
+            $body
+        }
+        {
+            let $name = "buffered_chain";
+            let $setup = || {
+                Buffered::new(
+                    Bytes::from_static(&data[..mid]).chain(Bytes::from_static(&data[mid..])),
+                )
+            };
+            $body
+        }
+    }};
+}
+
+/// Benchmark copying `len` bytes out of a buffer of type `B`, comparing the
+/// checked methods against the raw [`Buf`] methods as a baseline
+fn bench_copy<B, F>(group: &mut BenchmarkGroup<WallTime>, source: &str, len: usize, setup: F)
+where
+    B: Buf,
+    F: Fn() -> B + Copy,
+{
+    group.bench_with_input(
+        BenchmarkId::new(format!("{}/try_copy_to_slice", source), len),
+        &len,
+        |b, &len| {
+            let mut output = vec![0; len];
+            b.iter_batched(
+                setup,
+                |mut buffer| SafeBuf::try_copy_to_slice(&mut buffer, &mut output).unwrap(),
+                BatchSize::SmallInput,
+            )
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new(format!("{}/baseline_copy_to_slice", source), len),
+        &len,
+        |b, &len| {
+            let mut output = vec![0; len];
+            b.iter_batched(
+                setup,
+                |mut buffer| buffer.copy_to_slice(&mut output),
+                BatchSize::SmallInput,
+            )
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new(format!("{}/try_copy_to_bytes", source), len),
+        &len,
+        |b, &len| {
+            b.iter_batched(
+                setup,
+                |mut buffer| buffer.try_copy_to_bytes(len).unwrap(),
+                BatchSize::SmallInput,
+            )
+        },
+    );
+    group.bench_with_input(
+        BenchmarkId::new(format!("{}/baseline_copy_to_bytes", source), len),
+        &len,
+        |b, &len| {
+            b.iter_batched(
+                setup,
+                |mut buffer| buffer.copy_to_bytes(len),
+                BatchSize::SmallInput,
+            )
+        },
+    );
+}
+
+fn bench_copies(c: &mut Criterion) {
+    static DATA: [u8; 4096] = [0xA5; 4096];
+
+    let mut group = c.benchmark_group("copy");
+    for &len in &READ_SIZES {
+        group.throughput(Throughput::Bytes(len as u64));
+        for_each_source!(&DATA, |source, setup| {
+            bench_copy(&mut group, source, len, setup);
+        });
+    }
+    group.finish();
 }
 
-fn copy_to_bytes(_dummy: usize) {
-    let mut buffer = BytesMut::new();
-    buffer.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
-    let _output = buffer.try_copy_to_bytes(6).unwrap();
+/// Benchmark taking bytes from the front of a buffer of type `B`
+fn bench_take<B, F>(c: &mut Criterion, name: &str, setup: F)
+where
+    B: Buf,
+    F: Fn() -> B + Copy,
+{
+    let mut group = c.benchmark_group(format!("take/{}", name));
+
+    group.bench_function("try_copy_to_bytes", |b| {
+        b.iter_batched(
+            setup,
+            |mut buffer| buffer.try_copy_to_bytes(black_box(6)).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("try_take_const", |b| {
+        b.iter_batched(
+            setup,
+            |mut buffer| buffer.try_take_const::<6>().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("try_peek_then_advance", |b| {
+        b.iter_batched(
+            setup,
+            |mut buffer| {
+                let output = buffer.try_peek_const::<6>().unwrap();
+                buffer.advance(6);
+                output
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+/// Benchmark reading every value of the given type from a 1 KiB buffer, both
+/// contiguous and split across two chunks
+macro_rules! bench_getters {
+    ($c:ident, $($t:ty),*) => {
+        paste! {
+            let data = [0xA5_u8; 1024];
+            let mut group = $c.benchmark_group("get");
+            group.bench_function("u8/slice", |b| {
+                b.iter(|| {
+                    let mut buffer = black_box(&data[..]);
+                    while let Ok(value) = SafeBuf::try_get_u8(&mut buffer) {
+                        black_box(value);
+                    }
+                })
+            });
+            $(
+                group.bench_function(concat!(stringify!($t), "/slice"), |b| {
+                    b.iter(|| {
+                        let mut buffer = black_box(&data[..]);
+                        while let Ok(value) = SafeBuf::[<try_get_ $t>](&mut buffer) {
+                            black_box(value);
+                        }
+                    })
+                });
+                group.bench_function(concat!(stringify!($t), "_le/slice"), |b| {
+                    b.iter(|| {
+                        let mut buffer = black_box(&data[..]);
+                        while let Ok(value) = SafeBuf::[<try_get_ $t _le>](&mut buffer) {
+                            black_box(value);
+                        }
+                    })
+                });
+                group.bench_function(concat!(stringify!($t), "/slice_reader"), |b| {
+                    b.iter(|| {
+                        let mut reader = SliceReader::new(black_box(&data[..]));
+                        while let Ok(value) = reader.[<try_get_ $t>]() {
+                            black_box(value);
+                        }
+                    })
+                });
+                group.bench_function(concat!(stringify!($t), "/baseline"), |b| {
+                    b.iter(|| {
+                        let mut buffer = black_box(&data[..]);
+                        while buffer.remaining() >= std::mem::size_of::<$t>() {
+                            black_box(buffer.[<get_ $t>]());
+                        }
+                    })
+                });
+                group.bench_function(concat!(stringify!($t), "/chain"), |b| {
+                    b.iter(|| {
+                        let mut buffer = black_box(&data[..509]).chain(black_box(&data[509..]));
+                        while let Ok(value) = SafeBuf::[<try_get_ $t>](&mut buffer) {
+                            black_box(value);
+                        }
+                    })
+                });
+            )*
+            group.finish();
+        }
+    };
+}
+
+/// Benchmark decoding a 1 KiB buffer of little-endian `u32` values, one at a
+/// time versus in bulk
+fn bench_get_slice(c: &mut Criterion) {
+    let data = [0xA5_u8; 1024];
+    let mut group = c.benchmark_group("get_slice");
+
+    group.bench_function("u32_le/loop", |b| {
+        b.iter(|| {
+            let mut buffer = black_box(&data[..]);
+            let mut values = Vec::with_capacity(256);
+            while let Ok(value) = SafeBuf::try_get_u32_le(&mut buffer) {
+                values.push(value);
+            }
+            values
+        })
+    });
+    group.bench_function("u32_le/slice", |b| {
+        b.iter(|| {
+            let mut buffer = black_box(&data[..]);
+            buffer.try_get_u32_slice_le(256).unwrap()
+        })
+    });
+    group.bench_function("u32_le/chain", |b| {
+        b.iter(|| {
+            let mut buffer = black_box(&data[..509]).chain(black_box(&data[509..]));
+            buffer.try_get_u32_slice_le(256).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+/// A small fixed-layout record, as found in many binary formats
+struct Record {
+    tag: u16,
+    len: u32,
+    timestamp: u64,
+}
+
+impl FromBuf for Record {
+    fn from_buf<B>(mut buffer: B) -> safer_bytes::Result<Self>
+    where
+        B: Buf,
+    {
+        Ok(Self {
+            tag: SafeBuf::try_get_u16(&mut buffer)?,
+            len: SafeBuf::try_get_u32(&mut buffer)?,
+            timestamp: SafeBuf::try_get_u64(&mut buffer)?,
+        })
+    }
+}
+
+impl Record {
+    const LEN: usize = 14;
+
+    /// Parse a record with the raw [`Buf`] methods, as a baseline
+    fn from_buf_unchecked<B>(buffer: &mut B) -> Option<Self>
+    where
+        B: Buf,
+    {
+        if buffer.remaining() < Self::LEN {
+            return None;
+        }
+        Some(Self {
+            tag: buffer.get_u16(),
+            len: buffer.get_u32(),
+            timestamp: buffer.get_u64(),
+        })
+    }
+}
+
+/// Benchmark parsing a stream of records with [`FromBuf`], from each type of
+/// buffer
+fn bench_extract(c: &mut Criterion) {
+    static DATA: [u8; 1400] = [0xA5; 1400];
+
+    let mut group = c.benchmark_group("extract");
+    group.throughput(Throughput::Elements((DATA.len() / Record::LEN) as u64));
+    for_each_source!(&DATA, |source, setup| {
+        group.bench_function(format!("{}/extract", source), |b| {
+            b.iter_batched(
+                setup,
+                |mut buffer| {
+                    while let Ok(record) = buffer.extract::<Record>() {
+                        black_box((record.tag, record.len, record.timestamp));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("{}/baseline", source), |b| {
+            b.iter_batched(
+                setup,
+                |mut buffer| {
+                    while let Some(record) = Record::from_buf_unchecked(&mut buffer) {
+                        black_box((record.tag, record.len, record.timestamp));
+                    }
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    });
+    group.finish();
 }
 
 pub fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("copy_to_slice", |b| b.iter(|| copy_to_slice(black_box(6))));
-    c.bench_function("copy_to_bytes", |b| b.iter(|| copy_to_bytes(black_box(6))));
+    bench_copies(c);
+
+    bench_take(c, "bytes", || Bytes::from_static(&DATA));
+    bench_take(c, "bytes_mut", || BytesMut::from(&DATA[..]));
+    bench_take(c, "slice", || &DATA[..]);
+    bench_take(c, "chain", || (&DATA[..4]).chain(&DATA[4..]));
+
+    bench_getters!(c, u16, i16, u32, i32, u64, i64, u128, i128);
+    bench_get_slice(c);
+    bench_extract(c);
 }
 
 criterion_group!(benches, criterion_benchmark);
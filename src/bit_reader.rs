@@ -0,0 +1,197 @@
+//! A bit-level reader over a [`bytes::Buf`]
+
+use crate::{error, Error, SafeBuf};
+use bytes::Buf;
+
+/// A reader of fields narrower than a byte, most significant bit first
+///
+/// Formats such as MPEG headers, DNS flags, and sensor bit-packing divide
+/// bytes into fields of arbitrary widths. A [`BitReader`] pulls bytes from the
+/// underlying buffer as they are needed, and hands out their bits in order.
+///
+/// Every read is checked up front, so a read which fails because the buffer is
+/// truncated consumes nothing.
+///
+/// ```
+/// use safer_bytes::BitReader;
+///
+/// // the flags of a DNS response
+/// let mut flags = BitReader::new(&[0x81, 0x83][..]);
+///
+/// assert!(flags.try_read_bit()?); // QR
+/// assert_eq!(flags.try_read_bits(4)?, 0); // opcode
+/// flags.try_read_bits(7)?; // AA, TC, RD, RA, Z
+/// assert_eq!(flags.try_read_bits(4)?, 3); // RCODE (NXDOMAIN)
+/// assert!(flags.is_aligned());
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitReader<B> {
+    inner: B,
+    current: u8,
+    bits_left: u32,
+}
+
+impl<B> BitReader<B> {
+    /// Construct a new [`BitReader`], starting at the first bit of the buffer
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            current: 0,
+            bits_left: 0,
+        }
+    }
+
+    /// Whether the reader is at a byte boundary
+    pub fn is_aligned(&self) -> bool {
+        self.bits_left == 0
+    }
+
+    /// Skip to the start of the next byte, returning the number of bits which
+    /// were discarded
+    ///
+    /// This does nothing if the reader is already at a byte boundary.
+    pub fn align(&mut self) -> u32 {
+        let discarded = self.bits_left;
+        self.bits_left = 0;
+        discarded
+    }
+
+    /// Get a reference to the underlying buffer
+    ///
+    /// Any bits of a partially read byte have already been taken from the
+    /// buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the reader, returning the underlying buffer
+    ///
+    /// Any unread bits of a partially read byte are discarded.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> BitReader<B>
+where
+    B: Buf,
+{
+    /// The number of bits left to read
+    pub fn remaining_bits(&self) -> u64 {
+        u64::from(self.bits_left).saturating_add((self.inner.remaining() as u64).saturating_mul(8))
+    }
+
+    /// Read a single bit
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if there are no bits remaining
+    pub fn try_read_bit(&mut self) -> crate::Result<bool> {
+        Ok(self.try_read_bits(1)? == 1)
+    }
+
+    /// Read an unsigned field `count` bits wide (up to 64)
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `count` is greater than 64, or if
+    /// there are not enough bits remaining
+    pub fn try_read_bits(&mut self, count: u32) -> crate::Result<u64> {
+        if count > 64 {
+            return Err(Error::invalid("bit count", count));
+        }
+
+        let needed = (count.saturating_sub(self.bits_left) as usize + 7) / 8;
+        error::Truncated::check(needed, self.inner.remaining())?;
+
+        let mut value = 0;
+        let mut count = count;
+        while count > 0 {
+            if self.bits_left == 0 {
+                self.current = SafeBuf::try_get_u8(&mut self.inner)?;
+                self.bits_left = 8;
+            }
+
+            let take = count.min(self.bits_left);
+            let bits = (u64::from(self.current) >> (self.bits_left - take)) & ((1 << take) - 1);
+            value = (value << take) | bits;
+
+            self.bits_left -= take;
+            count -= take;
+        }
+        Ok(value)
+    }
+
+    /// Read a two's complement signed field `count` bits wide (up to 64)
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `count` is greater than 64, or if
+    /// there are not enough bits remaining
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn try_read_signed_bits(&mut self, count: u32) -> crate::Result<i64> {
+        let value = self.try_read_bits(count)?;
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let shift = 64 - count;
+        Ok((value << shift) as i64 >> shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitReader;
+    use crate::{error, Error};
+
+    #[test]
+    fn read_bits() {
+        let mut reader = BitReader::new(bytes::Buf::chain(&[0b1010_1100][..], &[0xFF, 0x01][..]));
+
+        assert_eq!(reader.try_read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.remaining_bits(), 21);
+        assert_eq!(reader.try_read_bits(9).unwrap(), 0b0_1100_1111);
+        assert_eq!(reader.try_read_bits(0).unwrap(), 0);
+        assert_eq!(reader.align(), 4);
+        assert!(reader.is_aligned());
+        assert_eq!(reader.try_read_bits(8).unwrap(), 1);
+        assert!(reader.try_read_bit().is_err());
+    }
+
+    #[test]
+    fn wide_fields() {
+        let data = [0x0F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xF0];
+        let mut reader = BitReader::new(&data[..]);
+
+        assert_eq!(reader.try_read_bits(4).unwrap(), 0);
+        assert_eq!(reader.try_read_bits(64).unwrap(), u64::MAX);
+        assert_eq!(
+            reader.try_read_bits(65).unwrap_err(),
+            Error::invalid("bit count", 65_u32)
+        );
+    }
+
+    #[test]
+    fn signed() {
+        let mut reader = BitReader::new(&[0b1110_0111][..]);
+
+        assert_eq!(reader.try_read_signed_bits(3).unwrap(), -1);
+        assert_eq!(reader.try_read_signed_bits(0).unwrap(), 0);
+        assert_eq!(reader.try_read_signed_bits(3).unwrap(), 1);
+        assert_eq!(reader.try_read_signed_bits(2).unwrap(), -1);
+    }
+
+    #[test]
+    fn truncated_reads_consume_nothing() {
+        let mut reader = BitReader::new(&[0xAB, 0xCD][..]);
+
+        assert_eq!(reader.try_read_bits(4).unwrap(), 0xA);
+        assert_eq!(
+            reader.try_read_bits(13).unwrap_err(),
+            error::Truncated::new(2, 1).into()
+        );
+        assert_eq!(reader.try_read_bits(12).unwrap(), 0xBCD);
+    }
+}
@@ -0,0 +1,247 @@
+//! A bit-level writer into a [`bytes::BufMut`]
+
+use crate::{error, Error};
+use bytes::BufMut;
+
+/// A writer of fields narrower than a byte, most significant bit first
+///
+/// This is the counterpart to [`BitReader`](crate::BitReader). Bits are
+/// collected into a pending byte, which is written to the underlying buffer
+/// once it is full. A partially filled byte is only written when the writer is
+/// explicitly aligned with [`BitWriter::try_align`] or
+/// [`BitWriter::try_finish`]; it is *not* flushed when the writer is dropped.
+///
+/// Every write is checked up front, so a write which fails because the buffer
+/// is out of space writes nothing.
+///
+/// ```
+/// use safer_bytes::BitWriter;
+///
+/// let mut flags = BitWriter::new(Vec::new());
+///
+/// flags.try_write_bit(true)?; // QR
+/// flags.try_write_bits(0, 4)?; // opcode
+/// flags.try_write_bits(0b0011000, 7)?; // AA, TC, RD, RA, Z
+/// flags.try_write_bits(3, 4)?; // RCODE (NXDOMAIN)
+///
+/// assert_eq!(flags.try_finish()?, [0x81, 0x83]);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitWriter<B> {
+    inner: B,
+    pending: u8,
+    bits_used: u32,
+}
+
+impl<B> BitWriter<B> {
+    /// Construct a new [`BitWriter`], starting at a byte boundary
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            pending: 0,
+            bits_used: 0,
+        }
+    }
+
+    /// Whether the writer is at a byte boundary
+    pub fn is_aligned(&self) -> bool {
+        self.bits_used == 0
+    }
+
+    /// Get a reference to the underlying buffer
+    ///
+    /// The bits of a partially filled byte have not yet been written to it.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the writer, returning the underlying buffer
+    ///
+    /// The bits of a partially filled byte are discarded. Use
+    /// [`BitWriter::try_finish`] to write them out.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> BitWriter<B>
+where
+    B: BufMut,
+{
+    /// Write a single bit
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer is out of space
+    pub fn try_write_bit(&mut self, bit: bool) -> crate::Result<()> {
+        self.try_write_bits(bit.into(), 1)
+    }
+
+    /// Write an unsigned field `count` bits wide (up to 64)
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `count` is greater than 64, if
+    /// `value` does not fit in `count` bits, or if the buffer does not have
+    /// space for the bytes which this write completes
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn try_write_bits(&mut self, value: u64, count: u32) -> crate::Result<()> {
+        if count > 64 {
+            return Err(Error::invalid("bit count", count));
+        }
+        if count < 64 && value >> count != 0 {
+            return Err(Error::invalid("bit field", value));
+        }
+
+        let completed = (self.bits_used + count) as usize / 8;
+        error::Truncated::check(completed, self.inner.remaining_mut())?;
+
+        let mut count = count;
+        while count > 0 {
+            let take = count.min(8 - self.bits_used);
+            let bits = (value >> (count - take)) & ((1 << take) - 1);
+            self.pending = (u64::from(self.pending) << take | bits) as u8;
+
+            self.bits_used += take;
+            count -= take;
+            if self.bits_used == 8 {
+                self.inner.put_u8(self.pending);
+                self.pending = 0;
+                self.bits_used = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a two's complement signed field `count` bits wide (up to 64)
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `count` is greater than 64, if
+    /// `value` does not fit in `count` bits, or if the buffer does not have
+    /// space for the bytes which this write completes
+    #[allow(clippy::cast_sign_loss)]
+    pub fn try_write_signed_bits(&mut self, value: i64, count: u32) -> crate::Result<()> {
+        if count > 64 {
+            return Err(Error::invalid("bit count", count));
+        }
+        if count < 64 {
+            let shift = 64 - count;
+            if count == 0 && value != 0 || count > 0 && (value << shift) >> shift != value {
+                return Err(Error::invalid("bit field", value));
+            }
+        }
+
+        let mask = if count == 64 {
+            u64::MAX
+        } else {
+            (1 << count) - 1
+        };
+        self.try_write_bits(value as u64 & mask, count)
+    }
+
+    /// Pad the pending byte to a byte boundary and write it out, returning the
+    /// number of padding bits
+    ///
+    /// The padding bits are ones if `padding` is `true`, otherwise zeros. This
+    /// does nothing if the writer is already at a byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer is out of space
+    pub fn try_align(&mut self, padding: bool) -> crate::Result<u32> {
+        if self.is_aligned() {
+            return Ok(0);
+        }
+
+        let count = 8 - self.bits_used;
+        let value = if padding { (1 << count) - 1 } else { 0 };
+        self.try_write_bits(value, count)?;
+        Ok(count)
+    }
+
+    /// Pad the pending byte with zeros and write it out, returning the
+    /// underlying buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer is out of space
+    pub fn try_finish(mut self) -> crate::Result<B> {
+        self.try_align(false)?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitWriter;
+    use crate::{error, BitReader, Error};
+
+    #[test]
+    fn write_bits() {
+        let mut writer = BitWriter::new(Vec::new());
+
+        writer.try_write_bits(0b101, 3).unwrap();
+        writer.try_write_bits(0b0_1100_1111, 9).unwrap();
+        writer.try_write_bits(0, 0).unwrap();
+        assert_eq!(writer.get_ref(), &[0b1010_1100]);
+        assert_eq!(writer.try_align(true).unwrap(), 4);
+        assert_eq!(writer.try_align(true).unwrap(), 0);
+        writer.try_write_bits(u64::MAX, 64).unwrap();
+
+        let bytes = writer.try_finish().unwrap();
+        assert_eq!(bytes[..2], [0b1010_1100, 0xFF]);
+        assert_eq!(bytes.len(), 10);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut writer = BitWriter::new(Vec::new());
+        writer.try_write_signed_bits(-3, 5).unwrap();
+        writer.try_write_bits(0x1234_5678_9ABC, 48).unwrap();
+        writer.try_write_signed_bits(i64::MIN, 64).unwrap();
+        writer.try_write_bit(true).unwrap();
+        let bytes = writer.try_finish().unwrap();
+
+        let mut reader = BitReader::new(&bytes[..]);
+        assert_eq!(reader.try_read_signed_bits(5).unwrap(), -3);
+        assert_eq!(reader.try_read_bits(48).unwrap(), 0x1234_5678_9ABC);
+        assert_eq!(reader.try_read_signed_bits(64).unwrap(), i64::MIN);
+        assert!(reader.try_read_bit().unwrap());
+        assert_eq!(reader.align(), 2);
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let mut writer = BitWriter::new(Vec::new());
+
+        assert_eq!(
+            writer.try_write_bits(4, 2).unwrap_err(),
+            Error::invalid("bit field", 4_u64)
+        );
+        assert_eq!(
+            writer.try_write_signed_bits(-5, 3).unwrap_err(),
+            Error::invalid("bit field", -5_i64)
+        );
+        assert!(writer.try_write_signed_bits(1, 0).is_err());
+        assert!(writer.try_write_bits(0, 65).is_err());
+        assert!(writer.is_aligned());
+    }
+
+    #[test]
+    fn out_of_space() {
+        let mut storage = [0_u8; 1];
+        let mut writer = BitWriter::new(&mut storage[..]);
+
+        writer.try_write_bits(0xF, 4).unwrap();
+        assert_eq!(
+            writer.try_write_bits(0xFFF, 12).unwrap_err(),
+            error::Truncated::new(2, 1).into()
+        );
+        writer.try_write_bits(0x0, 4).unwrap();
+        assert!(writer.try_write_bit(true).is_ok());
+        assert!(writer.try_finish().is_err());
+        assert_eq!(storage, [0xF0]);
+    }
+}
@@ -0,0 +1,182 @@
+//! A [`bytes::Buf`] adapter which caches the current chunk
+
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A buffer adapter which caches the current chunk of the underlying buffer
+///
+/// Every checked read calls [`Buf::chunk`] and [`Buf::remaining`] at least
+/// once. For chained or rope-like buffers these calls can be surprisingly
+/// expensive, since each one has to work out which of the underlying pieces is
+/// current. A [`Buffered`] takes the current chunk out of the underlying buffer
+/// as [`Bytes`], and serves reads from it until it is exhausted, only then
+/// consulting the underlying buffer again.
+///
+/// Taking a chunk out of the underlying buffer uses [`Buf::copy_to_bytes`].
+/// This is free for buffers made of [`Bytes`] (such as
+/// `Chain<Bytes, Bytes>`), but copies for other buffer types, so this adapter
+/// is only worthwhile for the former.
+///
+/// ```
+/// use safer_bytes::{Buffered, Bytes, SafeBuf};
+///
+/// let rope = bytes::Buf::chain(Bytes::from_static(&[0, 1, 2]), Bytes::from_static(&[3, 4]));
+/// let mut buffer = Buffered::new(rope);
+///
+/// assert_eq!(buffer.try_get_u16()?, 0x0001);
+/// assert_eq!(buffer.try_get_u16()?, 0x0203);
+/// assert_eq!(bytes::Buf::remaining(&buffer), 1);
+/// # Ok::<(), safer_bytes::error::Truncated>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Buffered<B> {
+    inner: B,
+    cache: Bytes,
+}
+
+impl<B> Buffered<B>
+where
+    B: Buf,
+{
+    /// Wrap a buffer, caching its current chunk
+    pub fn new(inner: B) -> Self {
+        let mut buffered = Self {
+            inner,
+            cache: Bytes::new(),
+        };
+        buffered.refill();
+        buffered
+    }
+
+    /// A reference to the underlying buffer
+    ///
+    /// This does not include the cached chunk.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the [`Buffered`], returning the cached chunk and the underlying
+    /// buffer
+    ///
+    /// The cached chunk has already been taken out of the underlying buffer,
+    /// and precedes it.
+    pub fn into_parts(self) -> (Bytes, B) {
+        (self.cache, self.inner)
+    }
+
+    fn refill(&mut self) {
+        let len = self.inner.chunk().len();
+        if len > 0 {
+            self.cache = self.inner.copy_to_bytes(len);
+        }
+    }
+}
+
+impl<B> Buf for Buffered<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.cache.len() + self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        // the cache is only ever empty if the underlying buffer is too
+        &self.cache
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        if self.cache.is_empty() {
+            return 0;
+        }
+        match dst.split_first_mut() {
+            Some((first, rest)) => {
+                *first = IoSlice::new(&self.cache);
+                1 + self.inner.chunks_vectored(rest)
+            }
+            None => 0,
+        }
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        if cnt < self.cache.len() {
+            self.cache.advance(cnt);
+            return;
+        }
+
+        cnt -= self.cache.len();
+        self.cache.clear();
+        self.inner.advance(cnt);
+        self.refill();
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        if len < self.cache.len() {
+            return self.cache.split_to(len);
+        }
+        if len == self.cache.len() {
+            let bytes = core::mem::take(&mut self.cache);
+            self.refill();
+            return bytes;
+        }
+
+        let mut bytes = bytes::BytesMut::with_capacity(len);
+        bytes.extend_from_slice(&self.cache);
+        self.cache.clear();
+        bytes.extend_from_slice(&self.inner.copy_to_bytes(len - bytes.len()));
+        self.refill();
+        bytes.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Buffered;
+    use crate::{Bytes, SafeBuf};
+
+    fn rope() -> Buffered<bytes::buf::Chain<Bytes, Bytes>> {
+        Buffered::new(bytes::Buf::chain(
+            Bytes::from_static(&[0, 1, 2]),
+            Bytes::from_static(&[3, 4, 5, 6]),
+        ))
+    }
+
+    #[test]
+    fn reads_across_chunks() {
+        let mut buffer = rope();
+
+        assert_eq!(bytes::Buf::chunk(&buffer), &[0, 1, 2]);
+        assert_eq!(buffer.try_get_u16().unwrap(), 0x0001);
+        assert_eq!(buffer.try_get_u16().unwrap(), 0x0203);
+        assert_eq!(bytes::Buf::chunk(&buffer), &[4, 5, 6]);
+        assert!(buffer.try_get_u32().is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 3);
+    }
+
+    #[test]
+    fn copy_to_bytes() {
+        let mut buffer = rope();
+
+        assert_eq!(buffer.try_copy_to_bytes(1).unwrap(), &[0][..]);
+        assert_eq!(buffer.try_copy_to_bytes(2).unwrap(), &[1, 2][..]);
+        assert_eq!(buffer.try_copy_to_bytes(1).unwrap(), &[3][..]);
+
+        let mut buffer = rope();
+        assert_eq!(buffer.try_copy_to_bytes(5).unwrap(), &[0, 1, 2, 3, 4][..]);
+        assert_eq!(buffer.take_rest(), &[5, 6][..]);
+        assert!(bytes::Buf::chunk(&buffer).is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn chunks_vectored() {
+        let buffer = rope();
+        let mut slices = [std::io::IoSlice::new(&[]); 4];
+
+        assert_eq!(bytes::Buf::chunks_vectored(&buffer, &mut slices), 2);
+        assert_eq!(&*slices[0], &[0, 1, 2]);
+        assert_eq!(&*slices[1], &[3, 4, 5, 6]);
+    }
+}
@@ -0,0 +1,346 @@
+//! A buffer over a lazily-pulled sequence of [`Bytes`] chunks
+
+use crate::{error, Error, FromBuf};
+use alloc::collections::VecDeque;
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A buffer over an iterator of [`Bytes`] chunks, such as individual messages
+/// or frames, which pulls the next chunk lazily
+///
+/// A [`ChunkedBuf`] implements [`Buf`] over the chunks pulled so far, so the
+/// [`SafeBuf`](crate::SafeBuf) methods can be used as normal once enough bytes
+/// are available (see [`ChunkedBuf::fill`]). [`ChunkedBuf::extract`] goes
+/// further, and transparently pulls more chunks whenever a parse fails for
+/// want of bytes. Chunks are never concatenated.
+///
+/// The number of bytes held is capped, so a corrupt length field cannot cause
+/// the whole of the source to be pulled into memory. A chunk which would take
+/// the total over the cap is split, and the rest is held back until there is
+/// room for it.
+///
+/// ```
+/// use safer_bytes::{Bytes, ChunkedBuf, SafeBuf};
+///
+/// let messages = vec![Bytes::from_static(&[0, 0]), Bytes::from_static(&[1, 2])];
+/// let mut buffer = ChunkedBuf::new(messages, 1024);
+///
+/// buffer.fill(4)?;
+/// assert_eq!(buffer.try_get_u32()?, 0x0102);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ChunkedBuf<I> {
+    source: I,
+    chunks: Chunks,
+    pending: Option<Bytes>,
+    max_buffered: usize,
+}
+
+impl<I> ChunkedBuf<I>
+where
+    I: Iterator<Item = Bytes>,
+{
+    /// Wrap a source of chunks, holding at most `max_buffered` bytes at a time
+    pub fn new(source: impl IntoIterator<IntoIter = I>, max_buffered: usize) -> Self {
+        Self {
+            source: source.into_iter(),
+            chunks: Chunks::default(),
+            pending: None,
+            max_buffered,
+        }
+    }
+
+    /// The maximum number of bytes which will be held
+    #[must_use]
+    pub fn max_buffered(&self) -> usize {
+        self.max_buffered
+    }
+
+    /// Pull chunks until (at least) `len` bytes are available
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `len` exceeds the maximum number of
+    /// buffered bytes, or if the source runs out of chunks first
+    pub fn fill(&mut self, len: usize) -> crate::Result<()> {
+        if self.fill_to(len)? {
+            Ok(())
+        } else {
+            Err(error::Truncated::new(len, self.chunks.remaining).into())
+        }
+    }
+
+    /// Deserialize an object, pulling more chunks as required
+    ///
+    /// The buffer is only advanced if the parse succeeds.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the parse fails for any reason
+    /// other than truncation, if the parse fails because a nested region is
+    /// truncated (which more input can't fix), if the source runs out of
+    /// chunks (in which case the parse error is returned), or if the number of
+    /// bytes needed exceeds the maximum number of buffered bytes
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        loop {
+            let mut view = self.chunks.clone();
+            let error = match T::from_buf(&mut view) {
+                Ok(value) => {
+                    self.chunks = view;
+                    return Ok(value);
+                }
+                Err(e) => e,
+            };
+
+            let missing = match error.root() {
+                Error::Truncated(truncated) => truncated.missing().max(1),
+                _ => return Err(error),
+            };
+
+            // overrunning a nested region fails the same way however much
+            // input follows it, so pulling more chunks wouldn't help
+            let mut padded = self.chunks.clone().chain(&[0][..]);
+            let retried = T::from_buf(&mut padded);
+            if !padded.last_ref().is_empty() && matches!(retried, Err(ref e) if *e == error) {
+                return Err(error);
+            }
+
+            if !self.fill_to(self.chunks.remaining.saturating_add(missing))? {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Pull chunks until `len` bytes are available, returning `false` if the
+    /// source runs out first
+    ///
+    /// A chunk which would take the total over the maximum is split, and the
+    /// rest held back for the next fill.
+    fn fill_to(&mut self, len: usize) -> crate::Result<bool> {
+        error::LimitExceeded::check("buffered length", len, self.max_buffered)?;
+
+        while self.chunks.remaining < len {
+            let mut chunk = match self.pending.take().or_else(|| self.source.next()) {
+                Some(chunk) => chunk,
+                None => return Ok(false),
+            };
+
+            let room = self.max_buffered - self.chunks.remaining;
+            if chunk.len() > room {
+                self.pending = Some(chunk.split_off(room));
+            }
+            self.chunks.push(chunk);
+        }
+
+        Ok(true)
+    }
+}
+
+impl<I> Buf for ChunkedBuf<I> {
+    fn remaining(&self) -> usize {
+        self.chunks.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.chunks.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.chunks.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.chunks.advance(cnt);
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        self.chunks.copy_to_bytes(len)
+    }
+}
+
+/// The chunks which have been pulled, but not yet consumed
+///
+/// This is cheap to clone, since cloning [`Bytes`] only bumps a reference
+/// count.
+#[derive(Debug, Clone, Default)]
+struct Chunks {
+    queue: VecDeque<Bytes>,
+    remaining: usize,
+}
+
+impl Chunks {
+    fn push(&mut self, chunk: Bytes) {
+        if !chunk.is_empty() {
+            self.remaining += chunk.len();
+            self.queue.push_back(chunk);
+        }
+    }
+}
+
+impl Buf for Chunks {
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.queue.front().map_or(&[], |chunk| &chunk[..])
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.queue
+            .iter()
+            .zip(dst.iter_mut())
+            .map(|(chunk, slot)| *slot = IoSlice::new(chunk))
+            .count()
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.remaining, "cannot advance past end of buffer");
+        self.remaining -= cnt;
+
+        while cnt > 0 {
+            let front = self.queue.front_mut().expect("remaining bytes are queued");
+            if cnt < front.len() {
+                front.advance(cnt);
+                return;
+            }
+            cnt -= front.len();
+            self.queue.pop_front();
+        }
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        match self.queue.front_mut() {
+            Some(front) if len <= front.len() => {
+                self.remaining -= len;
+                let bytes = front.split_to(len);
+                if front.is_empty() {
+                    self.queue.pop_front();
+                }
+                bytes
+            }
+            _ => {
+                assert!(len <= self.remaining, "cannot advance past end of buffer");
+                let mut bytes = bytes::BytesMut::with_capacity(len);
+                bytes::BufMut::put(&mut bytes, self.take(len));
+                bytes.freeze()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkedBuf;
+    use crate::{Bytes, Error, FromBuf, SafeBuf};
+
+    #[derive(Debug, PartialEq)]
+    struct Message(Bytes);
+
+    impl FromBuf for Message {
+        fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+        where
+            B: bytes::Buf,
+        {
+            let len = SafeBuf::try_get_u8(&mut buffer)?;
+            Ok(Self(buffer.try_copy_to_bytes(len.into())?))
+        }
+    }
+
+    fn chunks(chunks: &[&'static [u8]]) -> Vec<Bytes> {
+        chunks
+            .iter()
+            .map(|chunk| Bytes::from_static(chunk))
+            .collect()
+    }
+
+    #[test]
+    fn across_chunks() {
+        let source = chunks(&[&[0], &[], &[0, 1], &[2, 0xFF]]);
+        let mut buffer = ChunkedBuf::new(source, 16);
+
+        assert!(buffer.try_get_u16().is_err());
+        buffer.fill(4).unwrap();
+        assert_eq!(buffer.try_get_u32().unwrap(), 0x0102);
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+    }
+
+    #[test]
+    fn extract() {
+        let source = chunks(&[&[3, 1], &[2], &[3, 5, 9]]);
+        let mut buffer = ChunkedBuf::new(source, 16);
+
+        assert_eq!(
+            buffer.extract::<Message>().unwrap(),
+            Message(Bytes::from_static(&[1, 2, 3]))
+        );
+
+        let error = buffer.extract::<Message>().unwrap_err();
+        assert!(matches!(error, Error::Truncated(_)));
+        assert_eq!(bytes::Buf::remaining(&buffer), 2);
+    }
+
+    #[test]
+    fn max_buffered() {
+        let source = chunks(&[&[0; 4], &[0; 4]]);
+        let mut buffer = ChunkedBuf::new(source, 4);
+
+        assert!(matches!(
+            buffer.fill(5).unwrap_err(),
+            Error::LimitExceeded(_)
+        ));
+    }
+
+    #[test]
+    fn splits_final_chunk() {
+        let source = chunks(&[&[0, 1, 2, 3], &[4, 5, 6, 7]]);
+        let mut buffer = ChunkedBuf::new(source, 6);
+
+        buffer.fill(5).unwrap();
+        assert_eq!(bytes::Buf::remaining(&buffer), 6);
+        assert_eq!(
+            buffer.try_copy_to_bytes(6).unwrap(),
+            &[0, 1, 2, 3, 4, 5][..]
+        );
+
+        buffer.fill(2).unwrap();
+        assert_eq!(buffer.try_get_u16().unwrap(), 0x0607);
+        assert!(buffer.fill(1).is_err());
+    }
+
+    #[test]
+    fn overrun_region() {
+        #[derive(Debug)]
+        struct Envelope;
+
+        impl FromBuf for Envelope {
+            fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+            where
+                B: bytes::Buf,
+            {
+                let len = SafeBuf::try_get_u8(&mut buffer)?;
+                let mut region = buffer.try_take_buf(len.into())?;
+                Message::from_buf(&mut region)?;
+                Ok(Self)
+            }
+        }
+
+        // the message is longer than the envelope, which no more chunks can fix
+        let source = core::iter::once(Bytes::from_static(&[3, 5, 1, 2]))
+            .chain(core::iter::repeat(Bytes::from_static(&[0])));
+        let mut buffer = ChunkedBuf::new(source, 16);
+
+        assert_eq!(
+            buffer.extract::<Envelope>().unwrap_err(),
+            crate::error::Truncated::new(5, 2).into()
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 4);
+    }
+}
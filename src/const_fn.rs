@@ -0,0 +1,132 @@
+//! `const fn` readers over byte slices
+//!
+//! These mirror the `try_get_*_at` methods of [`SafeBuf`](crate::SafeBuf),
+//! returning [`None`] rather than an error if the slice is too short, so that
+//! fixed headers and embedded tables can be parsed at compile time with the
+//! same bounds-checking semantics as the runtime API.
+//!
+//! ```
+//! use safer_bytes::const_fn;
+//!
+//! const HEADER: &[u8] = &[0xCA, 0xFE, 0x00, 0x02];
+//! const MAGIC: Option<u16> = const_fn::get_u16_at(HEADER, 0);
+//! const VERSION: Option<u16> = const_fn::get_u16_at(HEADER, 2);
+//!
+//! assert_eq!(MAGIC, Some(0xCAFE));
+//! assert_eq!(VERSION, Some(2));
+//! assert_eq!(const_fn::get_u32_at(HEADER, 1), None);
+//! ```
+
+use paste::paste;
+
+/// Copy `$width` bytes starting at `offset` into an array, if they are in
+/// bounds
+macro_rules! read_array {
+    ($bytes:expr, $offset:expr, $width:literal) => {{
+        let bytes: &[u8] = $bytes;
+        let offset: usize = $offset;
+        if offset > bytes.len() || bytes.len() - offset < $width {
+            return None;
+        }
+
+        let mut array = [0; $width];
+        let mut i = 0;
+        while i < $width {
+            array[i] = bytes[offset + i];
+            i += 1;
+        }
+        array
+    }};
+}
+
+macro_rules! const_getters {
+    ($($t:ty: $width:literal),*) => {
+        paste! {
+            $(
+                #[doc = "Read a big-endian `" $t "` from `bytes`, starting `offset` bytes from the front"]
+                #[doc = ""]
+                #[doc = "Returns [`None`] if there are fewer than " $width " bytes after `offset`."]
+                #[must_use]
+                pub const fn [<get_ $t _at>](bytes: &[u8], offset: usize) -> Option<$t> {
+                    Some($t::from_be_bytes(read_array!(bytes, offset, $width)))
+                }
+
+                #[doc = "Read a little-endian `" $t "` from `bytes`, starting `offset` bytes from the front"]
+                #[doc = ""]
+                #[doc = "Returns [`None`] if there are fewer than " $width " bytes after `offset`."]
+                #[must_use]
+                pub const fn [<get_ $t _le_at>](bytes: &[u8], offset: usize) -> Option<$t> {
+                    Some($t::from_le_bytes(read_array!(bytes, offset, $width)))
+                }
+            )*
+        }
+    };
+}
+
+/// Read a `u8` from `bytes`, `offset` bytes from the front
+///
+/// Returns [`None`] if `offset` is out of bounds.
+#[must_use]
+pub const fn get_u8_at(bytes: &[u8], offset: usize) -> Option<u8> {
+    if offset < bytes.len() {
+        Some(bytes[offset])
+    } else {
+        None
+    }
+}
+
+/// Read an `i8` from `bytes`, `offset` bytes from the front
+///
+/// Returns [`None`] if `offset` is out of bounds.
+#[must_use]
+pub const fn get_i8_at(bytes: &[u8], offset: usize) -> Option<i8> {
+    if offset < bytes.len() {
+        Some(i8::from_be_bytes([bytes[offset]]))
+    } else {
+        None
+    }
+}
+
+const_getters!(u16: 2, i16: 2, u32: 4, i32: 4, u64: 8, i64: 8);
+
+#[cfg(test)]
+mod tests {
+    use super::{get_i16_le_at, get_i8_at, get_u16_at, get_u32_le_at, get_u64_at, get_u8_at};
+    use crate::SafeBuf;
+
+    const TABLE: &[u8] = &[0xFF, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+    const FIRST: Option<u8> = get_u8_at(TABLE, 0);
+    const WORD: Option<u32> = get_u32_le_at(TABLE, 1);
+
+    #[test]
+    fn const_evaluation() {
+        assert_eq!(FIRST, Some(0xFF));
+        assert_eq!(WORD, Some(0x0403_0201));
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(get_u8_at(TABLE, 9), None);
+        assert_eq!(get_i8_at(TABLE, 0), Some(-1));
+        assert_eq!(get_u64_at(TABLE, 1), Some(0x0102_0304_0506_0708));
+        assert_eq!(get_u64_at(TABLE, 2), None);
+        assert_eq!(get_u16_at(TABLE, usize::MAX), None);
+        assert_eq!(get_i16_le_at(TABLE, 7), Some(0x0807));
+    }
+
+    #[test]
+    fn matches_runtime_api() {
+        let buffer = TABLE;
+        for offset in 0..=TABLE.len() {
+            assert_eq!(
+                get_u16_at(TABLE, offset),
+                buffer.try_get_u16_at(offset).ok()
+            );
+            assert_eq!(
+                get_u32_le_at(TABLE, offset),
+                buffer.try_get_u32_le_at(offset).ok()
+            );
+        }
+    }
+}
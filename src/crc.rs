@@ -0,0 +1,98 @@
+//! Cyclic redundancy checks, for verifying checksummed payloads
+//!
+//! These back
+//! [`SafeBuf::try_take_with_crc32`](crate::SafeBuf::try_take_with_crc32)
+//! and [`SafeBuf::try_take_with_crc16`](crate::SafeBuf::try_take_with_crc16),
+//! but are exposed for computing the checksums of payloads being written.
+//!
+//! ```
+//! use safer_bytes::crc;
+//!
+//! assert_eq!(crc::crc32(b"123456789"), 0xCBF4_3926);
+//! assert_eq!(crc::crc16_ccitt(b"123456789"), 0x29B1);
+//! ```
+
+/// Lookup table for the reflected CRC-32 polynomial
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// Lookup table for the CRC-16/CCITT polynomial
+const CRC16_TABLE: [u16; 256] = crc16_table();
+
+#[allow(clippy::cast_possible_truncation)]
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[allow(clippy::cast_possible_truncation)]
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x1021
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// The CRC-32 used by Ethernet, zlib, PNG and many others (CRC-32/ISO-HDLC)
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[usize::from(crc as u8 ^ byte)] ^ (crc >> 8)
+    })
+}
+
+/// The CRC-16/CCITT variant with an initial value of `0xFFFF`
+/// (CRC-16/IBM-3740, also known as CRC-16/CCITT-FALSE)
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |crc, &byte| {
+        CRC16_TABLE[usize::from((crc >> 8) as u8 ^ byte)] ^ (crc << 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16_ccitt, crc32};
+
+    #[test]
+    fn check_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+
+        assert_eq!(crc16_ccitt(b""), 0xFFFF);
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+    }
+}
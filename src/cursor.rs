@@ -0,0 +1,160 @@
+//! A position-tracking wrapper around a [`bytes::Buf`]
+
+use crate::{error, SafeBuf};
+use bytes::{Buf, Bytes};
+
+/// A wrapper around a [`Buf`] which tracks how many bytes have been consumed
+///
+/// The absolute position in the stream can be used to produce diagnostics
+/// which point at the byte where parsing failed (see
+/// [`SafeCursor::locate`]), or to compute alignment padding (see
+/// [`SafeCursor::try_align`]).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SafeCursor<B> {
+    inner: B,
+    start: usize,
+    consumed: usize,
+}
+
+impl<B> SafeCursor<B> {
+    /// Wrap a buffer, treating its current position as the start of the
+    /// stream
+    pub fn new(inner: B) -> Self {
+        Self::with_offset(inner, 0)
+    }
+
+    /// Wrap a buffer whose current position is `offset` bytes into the stream
+    pub fn with_offset(inner: B, offset: usize) -> Self {
+        Self {
+            inner,
+            start: offset,
+            consumed: 0,
+        }
+    }
+
+    /// The absolute position of the cursor in the stream
+    pub fn position(&self) -> usize {
+        self.start + self.consumed
+    }
+
+    /// The number of bytes consumed since the buffer was wrapped
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the cursor, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> SafeCursor<B>
+where
+    B: Buf,
+{
+    /// Run a fallible parse against the cursor, annotating any error with the
+    /// position at which the parse began
+    ///
+    /// # Errors
+    ///
+    /// This method will return any error returned by the closure, along with
+    /// the position of the cursor before the closure was run
+    pub fn locate<T, E, F>(&mut self, f: F) -> core::result::Result<T, error::Located<E>>
+    where
+        F: FnOnce(&mut Self) -> core::result::Result<T, E>,
+    {
+        let offset = self.position();
+        f(self).map_err(|error| error::Located { offset, error })
+    }
+
+    /// Skip the padding needed to reach the next `alignment`-byte boundary,
+    /// relative to the start of the stream
+    ///
+    /// Returns the number of padding bytes that were skipped. See
+    /// [`SafeBuf::try_align_to`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    pub fn try_align(&mut self, alignment: usize) -> core::result::Result<usize, error::Truncated> {
+        let position = self.position();
+        self.try_align_to(position, alignment)
+    }
+}
+
+impl<B> Buf for SafeCursor<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [std::io::IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.advance(cnt);
+        self.consumed += cnt;
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        let bytes = self.inner.copy_to_bytes(len);
+        self.consumed += len;
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SafeCursor;
+    use crate::{error, SafeBuf};
+
+    #[test]
+    fn tracks_position() {
+        let mut cursor = SafeCursor::with_offset(&[0_u8, 1, 2, 3, 4, 5, 6, 7][..], 16);
+
+        cursor.try_get_u16().unwrap();
+        cursor.try_copy_to_bytes(1).unwrap();
+        assert_eq!(cursor.consumed(), 3);
+        assert_eq!(cursor.position(), 19);
+
+        assert_eq!(cursor.try_align(4), Ok(1));
+        assert_eq!(cursor.position(), 20);
+    }
+
+    #[test]
+    fn locate() {
+        let mut cursor = SafeCursor::new(&[0_u8, 1, 2, 3, 4, 5][..]);
+
+        cursor.try_get_u16().unwrap();
+        let result = cursor.locate(|c| {
+            c.try_get_u16()?;
+            c.try_get_u32()
+        });
+
+        assert_eq!(
+            result,
+            Err(error::Located {
+                offset: 2,
+                error: error::Truncated::new(4, 2),
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "object truncated (needed 4 bytes, but only 2 available) (at byte 0x2)"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! A [`bytes::Buf`] wrapper which limits the nesting depth of recursive
+//! parsers
+
+use crate::error;
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A wrapper around a [`Buf`] which tracks how deeply nested the current parse
+/// is
+///
+/// Recursive formats (nested TLV trees, self-describing binary formats, etc.)
+/// are naturally parsed with recursive functions. Given hostile input, these
+/// can recurse until the stack overflows. Wrapping each level of the parse in
+/// [`DepthLimited::nested`] bounds the recursion, failing with
+/// [`error::DepthLimitExceeded`] instead.
+///
+/// ```
+/// use safer_bytes::{DepthLimited, SafeBuf};
+///
+/// /// A tree where each node is a count of children, followed by the children
+/// fn count_nodes<B: bytes::Buf>(buffer: &mut DepthLimited<B>) -> safer_bytes::Result<usize> {
+///     buffer.nested(|buffer| {
+///         let children = buffer.try_get_u8()?;
+///         let mut nodes = 1;
+///         for _ in 0..children {
+///             nodes += count_nodes(buffer)?;
+///         }
+///         Ok(nodes)
+///     })
+/// }
+///
+/// let mut buffer = DepthLimited::new(&[2, 0, 1, 0][..], 8);
+/// assert_eq!(count_nodes(&mut buffer)?, 4);
+///
+/// let mut buffer = DepthLimited::new(&[1; 100][..], 8);
+/// assert!(count_nodes(&mut buffer).is_err());
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DepthLimited<B> {
+    inner: B,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<B> DepthLimited<B> {
+    /// Wrap a buffer, permitting parses to be nested up to `max_depth` levels
+    /// deep
+    pub fn new(inner: B, max_depth: usize) -> Self {
+        Self {
+            inner,
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// The current nesting depth
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The maximum nesting depth
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Run a parser one level deeper than the current parse
+    ///
+    /// # Errors
+    ///
+    /// This method will return [`error::DepthLimitExceeded`] (without running
+    /// the parser) if the maximum depth has already been reached, or any error
+    /// returned by the parser.
+    pub fn nested<T, F>(&mut self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&mut Self) -> crate::Result<T>,
+    {
+        if self.depth >= self.max_depth {
+            return Err(error::DepthLimitExceeded::new("structure", self.max_depth).into());
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+}
+
+impl<B> Buf for DepthLimited<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.advance(cnt);
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        self.inner.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DepthLimited;
+    use crate::{Error, SafeBuf};
+
+    #[test]
+    fn depth_is_restored() {
+        let mut buffer = DepthLimited::new(&[0_u8, 1][..], 1);
+
+        let result = buffer.nested(|buffer| {
+            assert_eq!(buffer.depth(), 1);
+            buffer.nested(|buffer| Ok(buffer.try_get_u8()?))
+        });
+        assert!(matches!(result.unwrap_err(), Error::DepthLimitExceeded(_)));
+        assert_eq!(buffer.depth(), 0);
+
+        assert_eq!(
+            buffer.nested(|buffer| Ok(buffer.try_get_u16()?)).unwrap(),
+            1
+        );
+    }
+}
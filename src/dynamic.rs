@@ -0,0 +1,418 @@
+//! Parsing driven by a schema defined at runtime
+//!
+//! A [`Schema`] is a sequence of named, typed fields. Executing it against a
+//! buffer produces a tree of [`Node`]s, each holding a [`Value`] and the range
+//! of bytes it was read from. This is the basis for tools such as protocol
+//! analysers and hex inspectors, where the layout isn't known until the
+//! program runs.
+//!
+//! Arrays and byte strings may take their length from an earlier integer
+//! field in the same record, referenced by name.
+//!
+//! ```
+//! use safer_bytes::{
+//!     dynamic::{FieldType, Length, Schema},
+//!     Endian,
+//! };
+//!
+//! let point = Schema::new()
+//!     .field("x", FieldType::I16(Endian::Big))
+//!     .field("y", FieldType::I16(Endian::Big));
+//!
+//! let schema = Schema::new().field("count", FieldType::U8).field(
+//!     "points",
+//!     FieldType::array(FieldType::Record(point), Length::Field("count".into())),
+//! );
+//!
+//! let mut buffer = &[0x02, 0x00, 0x01, 0xFF, 0xFF, 0x00, 0x03, 0x00, 0x04][..];
+//! let message = schema.parse(&mut buffer)?;
+//!
+//! let second = &message.get("points").unwrap().value().as_array().unwrap()[1];
+//! assert_eq!(second.get("y").unwrap().value().as_i64(), Some(4));
+//! assert_eq!(second.get("y").unwrap().range(), 7..9);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, error::ResultExt, Endian, Error, SafeBuf};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
+use bytes::{Buf, Bytes};
+use core::{convert::TryFrom, ops::Range};
+
+/// The length of an array or byte string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Length {
+    /// A fixed length
+    Fixed(usize),
+
+    /// The value of an earlier unsigned integer field in the same record
+    Field(String),
+}
+
+/// The type of a field in a [`Schema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    /// An unsigned 8-bit integer
+    U8,
+
+    /// An unsigned 16-bit integer
+    U16(Endian),
+
+    /// An unsigned 32-bit integer
+    U32(Endian),
+
+    /// An unsigned 64-bit integer
+    U64(Endian),
+
+    /// A signed 8-bit integer
+    I8,
+
+    /// A signed 16-bit integer
+    I16(Endian),
+
+    /// A signed 32-bit integer
+    I32(Endian),
+
+    /// A signed 64-bit integer
+    I64(Endian),
+
+    /// A string of raw bytes
+    Bytes(Length),
+
+    /// A sequence of elements of the same type
+    Array {
+        /// The type of each element
+        element: Box<FieldType>,
+
+        /// The number of elements
+        count: Length,
+    },
+
+    /// A nested record
+    Record(Schema),
+}
+
+impl FieldType {
+    /// An array of `count` elements of type `element`
+    #[must_use]
+    pub fn array(element: Self, count: Length) -> Self {
+        Self::Array {
+            element: Box::new(element),
+            count,
+        }
+    }
+}
+
+/// A runtime-defined record layout, made up of named fields in order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    fields: Vec<(String, FieldType)>,
+}
+
+impl Schema {
+    /// Construct a schema with no fields
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a field to the record
+    #[must_use]
+    pub fn field(mut self, name: impl Into<String>, ty: FieldType) -> Self {
+        self.fields.push((name.into(), ty));
+        self
+    }
+
+    /// The fields of the record, in order
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &FieldType)> + '_ {
+        self.fields.iter().map(|(name, ty)| (name.as_str(), ty))
+    }
+
+    /// Parse a record from the buffer
+    ///
+    /// The ranges of the nodes are relative to the position of the buffer
+    /// when this is called.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if a length references a field which is not an
+    /// earlier unsigned integer field of the same record, or if an array has
+    /// more elements than there are bytes remaining. The error is annotated
+    /// with the path to the offending field.
+    pub fn parse<B>(&self, buffer: &mut B) -> crate::Result<Node>
+    where
+        B: Buf,
+    {
+        let start = buffer.remaining();
+        Parser { buffer, start }.record(self)
+    }
+}
+
+/// A value read from the buffer, and the bytes it was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    range: Range<usize>,
+    value: Value,
+}
+
+impl Node {
+    /// The offsets of the bytes the value was read from
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The value
+    #[must_use]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Look up a field, if this is a record
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Self> {
+        self.value.get(name)
+    }
+}
+
+/// A value produced by executing a [`Schema`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// An unsigned integer
+    Unsigned(u64),
+
+    /// A signed integer
+    Signed(i64),
+
+    /// A string of raw bytes
+    Bytes(Bytes),
+
+    /// The elements of an array
+    Array(Vec<Node>),
+
+    /// The named fields of a record, in order
+    Record(Vec<(String, Node)>),
+}
+
+impl Value {
+    /// The value of an unsigned integer
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Unsigned(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a signed integer
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Signed(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The contents of a byte string
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<&Bytes> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The elements of an array
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[Node]> {
+        match self {
+            Self::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Look up a field, if this is a record
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Node> {
+        match self {
+            Self::Record(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, node)| node),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a, B> {
+    buffer: &'a mut B,
+    start: usize,
+}
+
+impl<B> Parser<'_, B>
+where
+    B: Buf,
+{
+    fn offset(&self) -> usize {
+        self.start - self.buffer.remaining()
+    }
+
+    fn record(&mut self, schema: &Schema) -> crate::Result<Node> {
+        let begin = self.offset();
+        let mut fields: Vec<(String, Node)> = Vec::with_capacity(schema.fields.len());
+        for (name, ty) in &schema.fields {
+            let node = self.node(ty, &fields).context(name.clone())?;
+            fields.push((name.clone(), node));
+        }
+
+        Ok(Node {
+            range: begin..self.offset(),
+            value: Value::Record(fields),
+        })
+    }
+
+    fn node(&mut self, ty: &FieldType, siblings: &[(String, Node)]) -> crate::Result<Node> {
+        let begin = self.offset();
+        let buffer = &mut *self.buffer;
+        let value = match ty {
+            FieldType::U8 => Value::Unsigned(SafeBuf::try_get_u8(buffer)?.into()),
+            FieldType::U16(Endian::Big) => Value::Unsigned(SafeBuf::try_get_u16(buffer)?.into()),
+            FieldType::U16(Endian::Little) => {
+                Value::Unsigned(SafeBuf::try_get_u16_le(buffer)?.into())
+            }
+            FieldType::U32(Endian::Big) => Value::Unsigned(SafeBuf::try_get_u32(buffer)?.into()),
+            FieldType::U32(Endian::Little) => {
+                Value::Unsigned(SafeBuf::try_get_u32_le(buffer)?.into())
+            }
+            FieldType::U64(Endian::Big) => Value::Unsigned(SafeBuf::try_get_u64(buffer)?),
+            FieldType::U64(Endian::Little) => Value::Unsigned(SafeBuf::try_get_u64_le(buffer)?),
+            FieldType::I8 => Value::Signed(SafeBuf::try_get_i8(buffer)?.into()),
+            FieldType::I16(Endian::Big) => Value::Signed(SafeBuf::try_get_i16(buffer)?.into()),
+            FieldType::I16(Endian::Little) => {
+                Value::Signed(SafeBuf::try_get_i16_le(buffer)?.into())
+            }
+            FieldType::I32(Endian::Big) => Value::Signed(SafeBuf::try_get_i32(buffer)?.into()),
+            FieldType::I32(Endian::Little) => {
+                Value::Signed(SafeBuf::try_get_i32_le(buffer)?.into())
+            }
+            FieldType::I64(Endian::Big) => Value::Signed(SafeBuf::try_get_i64(buffer)?),
+            FieldType::I64(Endian::Little) => Value::Signed(SafeBuf::try_get_i64_le(buffer)?),
+            FieldType::Bytes(len) => {
+                let len = resolve(len, siblings)?;
+                Value::Bytes(SafeBuf::try_copy_to_bytes(buffer, len)?)
+            }
+            FieldType::Array { element, count } => {
+                let count = resolve(count, siblings)?;
+                error::LimitExceeded::check("array length", count, buffer.remaining())?;
+
+                let mut elements = Vec::with_capacity(count);
+                for index in 0..count {
+                    let node = self
+                        .node(element, &[])
+                        .with_context(|| format!("[{}]", index))?;
+                    elements.push(node);
+                }
+                Value::Array(elements)
+            }
+            FieldType::Record(schema) => return self.record(schema),
+        };
+
+        Ok(Node {
+            range: begin..self.offset(),
+            value,
+        })
+    }
+}
+
+/// Resolve a length, which may refer to an earlier field of the record
+fn resolve(len: &Length, siblings: &[(String, Node)]) -> crate::Result<usize> {
+    let name = match len {
+        Length::Fixed(len) => return Ok(*len),
+        Length::Field(name) => name,
+    };
+
+    let value = siblings
+        .iter()
+        .find(|(field, _)| field == name)
+        .ok_or(Error::Deserialization("length refers to an unknown field"))?
+        .1
+        .value
+        .as_u64()
+        .ok_or(Error::Deserialization(
+            "length refers to a field which is not an unsigned integer",
+        ))?;
+    usize::try_from(value).map_err(|_| Error::invalid("length", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldType, Length, Schema, Value};
+    use crate::{error, Endian, Error};
+
+    fn tlv_list() -> Schema {
+        let entry = Schema::new()
+            .field("tag", FieldType::U8)
+            .field("len", FieldType::U16(Endian::Little))
+            .field("value", FieldType::Bytes(Length::Field("len".into())));
+
+        Schema::new()
+            .field("magic", FieldType::Bytes(Length::Fixed(2)))
+            .field("count", FieldType::U8)
+            .field(
+                "entries",
+                FieldType::array(FieldType::Record(entry), Length::Field("count".into())),
+            )
+    }
+
+    #[test]
+    fn nested_offsets() {
+        let mut buffer = &[b'T', b'L', 2, 7, 1, 0, 0xAA, 9, 0, 0, 0xFF][..];
+
+        let root = tlv_list().parse(&mut buffer).unwrap();
+        assert_eq!(buffer, &[0xFF]);
+        assert_eq!(root.range(), 0..10);
+
+        let entries = root.get("entries").unwrap();
+        assert_eq!(entries.range(), 3..10);
+        let entries = entries.value().as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let value = entries[0].get("value").unwrap();
+        assert_eq!(value.range(), 6..7);
+        assert_eq!(value.value(), &Value::Bytes(vec![0xAA].into()));
+
+        assert_eq!(entries[1].range(), 7..10);
+        assert_eq!(entries[1].get("tag").unwrap().value().as_u64(), Some(9));
+        assert!(entries[1].get("missing").is_none());
+    }
+
+    #[test]
+    fn errors_have_paths() {
+        let error = tlv_list()
+            .parse(&mut &[b'T', b'L', 2, 7, 1, 0, 0xAA, 9, 5, 0, 0xFF][..])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "entries[1].value: object truncated (needed 5 bytes, but only 1 available)"
+        );
+        assert_eq!(error.root(), &error::Truncated::new(5, 1).into());
+
+        let schema = Schema::new().field("count", FieldType::I8).field(
+            "items",
+            FieldType::array(FieldType::U8, Length::Field("count".into())),
+        );
+        assert!(matches!(
+            schema.parse(&mut &[1, 1][..]).unwrap_err().root(),
+            Error::Deserialization(_)
+        ));
+
+        let schema = Schema::new().field(
+            "items",
+            FieldType::array(FieldType::U8, Length::Fixed(usize::MAX)),
+        );
+        assert_eq!(
+            schema.parse(&mut &[1, 1][..]).unwrap_err().root(),
+            &error::LimitExceeded::new("array length", usize::MAX, 2).into()
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Byte order configuration
+
+/// The byte order of a multi-byte integer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Most significant byte first (network byte order)
+    Big,
+
+    /// Least significant byte first
+    Little,
+}
+
+impl Default for Endian {
+    fn default() -> Self {
+        Self::Big
+    }
+}
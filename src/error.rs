@@ -1,30 +1,1114 @@
 //! Errors
+//!
+//! The error types only depend on `core` and `alloc`. Implementations of
+//! [`std::error::Error`], conversions into [`std::io::Error`], and
+//! [`Error::Custom`] require the `std` feature (enabled by default).
+
+use alloc::{borrow::Cow, boxed::Box, vec, vec::Vec};
+use bytes::Bytes;
+use core::{fmt, ops::RangeInclusive};
+#[cfg(feature = "std")]
+use std::{io, sync::Arc};
 
 /// Errors that can occur when deserialising objects from a buffer
-#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 #[non_exhaustive]
 pub enum Error {
     /// Tried to read something, but not enough bytes left in the buffer
-    #[error(transparent)]
-    Truncated(#[from] Truncated),
+    Truncated(Truncated),
 
-    /// Called Reader::should_be_exhausted(), but found bytes anyway.
-    #[error(transparent)]
-    ExtraneousBytes(#[from] ExtraneousBytes),
+    /// Called [`SafeBuf::should_be_exhausted`](crate::SafeBuf::should_be_exhausted), but found
+    /// bytes anyway.
+    ExtraneousBytes(ExtraneousBytes),
 
     /// An attempt to parse an object failed for some reason related to its
     /// contents.
-    #[error("deserialisation error: {0}")]
     Deserialization(&'static str),
+
+    /// A value was read from the buffer, but is not valid (a bad magic
+    /// number, an out-of-range value, an unknown discriminant, etc.)
+    InvalidValue(InvalidValue),
+
+    /// A read would have allocated or buffered more bytes than permitted
+    LimitExceeded(LimitExceeded),
+
+    /// Nested structures were more deeply nested than permitted
+    DepthLimitExceeded(DepthLimitExceeded),
+
+    /// The bytes in the buffer did not match a fixed sequence, such as a
+    /// magic number
+    Mismatch(Mismatch),
+
+    /// A version field held a version which is not supported
+    UnsupportedVersion(UnsupportedVersion),
+
+    /// A checksum did not match the checksum computed over the data it covers
+    ChecksumMismatch(ChecksumMismatch),
+
+    /// A user-defined error, for [`FromBuf`](crate::FromBuf) implementations
+    /// with domain-specific failures.
+    ///
+    /// This is constructed using [`Error::custom`].
+    #[cfg(feature = "std")]
+    Custom(Custom),
+
+    /// An error which occurred while parsing a named field or structure.
+    ///
+    /// This is constructed using [`Error::context`] or [`ResultExt`].
+    Context {
+        /// The path to the field which failed to parse, outermost first
+        path: Vec<Cow<'static, str>>,
+
+        /// The underlying error
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Annotate this error with the name of the field or structure being
+    /// parsed
+    ///
+    /// Context added by nested parsers is joined into a single path, so that
+    /// errors read like `message.body.entries[3].id: truncated`. Segments
+    /// starting with `[` are treated as indices, and are not separated by a
+    /// `.`.
+    #[must_use]
+    pub fn context(self, context: impl Into<Cow<'static, str>>) -> Self {
+        match self {
+            Self::Context { mut path, source } => {
+                path.insert(0, context.into());
+                Self::Context { path, source }
+            }
+            error => Self::Context {
+                path: vec![context.into()],
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Construct an [`Error::InvalidValue`] error
+    ///
+    /// `what` describes the value which was invalid (for example, `"magic
+    /// number"` or `"message type"`).
+    pub fn invalid(what: &'static str, value: impl Into<OffendingValue>) -> Self {
+        InvalidValue::new(what, value).into()
+    }
+
+    /// Construct an [`Error::Custom`] error from a user-defined error
+    ///
+    /// The original error is preserved, and can be recovered using
+    /// [`Custom::downcast_ref`].
+    #[cfg(feature = "std")]
+    pub fn custom(error: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        Custom(Arc::from(error.into())).into()
+    }
+
+    /// A stable code classifying this error
+    ///
+    /// Context is ignored, so an error has the same code as its
+    /// [`root`](Error::root).
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self.root() {
+            Self::Truncated(_) => ErrorCode::Truncated,
+            Self::ExtraneousBytes(_) => ErrorCode::ExtraneousBytes,
+            Self::Deserialization(_) => ErrorCode::Deserialization,
+            Self::InvalidValue(_) => ErrorCode::InvalidValue,
+            Self::LimitExceeded(_) => ErrorCode::LimitExceeded,
+            Self::DepthLimitExceeded(_) => ErrorCode::DepthLimitExceeded,
+            Self::Mismatch(_) => ErrorCode::Mismatch,
+            Self::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+            Self::ChecksumMismatch(_) => ErrorCode::ChecksumMismatch,
+            #[cfg(feature = "std")]
+            Self::Custom(_) => ErrorCode::Custom,
+            Self::Context { .. } => unreachable!("the root of an error never has context"),
+        }
+    }
+
+    /// The underlying error, with any context removed
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        let mut error = self;
+        while let Self::Context { source, .. } = error {
+            error = source;
+        }
+        error
+    }
+}
+
+/// Stable codes classifying the variants of [`Error`]
+///
+/// These are intended for FFI boundaries and metrics pipelines, where errors
+/// need to be classified without matching on strings. The numeric values (see
+/// [`ErrorCode::value`]) and names (see [`ErrorCode::name`]) will not change
+/// between releases, though new codes may be added.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum ErrorCode {
+    /// See [`Error::Truncated`]
+    Truncated = 1,
+
+    /// See [`Error::ExtraneousBytes`]
+    ExtraneousBytes = 2,
+
+    /// See [`Error::Deserialization`]
+    Deserialization = 3,
+
+    /// See [`Error::InvalidValue`]
+    InvalidValue = 4,
+
+    /// See `Error::Custom`
+    Custom = 5,
+
+    /// See [`Error::LimitExceeded`]
+    LimitExceeded = 6,
+
+    /// See [`Error::DepthLimitExceeded`]
+    DepthLimitExceeded = 7,
+
+    /// See [`Error::Mismatch`]
+    Mismatch = 8,
+
+    /// See [`Error::UnsupportedVersion`]
+    UnsupportedVersion = 9,
+
+    /// See [`Error::ChecksumMismatch`]
+    ChecksumMismatch = 10,
+}
+
+impl ErrorCode {
+    /// The stable numeric value of this code
+    #[must_use]
+    pub const fn value(self) -> u32 {
+        self as u32
+    }
+
+    /// The stable name of this code, suitable for use as a metrics label
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Truncated => "truncated",
+            Self::ExtraneousBytes => "extraneous_bytes",
+            Self::Deserialization => "deserialization",
+            Self::InvalidValue => "invalid_value",
+            Self::Custom => "custom",
+            Self::LimitExceeded => "limit_exceeded",
+            Self::DepthLimitExceeded => "depth_limit_exceeded",
+            Self::Mismatch => "mismatch",
+            Self::UnsupportedVersion => "unsupported_version",
+            Self::ChecksumMismatch => "checksum_mismatch",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(e) => e.fmt(f),
+            Self::ExtraneousBytes(e) => e.fmt(f),
+            Self::Deserialization(msg) => write!(f, "deserialisation error: {}", msg),
+            Self::InvalidValue(e) => e.fmt(f),
+            Self::LimitExceeded(e) => e.fmt(f),
+            Self::DepthLimitExceeded(e) => e.fmt(f),
+            Self::Mismatch(e) => e.fmt(f),
+            Self::UnsupportedVersion(e) => e.fmt(f),
+            Self::ChecksumMismatch(e) => e.fmt(f),
+            #[cfg(feature = "std")]
+            Self::Custom(e) => e.fmt(f),
+            Self::Context { path, source } => {
+                write_path(f, path)?;
+                write!(f, ": {}", source)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Custom(e) => e.source(),
+            Self::Context { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! error_from {
+    ($($variant:ident),*) => {
+        $(
+            impl From<$variant> for Error {
+                fn from(e: $variant) -> Self {
+                    Self::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+error_from!(
+    Truncated,
+    ExtraneousBytes,
+    InvalidValue,
+    LimitExceeded,
+    DepthLimitExceeded,
+    Mismatch,
+    UnsupportedVersion,
+    ChecksumMismatch
+);
+
+#[cfg(feature = "std")]
+error_from!(Custom);
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    /// Convert to an [`io::Error`]
+    ///
+    /// Truncation maps to [`io::ErrorKind::UnexpectedEof`], and all other
+    /// errors map to [`io::ErrorKind::InvalidData`]. The original error is
+    /// preserved as the inner error.
+    fn from(error: Error) -> Self {
+        let kind = match error.root() {
+            Error::Truncated(_) => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        Self::new(kind, error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Truncated> for io::Error {
+    fn from(error: Truncated) -> Self {
+        Error::from(error).into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ExtraneousBytes> for io::Error {
+    fn from(error: ExtraneousBytes) -> Self {
+        Error::from(error).into()
+    }
+}
+
+fn write_path(f: &mut fmt::Formatter<'_>, path: &[Cow<'static, str>]) -> fmt::Result {
+    for (i, segment) in path.iter().enumerate() {
+        if i > 0 && !segment.starts_with('[') {
+            f.write_str(".")?;
+        }
+        f.write_str(segment)?;
+    }
+    Ok(())
+}
+
+/// Extension trait for adding context to fallible results
+pub trait ResultExt<T> {
+    /// Annotate the error (if any) with the name of the field or structure
+    /// being parsed
+    ///
+    /// See [`Error::context`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return the original error, with the added context
+    fn context(self, context: impl Into<Cow<'static, str>>) -> Result<T, Error>;
+
+    /// Annotate the error (if any) with the name of the field or structure
+    /// being parsed, where the name is computed lazily
+    ///
+    /// This avoids allocating the name when the operation succeeds.
+    ///
+    /// # Errors
+    ///
+    /// This method will return the original error, with the added context
+    fn with_context<C, F>(self, f: F) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C;
+}
+
+impl<T, E> ResultExt<T> for Result<T, E>
+where
+    E: Into<Error>,
+{
+    fn context(self, context: impl Into<Cow<'static, str>>) -> Result<T, Error> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C, F>(self, f: F) -> Result<T, Error>
+    where
+        C: Into<Cow<'static, str>>,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|e| e.into().context(f()))
+    }
+}
+
+/// Collects recoverable errors during tolerant parsing
+///
+/// Lenient readers of slightly corrupted data may want to record problems
+/// with optional fields or unknown tags while continuing to parse, rather
+/// than aborting on the first error.
+///
+/// ```
+/// use safer_bytes::{error::ErrorSink, SafeBuf};
+///
+/// let mut buffer = &[0x01, 0x02][..];
+/// let mut sink = ErrorSink::new();
+///
+/// let flags = sink.recover(buffer.try_get_u8()).unwrap_or_default();
+/// let length = sink.recover(buffer.try_get_u32()).unwrap_or_default();
+///
+/// let partial = sink.finish((flags, length));
+/// assert_eq!(partial.value, (1, 0));
+/// assert_eq!(partial.errors.len(), 1);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorSink {
+    errors: Vec<Error>,
+}
+
+impl ErrorSink {
+    /// Construct a new, empty [`ErrorSink`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a recoverable error
+    pub fn push(&mut self, error: impl Into<Error>) {
+        self.errors.push(error.into());
+    }
+
+    /// Record the error from a result (if any), returning the value if the
+    /// result was successful
+    pub fn recover<T, E>(&mut self, result: Result<T, E>) -> Option<T>
+    where
+        E: Into<Error>,
+    {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    /// The errors which have been recorded so far
+    #[must_use]
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Whether any errors have been recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Consume the sink, pairing the recorded errors with the (partially)
+    /// parsed value
+    pub fn finish<T>(self, value: T) -> Partial<T> {
+        Partial {
+            value,
+            errors: self.errors,
+        }
+    }
+}
+
+/// A partially parsed value, along with any recoverable errors which
+/// occurred while parsing it
+///
+/// This is created by [`ErrorSink::finish`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partial<T> {
+    /// The parsed value
+    pub value: T,
+
+    /// The recoverable errors which occurred while parsing the value
+    pub errors: Vec<Error>,
+}
+
+impl<T> Partial<T> {
+    /// Whether the value was parsed without any errors
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Convert into a [`Result`], failing with the first recorded error (if
+    /// any)
+    ///
+    /// # Errors
+    ///
+    /// This method will return the first recorded error, if there is one
+    pub fn into_result(self) -> Result<T, Error> {
+        match self.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(self.value),
+        }
+    }
 }
 
 /// Tried to read something, but not enough bytes left in the buffer
-#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
-#[error("object truncated (or not fully present)")]
-pub struct Truncated;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Truncated {
+    /// The number of bytes needed by the read
+    pub needed: usize,
+
+    /// The number of bytes which were available
+    pub available: usize,
+}
+
+impl Truncated {
+    /// Construct a new [`Truncated`] error
+    #[must_use]
+    pub fn new(needed: usize, available: usize) -> Self {
+        Self { needed, available }
+    }
+
+    /// The number of additional bytes which would be needed for the read to
+    /// succeed
+    ///
+    /// Streaming callers can use this to wait for exactly the missing number of
+    /// bytes before retrying the parse.
+    #[must_use]
+    pub fn missing(&self) -> usize {
+        self.needed.saturating_sub(self.available)
+    }
+
+    /// Check that `needed` bytes are `available`
+    pub(crate) fn check(needed: usize, available: usize) -> Result<(), Self> {
+        if available < needed {
+            Err(Self::new(needed, available))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "object truncated (needed {} bytes, but only {} available)",
+            self.needed, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Truncated {}
+
+impl From<bytes::TryGetError> for Truncated {
+    fn from(e: bytes::TryGetError) -> Self {
+        Self::new(e.requested, e.available)
+    }
+}
+
+impl From<bytes::TryGetError> for Error {
+    fn from(e: bytes::TryGetError) -> Self {
+        Truncated::from(e).into()
+    }
+}
 
 #[rustfmt::skip]
 /// Called [`SafeBuf::should_be_exhausted`](crate::SafeBuf::should_be_exhausted), but found bytes remaining
-#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone, Copy)]
-#[error("extra bytes at end of object")]
-pub struct ExtraneousBytes;
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExtraneousBytes {
+    /// The number of bytes which were left over
+    pub remaining: usize,
+
+    preview: [u8; ExtraneousBytes::PREVIEW_LEN],
+    preview_len: usize,
+}
+
+impl ExtraneousBytes {
+    /// The maximum number of leftover bytes captured in the preview
+    pub const PREVIEW_LEN: usize = 8;
+
+    /// Construct a new [`ExtraneousBytes`] error, with no preview
+    #[must_use]
+    pub fn new(remaining: usize) -> Self {
+        Self {
+            remaining,
+            preview: [0; Self::PREVIEW_LEN],
+            preview_len: 0,
+        }
+    }
+
+    /// Construct a new [`ExtraneousBytes`] error, capturing (up to
+    /// [`ExtraneousBytes::PREVIEW_LEN`]) leading bytes of the leftovers
+    #[must_use]
+    pub fn with_preview(remaining: usize, leftovers: &[u8]) -> Self {
+        let preview_len = leftovers.len().min(Self::PREVIEW_LEN).min(remaining);
+        let mut preview = [0; Self::PREVIEW_LEN];
+        preview[..preview_len].copy_from_slice(&leftovers[..preview_len]);
+        Self {
+            remaining,
+            preview,
+            preview_len,
+        }
+    }
+
+    /// Construct a new [`ExtraneousBytes`] error describing the bytes left in
+    /// a buffer
+    pub(crate) fn from_buf<B>(buffer: &B) -> Self
+    where
+        B: bytes::Buf + ?Sized,
+    {
+        Self::with_preview(buffer.remaining(), buffer.chunk())
+    }
+
+    /// The first few bytes which were left over, if they were captured
+    #[must_use]
+    pub fn preview(&self) -> &[u8] {
+        &self.preview[..self.preview_len]
+    }
+}
+
+impl fmt::Display for ExtraneousBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "extra bytes at end of object ({} bytes remaining",
+            self.remaining
+        )?;
+        if self.preview_len > 0 {
+            write!(f, ", starting {}", OffendingValue::from(self.preview()))?;
+            if self.remaining > self.preview_len {
+                f.write_str(" ...")?;
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExtraneousBytes {}
+
+/// A read would have allocated or buffered more bytes than permitted
+///
+/// This guards against hostile length prefixes. A limit is checked before
+/// anything is allocated, so it is reported even if the buffer is truncated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LimitExceeded {
+    /// A description of what was being allocated or buffered
+    pub what: &'static str,
+
+    /// The number of bytes which were requested
+    pub requested: usize,
+
+    /// The maximum number of bytes permitted
+    pub limit: usize,
+}
+
+impl LimitExceeded {
+    /// Construct a new [`LimitExceeded`] error
+    #[must_use]
+    pub fn new(what: &'static str, requested: usize, limit: usize) -> Self {
+        Self {
+            what,
+            requested,
+            limit,
+        }
+    }
+
+    /// Check that `requested` bytes are within the `limit`
+    pub(crate) fn check(what: &'static str, requested: usize, limit: usize) -> Result<(), Self> {
+        if requested > limit {
+            Err(Self::new(what, requested, limit))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} bytes exceeds the limit of {} bytes",
+            self.what, self.requested, self.limit
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LimitExceeded {}
+
+/// Nested structures were more deeply nested than permitted
+///
+/// This guards recursive parsers against exhausting the stack when given
+/// hostile input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DepthLimitExceeded {
+    /// A description of what was nested
+    pub what: &'static str,
+
+    /// The maximum nesting depth permitted
+    pub limit: usize,
+}
+
+impl DepthLimitExceeded {
+    /// Construct a new [`DepthLimitExceeded`] error
+    #[must_use]
+    pub fn new(what: &'static str, limit: usize) -> Self {
+        Self { what, limit }
+    }
+}
+
+impl fmt::Display for DepthLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nested more than {} levels deep",
+            self.what, self.limit
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DepthLimitExceeded {}
+
+/// The bytes in the buffer did not match a fixed sequence
+///
+/// This is returned by
+/// [`SafeBuf::try_expect_bytes`](crate::SafeBuf::try_expect_bytes).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Mismatch {
+    /// The bytes which were expected
+    pub expected: Bytes,
+
+    /// The bytes which were found instead
+    pub found: Bytes,
+}
+
+impl Mismatch {
+    /// Construct a new [`Mismatch`] error
+    pub fn new(expected: impl Into<Bytes>, found: impl Into<Bytes>) -> Self {
+        Self {
+            expected: expected.into(),
+            found: found.into(),
+        }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {}, found {}",
+            OffendingValue::Bytes(self.expected.clone()),
+            OffendingValue::Bytes(self.found.clone())
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Mismatch {}
+
+/// A version field held a version which is not supported
+///
+/// This is returned by
+/// [`SafeBuf::extract_versioned`](crate::SafeBuf::extract_versioned).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnsupportedVersion {
+    /// The version which was read
+    pub version: u64,
+
+    /// The range of supported versions
+    pub supported: RangeInclusive<u64>,
+}
+
+impl UnsupportedVersion {
+    /// Construct a new [`UnsupportedVersion`] error
+    #[must_use]
+    pub fn new(version: u64, supported: RangeInclusive<u64>) -> Self {
+        Self { version, supported }
+    }
+}
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported version {} (supported versions are {} to {})",
+            self.version,
+            self.supported.start(),
+            self.supported.end()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsupportedVersion {}
+
+/// A checksum did not match the checksum computed over the data it covers
+///
+/// This is returned by
+/// [`SafeBuf::try_take_with_crc32`](crate::SafeBuf::try_take_with_crc32) and
+/// [`SafeBuf::try_take_with_crc16`](crate::SafeBuf::try_take_with_crc16).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ChecksumMismatch {
+    /// The checksum which was read from the buffer
+    pub expected: u32,
+
+    /// The checksum computed over the data
+    pub actual: u32,
+}
+
+impl ChecksumMismatch {
+    /// Construct a new [`ChecksumMismatch`] error
+    #[must_use]
+    pub fn new(expected: u32, actual: u32) -> Self {
+        Self { expected, actual }
+    }
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checksum mismatch (expected {:#x}, computed {:#x})",
+            self.expected, self.actual
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChecksumMismatch {}
+
+/// A value was read from the buffer, but is not valid
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InvalidValue {
+    /// A description of the value which was invalid
+    pub what: &'static str,
+
+    /// The offending value
+    pub value: OffendingValue,
+}
+
+impl InvalidValue {
+    /// Construct a new [`InvalidValue`] error
+    pub fn new(what: &'static str, value: impl Into<OffendingValue>) -> Self {
+        Self {
+            what,
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {}: {}", self.what, self.value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidValue {}
+
+/// The value carried by an [`InvalidValue`] error
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum OffendingValue {
+    /// An unsigned integer
+    Unsigned(u128),
+
+    /// A signed integer
+    Signed(i128),
+
+    /// A sequence of raw bytes
+    Bytes(Bytes),
+}
+
+impl fmt::Display for OffendingValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsigned(value) => write!(f, "{}", value),
+            Self::Signed(value) => write!(f, "{}", value),
+            Self::Bytes(bytes) => {
+                f.write_str("[")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+macro_rules! offending_value_from {
+    ($variant:ident, $($t:ty),*) => {
+        $(
+            impl From<$t> for OffendingValue {
+                fn from(value: $t) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+offending_value_from!(Unsigned, u8, u16, u32, u64, u128);
+offending_value_from!(Signed, i8, i16, i32, i64, i128);
+offending_value_from!(Bytes, Bytes, Vec<u8>);
+
+impl From<&[u8]> for OffendingValue {
+    fn from(value: &[u8]) -> Self {
+        Self::Bytes(Bytes::copy_from_slice(value))
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for OffendingValue {
+    fn from(value: [u8; N]) -> Self {
+        Self::Bytes(Bytes::copy_from_slice(&value))
+    }
+}
+
+/// A user-defined error
+///
+/// Two [`Custom`] errors compare equal only if they share the same underlying
+/// error.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct Custom(Arc<dyn std::error::Error + Send + Sync>);
+
+#[cfg(feature = "std")]
+impl Custom {
+    /// Get a reference to the underlying error
+    #[must_use]
+    pub fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+
+    /// Attempt to downcast the underlying error to a concrete type
+    #[must_use]
+    pub fn downcast_ref<E>(&self) -> Option<&E>
+    where
+        E: std::error::Error + 'static,
+    {
+        self.0.downcast_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Custom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Custom {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for Custom {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for Custom {}
+
+/// An error annotated with the offset in the stream at which it occurred
+///
+/// This is returned by [`SafeCursor::locate`](crate::SafeCursor::locate).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Located<E> {
+    /// The offset in the stream at which the failed read began
+    pub offset: usize,
+
+    /// The underlying error
+    pub error: E,
+}
+
+impl<E> fmt::Display for Located<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at byte {:#x})", self.error, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for Located<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChecksumMismatch, DepthLimitExceeded, Error, ErrorCode, ErrorSink, ExtraneousBytes,
+        LimitExceeded, Mismatch, ResultExt, Truncated, UnsupportedVersion,
+    };
+
+    #[test]
+    fn invalid_value() {
+        assert_eq!(
+            Error::invalid("message type", 7_u8).to_string(),
+            "invalid message type: 7"
+        );
+        assert_eq!(
+            Error::invalid("magic number", [0xCA, 0xFE]).to_string(),
+            "invalid magic number: [ca fe]"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn custom() {
+        #[derive(Debug, PartialEq)]
+        struct UnknownCodec;
+
+        impl std::fmt::Display for UnknownCodec {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("unknown codec")
+            }
+        }
+
+        impl std::error::Error for UnknownCodec {}
+
+        let error = Error::custom(UnknownCodec);
+        assert_eq!(error.to_string(), "unknown codec");
+        assert_eq!(error, error.clone());
+
+        match error {
+            Error::Custom(custom) => assert_eq!(custom.downcast_ref(), Some(&UnknownCodec)),
+            _ => panic!("expected a custom error"),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn into_io_error() {
+        let error = std::io::Error::from(Error::from(Truncated::new(4, 2)).context("header"));
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            error.to_string(),
+            "header: object truncated (needed 4 bytes, but only 2 available)"
+        );
+
+        let error = std::io::Error::from(Error::invalid("message type", 7_u8));
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        let error = std::io::Error::from(ExtraneousBytes::new(1));
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn extraneous_bytes() {
+        assert_eq!(
+            ExtraneousBytes::new(1).to_string(),
+            "extra bytes at end of object (1 bytes remaining)"
+        );
+
+        let error = ExtraneousBytes::with_preview(4096, &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(error.preview(), &[0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            error.to_string(),
+            "extra bytes at end of object (4096 bytes remaining, starting [de ad be ef] ...)"
+        );
+    }
+
+    #[test]
+    fn code() {
+        let error = Error::from(Truncated::new(4, 2)).context("header");
+        assert_eq!(error.code(), ErrorCode::Truncated);
+        assert_eq!(error.code().value(), 1);
+        assert_eq!(error.code().name(), "truncated");
+
+        let error = Error::invalid("message type", 7_u8);
+        assert_eq!(error.code().value(), 4);
+
+        let error = Error::from(LimitExceeded::new("allocation", 8, 4));
+        assert_eq!(error.code().name(), "limit_exceeded");
+        assert_eq!(
+            error.to_string(),
+            "allocation of 8 bytes exceeds the limit of 4 bytes"
+        );
+
+        let error = Error::from(DepthLimitExceeded::new("group", 4));
+        assert_eq!(error.code().value(), 7);
+        assert_eq!(error.to_string(), "group nested more than 4 levels deep");
+
+        let error = Error::from(Mismatch::new(&b"GIF8"[..], &b"\x89PNG"[..]));
+        assert_eq!(error.code().name(), "mismatch");
+        assert_eq!(
+            error.to_string(),
+            "expected [47 49 46 38], found [89 50 4e 47]"
+        );
+
+        let error = Error::from(UnsupportedVersion::new(3, 1..=2));
+        assert_eq!(error.code().value(), 9);
+        assert_eq!(
+            error.to_string(),
+            "unsupported version 3 (supported versions are 1 to 2)"
+        );
+
+        let error = Error::from(ChecksumMismatch::new(0xCBF4_3926, 0x414F_A339));
+        assert_eq!(error.code().name(), "checksum_mismatch");
+        assert_eq!(
+            error.to_string(),
+            "checksum mismatch (expected 0xcbf43926, computed 0x414fa339)"
+        );
+    }
+
+    #[test]
+    fn error_sink() {
+        let mut sink = ErrorSink::new();
+        assert_eq!(sink.recover::<_, Truncated>(Ok(1)), Some(1));
+        assert!(sink.is_empty());
+
+        assert_eq!(sink.recover::<u8, _>(Err(Truncated::new(4, 2))), None);
+        sink.push(Error::invalid("tag", 9_u8));
+        assert_eq!(sink.errors().len(), 2);
+
+        let partial = sink.finish(());
+        assert!(!partial.is_clean());
+        assert_eq!(
+            partial.into_result(),
+            Err(Error::Truncated(Truncated::new(4, 2)))
+        );
+    }
+
+    #[test]
+    fn context_path() {
+        let result: Result<(), _> = Err(Truncated::new(4, 2));
+
+        let error = result
+            .context("id")
+            .with_context(|| format!("[{}]", 3))
+            .context("entries")
+            .context("body")
+            .context("message")
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "message.body.entries[3].id: object truncated (needed 4 bytes, but only 2 available)"
+        );
+        assert_eq!(error.root(), &Error::Truncated(Truncated::new(4, 2)));
+    }
+}
@@ -0,0 +1,232 @@
+//! Reading [`bitflags`](https://docs.rs/bitflags/1) types
+//!
+//! Flag fields are usually read as an integer, and then validated against the
+//! set of known flags. [`SafeBuf::extract_flags`] does both, with an
+//! [`UnknownBits`] policy for the bits which don't correspond to a defined
+//! flag.
+//!
+//! `bitflags` types don't share a trait, so the [`Flags`] trait is implemented
+//! for them with the [`impl_flags!`](crate::impl_flags) macro.
+//!
+//! ```
+//! use bitflags::bitflags;
+//! use safer_bytes::{flags::UnknownBits, SafeBuf};
+//!
+//! bitflags! {
+//!     struct Permissions: u16 {
+//!         const READ = 0b001;
+//!         const WRITE = 0b010;
+//!         const EXECUTE = 0b100;
+//!     }
+//! }
+//!
+//! safer_bytes::impl_flags!(Permissions: u16);
+//!
+//! let mut buffer = &[0x00, 0x03, 0x80, 0x01][..];
+//!
+//! let permissions: Permissions = buffer.extract_flags(UnknownBits::Reject)?;
+//! assert_eq!(permissions, Permissions::READ | Permissions::WRITE);
+//!
+//! assert!(buffer
+//!     .extract_flags::<Permissions>(UnknownBits::Reject)
+//!     .is_err());
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, Error, SafeBuf};
+use bytes::Buf;
+
+/// What to do with bits which don't correspond to a defined flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnknownBits {
+    /// Return an error
+    Reject,
+
+    /// Discard the unknown bits
+    Truncate,
+
+    /// Keep the unknown bits, so that they are written back out unchanged
+    Preserve,
+}
+
+/// The integer representation of a set of flags
+pub trait FlagBits: Copy + Into<error::OffendingValue> {
+    /// Read a big-endian value
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_get<B>(buffer: &mut B) -> Result<Self, error::Truncated>
+    where
+        B: Buf;
+
+    /// Read a little-endian value
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_get_le<B>(buffer: &mut B) -> Result<Self, error::Truncated>
+    where
+        B: Buf;
+}
+
+macro_rules! flag_bits {
+    ($($t:ty: $be:ident, $le:ident),*) => {
+        $(
+            impl FlagBits for $t {
+                fn try_get<B>(buffer: &mut B) -> Result<Self, error::Truncated>
+                where
+                    B: Buf,
+                {
+                    SafeBuf::$be(buffer)
+                }
+
+                fn try_get_le<B>(buffer: &mut B) -> Result<Self, error::Truncated>
+                where
+                    B: Buf,
+                {
+                    SafeBuf::$le(buffer)
+                }
+            }
+        )*
+    };
+}
+
+flag_bits!(
+    u8: try_get_u8, try_get_u8,
+    u16: try_get_u16, try_get_u16_le,
+    u32: try_get_u32, try_get_u32_le,
+    u64: try_get_u64, try_get_u64_le
+);
+
+/// A set of flags which can be read from a buffer
+///
+/// This is implemented for `bitflags` types with the
+/// [`impl_flags!`](crate::impl_flags) macro.
+pub trait Flags: Sized {
+    /// The integer representation of the flags
+    type Bits: FlagBits;
+
+    /// Convert from the integer representation, unless it contains unknown
+    /// bits
+    fn from_bits(bits: Self::Bits) -> Option<Self>;
+
+    /// Convert from the integer representation, discarding unknown bits
+    fn from_bits_truncate(bits: Self::Bits) -> Self;
+
+    /// Convert from the integer representation, keeping unknown bits
+    fn from_bits_preserve(bits: Self::Bits) -> Self;
+
+    /// Convert from the integer representation, according to `unknown`
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `bits` contains unknown bits, and
+    /// `unknown` is [`UnknownBits::Reject`]
+    fn from_bits_with(bits: Self::Bits, unknown: UnknownBits) -> crate::Result<Self> {
+        match unknown {
+            UnknownBits::Reject => {
+                Self::from_bits(bits).ok_or_else(|| Error::invalid("flags", bits))
+            }
+            UnknownBits::Truncate => Ok(Self::from_bits_truncate(bits)),
+            UnknownBits::Preserve => Ok(Self::from_bits_preserve(bits)),
+        }
+    }
+}
+
+/// Implement [`Flags`] for a type declared with `bitflags!`
+///
+/// This takes the name of the type, and its integer representation (one of
+/// `u8`, `u16`, `u32` or `u64`).
+///
+/// ```
+/// use bitflags::bitflags;
+///
+/// bitflags! {
+///     struct TcpFlags: u8 {
+///         const FIN = 0x01;
+///         const SYN = 0x02;
+///         const RST = 0x04;
+///         const ACK = 0x10;
+///     }
+/// }
+///
+/// safer_bytes::impl_flags!(TcpFlags: u8);
+/// ```
+#[macro_export]
+macro_rules! impl_flags {
+    ($name:ty: $bits:ty) => {
+        impl $crate::flags::Flags for $name {
+            type Bits = $bits;
+
+            fn from_bits(bits: $bits) -> ::core::option::Option<Self> {
+                <$name>::from_bits(bits)
+            }
+
+            fn from_bits_truncate(bits: $bits) -> Self {
+                <$name>::from_bits_truncate(bits)
+            }
+
+            fn from_bits_preserve(bits: $bits) -> Self {
+                // `bitflags` marks this as unsafe, but any bit pattern is a
+                // valid value of a flags type
+                #[allow(unsafe_code)]
+                unsafe {
+                    <$name>::from_bits_unchecked(bits)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnknownBits;
+    use crate::{error, Error, SafeBuf};
+    use bitflags::bitflags;
+
+    bitflags! {
+        struct Options: u32 {
+            const A = 0x0000_0001;
+            const B = 0x0001_0000;
+        }
+    }
+
+    crate::impl_flags!(Options: u32);
+
+    #[test]
+    fn unknown_bits() {
+        let bytes = [0x80, 0x01, 0x00, 0x01];
+
+        assert_eq!(
+            (&bytes[..])
+                .extract_flags::<Options>(UnknownBits::Reject)
+                .unwrap_err(),
+            Error::invalid("flags", 0x8001_0001_u32)
+        );
+
+        let options: Options = (&bytes[..]).extract_flags(UnknownBits::Truncate).unwrap();
+        assert_eq!(options, Options::A | Options::B);
+
+        let options: Options = (&bytes[..]).extract_flags(UnknownBits::Preserve).unwrap();
+        assert_eq!(options.bits(), 0x8001_0001);
+        assert!(options.contains(Options::A | Options::B));
+    }
+
+    #[test]
+    fn little_endian() {
+        let mut buffer = &[0x01, 0x00, 0x01, 0x00, 0x01][..];
+
+        let options: Options = buffer.extract_flags_le(UnknownBits::Reject).unwrap();
+        assert_eq!(options, Options::A | Options::B);
+        assert_eq!(
+            buffer
+                .extract_flags_le::<Options>(UnknownBits::Reject)
+                .unwrap_err(),
+            error::Truncated::new(4, 1).into()
+        );
+        assert_eq!(buffer.len(), 1);
+    }
+}
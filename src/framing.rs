@@ -0,0 +1,417 @@
+//! Incremental decoding of frames from partial reads
+//!
+//! [`FromBuf`] parses an object from a buffer which is expected to contain the
+//! whole object. Parsers fed by partial network reads need something
+//! different, since "not enough bytes yet" is a normal outcome rather than an
+//! error. The [`Decode`] trait models this.
+//!
+//! The [`Encode`] trait is the counterpart for writing frames.
+
+use crate::{Error, FromBuf};
+use bytes::{Buf, BufMut, BytesMut};
+use core::{fmt, marker::PhantomData};
+
+pub mod cobs;
+pub use cobs::Cobs;
+
+mod delimited;
+pub use delimited::DelimitedCodec;
+
+mod length_delimited;
+pub use length_delimited::{LengthDelimited, PrefixWidth};
+
+mod netstring;
+pub use netstring::Netstring;
+
+mod reassembler;
+pub use reassembler::{Frames, Reassembler};
+
+pub mod slip;
+pub use slip::Slip;
+
+/// The outcome of a [`Decode::try_decode`] call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoded<T> {
+    /// A complete item was decoded
+    Item(T),
+
+    /// The buffer does not yet contain a complete item
+    ///
+    /// At least this many more bytes are needed before decoding can succeed.
+    /// This is a lower bound; decoding may report further shortfalls once
+    /// these bytes arrive.
+    NeedMore(usize),
+}
+
+impl<T> Decoded<T> {
+    /// Convert into an [`Option`], discarding the number of bytes needed
+    #[must_use]
+    pub fn item(self) -> Option<T> {
+        match self {
+            Self::Item(item) => Some(item),
+            Self::NeedMore(_) => None,
+        }
+    }
+}
+
+/// Objects which implement [`Decode`] are capable of incrementally decoding
+/// items from a buffer which is being filled by partial reads
+pub trait Decode {
+    /// The type of item being decoded
+    type Item;
+
+    /// Attempt to decode an item from the front of the buffer
+    ///
+    /// If the buffer contains a complete item, it should be removed from the
+    /// buffer and returned. If the buffer does not yet contain a complete
+    /// item, this should return `Ok(None)` and leave the buffer in a state
+    /// where decoding can be retried once more bytes have arrived.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the bytes in the buffer cannot be
+    /// decoded (regardless of how many more bytes arrive).
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>>;
+
+    /// Attempt to decode an item from the front of the buffer, reporting how
+    /// many more bytes are needed if it is incomplete
+    ///
+    /// This lets a caller read exactly the missing number of bytes rather than
+    /// retrying after every small read. The default implementation calls
+    /// [`Decode::decode`], and can only report that at least one more byte is
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the bytes in the buffer cannot be
+    /// decoded (regardless of how many more bytes arrive).
+    fn try_decode(&mut self, src: &mut BytesMut) -> crate::Result<Decoded<Self::Item>> {
+        Ok(match self.decode(src)? {
+            Some(item) => Decoded::Item(item),
+            None => Decoded::NeedMore(1),
+        })
+    }
+
+    /// Attempt to decode an item from the front of the buffer, once no more
+    /// bytes will arrive
+    ///
+    /// By default, this calls [`Decode::decode`], and treats any bytes left
+    /// over as a truncated item. Since the true length of that item is
+    /// unknown, the reported number of bytes needed is a lower bound.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the bytes in the buffer cannot be
+    /// decoded, or if there is an incomplete item left in the buffer.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>> {
+        match self.decode(src)? {
+            Some(item) => Ok(Some(item)),
+            None if src.is_empty() => Ok(None),
+            None => Err(crate::error::Truncated::new(src.len() + 1, src.len()).into()),
+        }
+    }
+}
+
+impl<D> Decode for &mut D
+where
+    D: Decode + ?Sized,
+{
+    type Item = D::Item;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>> {
+        (**self).decode(src)
+    }
+
+    fn try_decode(&mut self, src: &mut BytesMut) -> crate::Result<Decoded<Self::Item>> {
+        (**self).try_decode(src)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>> {
+        (**self).decode_eof(src)
+    }
+}
+
+/// Objects which implement [`Encode`] are capable of encoding items into a
+/// buffer
+pub trait Encode<Item> {
+    /// Encode an item, appending it to the buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the item cannot be encoded. The
+    /// buffer may contain a partially encoded item, see
+    /// [`Encode::encode_into`] for a method which avoids this.
+    fn encode(&mut self, item: Item, dst: &mut BytesMut) -> crate::Result<()>;
+
+    /// The number of bytes the item will occupy once encoded (or an upper
+    /// bound), if this can be computed cheaply
+    ///
+    /// This is used to reserve capacity up front. The default implementation
+    /// returns `None`.
+    fn encoded_len(&self, item: &Item) -> Option<usize> {
+        let _ = item;
+        None
+    }
+
+    /// Encode an item, appending it to the buffer
+    ///
+    /// Unlike [`Encode::encode`], this reserves capacity up front (if the
+    /// encoded length is known), and leaves the buffer unchanged if encoding
+    /// fails.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the item cannot be encoded.
+    fn encode_into(&mut self, item: Item, dst: &mut BytesMut) -> crate::Result<()> {
+        if let Some(len) = self.encoded_len(&item) {
+            dst.reserve(len);
+        }
+
+        let start = dst.len();
+        let result = self.encode(item, dst);
+        if result.is_err() {
+            dst.truncate(start);
+        }
+        result
+    }
+}
+
+impl<E, Item> Encode<Item> for &mut E
+where
+    E: Encode<Item> + ?Sized,
+{
+    fn encode(&mut self, item: Item, dst: &mut BytesMut) -> crate::Result<()> {
+        (**self).encode(item, dst)
+    }
+
+    fn encoded_len(&self, item: &Item) -> Option<usize> {
+        (**self).encoded_len(item)
+    }
+}
+
+/// A [`Decode`] implementation for any type which implements [`FromBuf`]
+///
+/// Decoding is attempted against the bytes received so far. If
+/// [`FromBuf::from_buf`] fails because the bytes are truncated, the buffer is
+/// left untouched and decoding can be retried once more bytes have arrived.
+///
+/// A truncation which more bytes can't fix (such as a nested length which
+/// overruns its enclosing region) is returned as an error. To tell the two
+/// apart, the parse is retried with an extra byte of input: overrunning a
+/// region fails the same way however much input follows it, without reaching
+/// the extra byte.
+pub struct FromBufDecoder<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> FromBufDecoder<T> {
+    /// Construct a new [`FromBufDecoder`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for FromBufDecoder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for FromBufDecoder<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FromBufDecoder<T> {}
+
+impl<T> fmt::Debug for FromBufDecoder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromBufDecoder").finish()
+    }
+}
+
+impl<T> Decode for FromBufDecoder<T>
+where
+    T: FromBuf,
+{
+    type Item = T;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<T>> {
+        self.try_decode(src).map(Decoded::item)
+    }
+
+    fn try_decode(&mut self, src: &mut BytesMut) -> crate::Result<Decoded<T>> {
+        let mut view = &src[..];
+        match T::from_buf(&mut view) {
+            Ok(item) => {
+                let consumed = src.len() - view.len();
+                src.advance(consumed);
+                Ok(Decoded::Item(item))
+            }
+            Err(e) => {
+                let missing = match e.root() {
+                    Error::Truncated(truncated) => truncated.missing().max(1),
+                    _ => return Err(e),
+                };
+
+                let len = src.len();
+                src.put_u8(0);
+                let mut view = &src[..];
+                let retried = T::from_buf(&mut view);
+                let overrun = !view.is_empty() && matches!(retried, Err(ref r) if *r == e);
+                src.truncate(len);
+
+                if overrun {
+                    Err(e)
+                } else {
+                    Ok(Decoded::NeedMore(missing))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decode, Decoded, Encode, FromBufDecoder};
+    use crate::{BufMut, Error, FromBuf, SafeBuf};
+    use bytes::BytesMut;
+    use core::convert::TryFrom;
+
+    #[derive(Debug, PartialEq)]
+    struct Message(Vec<u8>);
+
+    impl FromBuf for Message {
+        fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+        where
+            B: bytes::Buf,
+        {
+            let len = SafeBuf::try_get_u8(&mut buffer)?;
+            let body = buffer.try_copy_to_bytes(len.into())?;
+            Ok(Self(body.to_vec()))
+        }
+    }
+
+    #[test]
+    fn decode_partial() {
+        let mut decoder = FromBufDecoder::<Message>::new();
+        let mut src = BytesMut::new();
+
+        assert_eq!(decoder.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&[3, 1, 2]);
+        assert_eq!(decoder.decode(&mut src).unwrap(), None);
+        assert_eq!(src.len(), 3);
+
+        src.extend_from_slice(&[3, 0]);
+        assert_eq!(
+            decoder.decode(&mut src).unwrap(),
+            Some(Message(vec![1, 2, 3]))
+        );
+        assert_eq!(&src[..], &[0]);
+
+        assert_eq!(decoder.decode(&mut src).unwrap(), Some(Message(vec![])));
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn need_more() {
+        let mut decoder = FromBufDecoder::<Message>::new();
+        let mut src = BytesMut::new();
+
+        assert_eq!(decoder.try_decode(&mut src).unwrap(), Decoded::NeedMore(1));
+
+        src.extend_from_slice(&[3, 1]);
+        assert_eq!(decoder.try_decode(&mut src).unwrap(), Decoded::NeedMore(2));
+
+        src.extend_from_slice(&[2, 3]);
+        assert_eq!(
+            decoder.try_decode(&mut src).unwrap(),
+            Decoded::Item(Message(vec![1, 2, 3]))
+        );
+    }
+
+    /// A message wrapped in a length-prefixed envelope
+    #[derive(Debug, PartialEq)]
+    struct Envelope(Message);
+
+    impl FromBuf for Envelope {
+        fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+        where
+            B: bytes::Buf,
+        {
+            let len = SafeBuf::try_get_u8(&mut buffer)?;
+            let mut region = buffer.try_take_buf(len.into())?;
+            let message = Message::from_buf(&mut region)?;
+            region.finish()?;
+            Ok(Self(message))
+        }
+    }
+
+    #[test]
+    fn overrun_region() {
+        let mut decoder = FromBufDecoder::<Envelope>::new();
+
+        // the envelope is incomplete
+        let mut src = BytesMut::new();
+        assert_eq!(decoder.try_decode(&mut src).unwrap(), Decoded::NeedMore(1));
+
+        let mut src = BytesMut::from(&[3, 2, 1][..]);
+        assert_eq!(decoder.try_decode(&mut src).unwrap(), Decoded::NeedMore(1));
+
+        // the message is longer than the envelope, which no more input can fix
+        let mut src = BytesMut::from(&[3, 5, 1, 2][..]);
+        assert_eq!(
+            decoder.try_decode(&mut src).unwrap_err(),
+            crate::error::Truncated::new(5, 2).into()
+        );
+        assert_eq!(&src[..], &[3, 5, 1, 2]);
+
+        src.extend_from_slice(&[3, 4, 5]);
+        assert!(decoder.try_decode(&mut src).is_err());
+    }
+
+    struct MessageEncoder;
+
+    impl Encode<&[u8]> for MessageEncoder {
+        fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+            dst.put_u8(0xAA);
+            let len = u8::try_from(item.len())
+                .map_err(|_| Error::invalid("message length", item.len() as u64))?;
+            dst.put_u8(len);
+            dst.put_slice(item);
+            Ok(())
+        }
+
+        fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+            Some(2 + item.len())
+        }
+    }
+
+    #[test]
+    fn encode_into() {
+        let mut dst = BytesMut::new();
+
+        MessageEncoder.encode_into(&[1, 2, 3], &mut dst).unwrap();
+        assert_eq!(&dst[..], &[0xAA, 3, 1, 2, 3]);
+
+        dst.clear();
+        assert!(MessageEncoder.encode_into(&[0; 300], &mut dst).is_err());
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn decode_eof() {
+        let mut decoder = FromBufDecoder::<Message>::new();
+        let mut src = BytesMut::from(&[3, 1][..]);
+
+        assert!(decoder.decode_eof(&mut src).is_err());
+
+        src.clear();
+        assert_eq!(decoder.decode_eof(&mut src).unwrap(), None);
+    }
+}
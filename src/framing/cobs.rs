@@ -0,0 +1,311 @@
+//! Consistent Overhead Byte Stuffing (COBS)
+//!
+//! COBS encodes arbitrary data so that it contains no zero bytes, allowing a
+//! zero byte to be used as an unambiguous frame delimiter. This is commonly
+//! used over serial links. [`encode`] and [`decode`] operate on single frames
+//! (without the delimiter), while [`Cobs`] is a streaming codec which handles
+//! the delimiters.
+
+use super::{Decode, Encode};
+use crate::{error, Error};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// The delimiter which separates COBS frames
+pub const DELIMITER: u8 = 0x00;
+
+/// The maximum length of a COBS block (including its code byte)
+const MAX_BLOCK: u8 = 0xFF;
+
+/// The maximum length of the COBS encoding of `len` bytes (excluding the
+/// delimiter)
+#[must_use]
+pub const fn max_encoded_len(len: usize) -> usize {
+    len.saturating_add(len / (MAX_BLOCK as usize - 1))
+        .saturating_add(1)
+}
+
+/// COBS-encode a frame, appending it to `dst`
+///
+/// The delimiter is not appended.
+pub fn encode(src: &[u8], dst: &mut BytesMut) {
+    dst.reserve(max_encoded_len(src.len()));
+
+    let mut code_index = dst.len();
+    dst.put_u8(0);
+    let mut code = 1;
+    let mut after_full_block = false;
+
+    for &byte in src {
+        if byte != DELIMITER {
+            dst.put_u8(byte);
+            code += 1;
+        }
+
+        if byte == DELIMITER || code == MAX_BLOCK {
+            after_full_block = code == MAX_BLOCK;
+            dst[code_index] = code;
+            code_index = dst.len();
+            dst.put_u8(0);
+            code = 1;
+        }
+    }
+
+    if after_full_block && code == 1 {
+        // a full block needs no empty block after it at the end of the frame
+        dst.truncate(code_index);
+    } else {
+        dst[code_index] = code;
+    }
+}
+
+/// Decode a COBS-encoded frame
+///
+/// The frame should not include the delimiter.
+///
+/// # Errors
+///
+/// This method will return an error if the frame is not valid COBS.
+pub fn decode(src: &[u8]) -> crate::Result<Bytes> {
+    let mut dst = BytesMut::with_capacity(src.len());
+    let mut rest = src;
+
+    while let Some((&code, tail)) = rest.split_first() {
+        if code == DELIMITER {
+            return Err(Error::invalid("COBS code byte", code));
+        }
+
+        let block_len = usize::from(code - 1);
+        let block = tail
+            .get(..block_len)
+            .ok_or_else(|| Error::invalid("COBS block length", code))?;
+        if block.contains(&DELIMITER) {
+            return Err(Error::invalid("COBS block", block));
+        }
+
+        dst.put_slice(block);
+        rest = &tail[block_len..];
+
+        if code != MAX_BLOCK && !rest.is_empty() {
+            dst.put_u8(DELIMITER);
+        }
+    }
+
+    Ok(dst.freeze())
+}
+
+/// A streaming COBS codec
+///
+/// Frames are COBS-encoded, and separated by a zero byte. Empty frames
+/// (consecutive delimiters) are skipped when decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cobs {
+    max_frame_len: usize,
+    /// The offset at which to resume searching for the delimiter, so that
+    /// partial frames are not rescanned after each read
+    next_index: usize,
+}
+
+impl Cobs {
+    /// Construct a new codec, accepting frames of up to `max_frame_len` bytes
+    /// (before encoding)
+    #[must_use]
+    pub fn new(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            next_index: 0,
+        }
+    }
+}
+
+impl Decode for Cobs {
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        loop {
+            // the buffer may have been cleared or replaced since the last call
+            if self.next_index > src.len() {
+                self.next_index = 0;
+            }
+
+            let end = match src[self.next_index..].iter().position(|&b| b == DELIMITER) {
+                Some(i) => self.next_index + i,
+                None if src.len() > max_encoded_len(self.max_frame_len) => {
+                    self.next_index = 0;
+                    return Err(error::LimitExceeded::new(
+                        "encoded frame length",
+                        src.len(),
+                        max_encoded_len(self.max_frame_len),
+                    )
+                    .into());
+                }
+                None => {
+                    self.next_index = src.len();
+                    return Ok(None);
+                }
+            };
+
+            self.next_index = 0;
+            let frame = src.split_to(end);
+            src.advance(1);
+
+            if frame.is_empty() {
+                continue;
+            }
+
+            let decoded = decode(&frame)?;
+            error::LimitExceeded::check("frame length", decoded.len(), self.max_frame_len)?;
+            return Ok(Some(decoded));
+        }
+    }
+}
+
+impl Encode<&[u8]> for Cobs {
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        error::LimitExceeded::check("frame length", item.len(), self.max_frame_len)?;
+        encode(item, dst);
+        dst.put_u8(DELIMITER);
+        Ok(())
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(max_encoded_len(item.len()).saturating_add(1))
+    }
+}
+
+impl Encode<Bytes> for Cobs {
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> crate::Result<()> {
+        self.encode(&item[..], dst)
+    }
+
+    fn encoded_len(&self, item: &Bytes) -> Option<usize> {
+        Some(max_encoded_len(item.len()).saturating_add(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, max_encoded_len, Cobs};
+    use crate::{
+        error,
+        framing::{Decode, Encode},
+    };
+    use bytes::BytesMut;
+
+    fn encoded(src: &[u8]) -> Vec<u8> {
+        let mut dst = BytesMut::new();
+        encode(src, &mut dst);
+        dst.to_vec()
+    }
+
+    #[test]
+    fn known_vectors() {
+        assert_eq!(encoded(&[]), [0x01]);
+        assert_eq!(encoded(&[0x00]), [0x01, 0x01]);
+        assert_eq!(encoded(&[0x00, 0x00]), [0x01, 0x01, 0x01]);
+        assert_eq!(
+            encoded(&[0x11, 0x22, 0x00, 0x33]),
+            [0x03, 0x11, 0x22, 0x02, 0x33]
+        );
+        assert_eq!(
+            encoded(&[0x11, 0x00, 0x00, 0x00]),
+            [0x02, 0x11, 0x01, 0x01, 0x01]
+        );
+
+        let run: Vec<u8> = (0x01..=0xFF).collect();
+        let expected: Vec<u8> = [&[0xFF][..], &run[..254]].concat();
+        assert_eq!(encoded(&run[..254]), expected);
+        let expected: Vec<u8> = [&[0xFF][..], &run[..254], &[0x02, 0xFF]].concat();
+        assert_eq!(encoded(&run), expected);
+
+        let input: Vec<u8> = [&run[1..], &[0x00]].concat();
+        let expected: Vec<u8> = [&[0xFF][..], &run[1..], &[0x01, 0x01]].concat();
+        assert_eq!(encoded(&input), expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let inputs: Vec<Vec<u8>> = vec![
+            vec![],
+            vec![0],
+            vec![1, 2, 0, 3, 0],
+            (0..=255).cycle().take(1000).collect(),
+            vec![0xAA; 254],
+            vec![0xAA; 255],
+        ];
+
+        for input in inputs {
+            assert_eq!(decode(&encoded(&input)).unwrap(), &input[..]);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(decode(&[0x05, 0x11]).is_err());
+        assert!(decode(&[0x00]).is_err());
+    }
+
+    #[test]
+    fn streaming() {
+        let mut codec = Cobs::new(16);
+        let mut buffer = BytesMut::new();
+
+        codec.encode_into(&[1, 0, 2][..], &mut buffer).unwrap();
+        codec.encode_into(&[3][..], &mut buffer).unwrap();
+        let mut second = buffer.split_off(5);
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[1, 0, 2][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(&[0]);
+        buffer.unsplit(second.split());
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[3][..]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn max_frame_len() {
+        let mut codec = Cobs::new(2);
+
+        let mut src = BytesMut::from(&[0x04, 1, 2, 3][..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap_err(),
+            error::LimitExceeded::new("encoded frame length", 4, 3).into()
+        );
+
+        let mut src = BytesMut::from(&[0x04, 1, 2, 3, 0][..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap_err(),
+            error::LimitExceeded::new("frame length", 3, 2).into()
+        );
+
+        let mut dst = BytesMut::new();
+        assert_eq!(
+            codec.encode_into(&[1, 2, 3][..], &mut dst).unwrap_err(),
+            error::LimitExceeded::new("frame length", 3, 2).into()
+        );
+    }
+
+    #[test]
+    fn max_encoded_len_saturates() {
+        assert_eq!(max_encoded_len(3), 4);
+        assert_eq!(max_encoded_len(usize::MAX), usize::MAX);
+
+        let mut codec = Cobs::new(usize::MAX);
+        let mut src = BytesMut::from(&b"\x05abc"[..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+        assert_eq!(codec.encoded_len(&&[][..]), Some(2));
+    }
+
+    #[test]
+    fn resume_after_clear() {
+        let mut codec = Cobs::new(16);
+
+        let mut src = BytesMut::from(&[0x05, 1, 2, 3][..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.clear();
+        src.extend_from_slice(&[0x02, 4, 0]);
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), &[4][..]);
+        assert!(src.is_empty());
+    }
+}
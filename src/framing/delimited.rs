@@ -0,0 +1,254 @@
+//! Delimiter-based framing
+
+use super::{Decode, Encode};
+use crate::{error, Error};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// A codec for frames separated by a delimiter (such as newline-delimited
+/// records)
+///
+/// Decoded frames do not include the delimiter. The maximum frame length is
+/// mandatory, so a peer which never sends a delimiter cannot cause unbounded
+/// buffering.
+///
+/// ```
+/// use safer_bytes::{
+///     framing::{Decode, DelimitedCodec},
+///     BytesMut,
+/// };
+///
+/// let mut codec = DelimitedCodec::new(&b"\r\n"[..], 1024).unwrap();
+/// let mut buffer = BytesMut::from(&b"first\r\nsec"[..]);
+///
+/// assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"first"[..]);
+/// assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelimitedCodec {
+    delimiter: Bytes,
+    max_frame_len: usize,
+
+    /// The offset at which to resume searching for the delimiter, so that
+    /// partial frames are not rescanned after each read
+    next_index: usize,
+}
+
+impl DelimitedCodec {
+    /// Construct a new codec, splitting on `delimiter` and accepting frames of
+    /// up to `max_frame_len` bytes (excluding the delimiter)
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the delimiter is empty
+    pub fn new(delimiter: impl Into<Bytes>, max_frame_len: usize) -> crate::Result<Self> {
+        let delimiter = delimiter.into();
+        if delimiter.is_empty() {
+            return Err(Error::invalid("delimiter", delimiter));
+        }
+
+        Ok(Self {
+            delimiter,
+            max_frame_len,
+            next_index: 0,
+        })
+    }
+
+    /// Construct a new codec for newline-delimited frames
+    #[must_use]
+    pub fn lines(max_frame_len: usize) -> Self {
+        Self {
+            delimiter: Bytes::from_static(b"\n"),
+            max_frame_len,
+            next_index: 0,
+        }
+    }
+
+    /// The delimiter which separates frames
+    #[must_use]
+    pub fn delimiter(&self) -> &[u8] {
+        &self.delimiter
+    }
+
+    fn find_delimiter(&self, src: &[u8]) -> Option<usize> {
+        memchr::memmem::find(src.get(self.next_index..)?, &self.delimiter)
+            .map(|i| i + self.next_index)
+    }
+}
+
+impl Decode for DelimitedCodec {
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        // the buffer may have been cleared or replaced since the last call
+        if self.next_index > src.len() {
+            self.next_index = 0;
+        }
+
+        match self.find_delimiter(src) {
+            Some(end) if end > self.max_frame_len => {
+                self.next_index = 0;
+                Err(error::LimitExceeded::new("frame length", end, self.max_frame_len).into())
+            }
+            Some(end) => {
+                self.next_index = 0;
+                let frame = src.split_to(end).freeze();
+                src.advance(self.delimiter.len());
+                Ok(Some(frame))
+            }
+            // a delimiter which hasn't arrived yet would start too late
+            None if src.len() >= self.max_frame_len.saturating_add(self.delimiter.len()) => {
+                self.next_index = 0;
+                Err(error::LimitExceeded::new("frame length", src.len(), self.max_frame_len).into())
+            }
+            None => {
+                // the delimiter may straddle the end of the buffer, so resume
+                // the search just before it
+                self.next_index = (src.len() + 1).saturating_sub(self.delimiter.len());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Decode a frame once no more bytes will arrive
+    ///
+    /// Any bytes left over after the final delimiter are returned as a final
+    /// frame.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => {
+                self.next_index = 0;
+                Ok(Some(src.split().freeze()))
+            }
+        }
+    }
+}
+
+impl Encode<&[u8]> for DelimitedCodec {
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        error::LimitExceeded::check("frame length", item.len(), self.max_frame_len)?;
+        if memchr::memmem::find(item, &self.delimiter).is_some() {
+            return Err(Error::invalid("frame containing the delimiter", item));
+        }
+
+        dst.put_slice(item);
+        dst.put_slice(&self.delimiter);
+        Ok(())
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(item.len() + self.delimiter.len())
+    }
+}
+
+impl Encode<Bytes> for DelimitedCodec {
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> crate::Result<()> {
+        self.encode(&item[..], dst)
+    }
+
+    fn encoded_len(&self, item: &Bytes) -> Option<usize> {
+        Some(item.len() + self.delimiter.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DelimitedCodec;
+    use crate::{
+        error,
+        framing::{Decode, Encode},
+    };
+    use bytes::BytesMut;
+
+    #[test]
+    fn delimiter_across_reads() {
+        let mut codec = DelimitedCodec::new(&b"\r\n"[..], 16).unwrap();
+        let mut buffer = BytesMut::from(&b"abc\r"[..]);
+
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(b"\ndef\r\n");
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"abc"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"def"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+    }
+
+    #[test]
+    fn decode_eof() {
+        let mut codec = DelimitedCodec::lines(16);
+        let mut buffer = BytesMut::from(&b"abc\ndef"[..]);
+
+        assert_eq!(codec.decode_eof(&mut buffer).unwrap().unwrap(), &b"abc"[..]);
+        assert_eq!(codec.decode_eof(&mut buffer).unwrap().unwrap(), &b"def"[..]);
+        assert_eq!(codec.decode_eof(&mut buffer).unwrap(), None);
+    }
+
+    #[test]
+    fn max_frame_len() {
+        let mut codec = DelimitedCodec::lines(4);
+
+        let mut buffer = BytesMut::from(&b"abcd"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.extend_from_slice(b"e");
+        assert!(codec.decode(&mut buffer).is_err());
+
+        let mut buffer = BytesMut::from(&b"abcde\n"[..]);
+        assert_eq!(
+            DelimitedCodec::lines(4).decode(&mut buffer).unwrap_err(),
+            error::LimitExceeded::new("frame length", 5, 4).into()
+        );
+
+        // exactly `max_frame_len + delimiter.len()` bytes, with no delimiter
+        let mut codec = DelimitedCodec::new(&b"\r\n"[..], 4).unwrap();
+        let mut buffer = BytesMut::from(&b"abcd\r"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.extend_from_slice(b"e");
+        assert!(codec.decode(&mut buffer).is_err());
+
+        let mut buffer = BytesMut::from(&b"abcd\r\n"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"abcd"[..]);
+
+        let mut codec = DelimitedCodec::lines(usize::MAX);
+        let mut buffer = BytesMut::from(&b"abcd"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+    }
+
+    #[test]
+    fn recover_after_error() {
+        let mut codec = DelimitedCodec::lines(4);
+
+        let mut buffer = BytesMut::from(&b"abcd"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.extend_from_slice(b"e");
+        assert!(codec.decode(&mut buffer).is_err());
+
+        buffer.clear();
+        buffer.extend_from_slice(b"ok\n");
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"ok"[..]);
+        buffer.extend_from_slice(b"more\n");
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"more"[..]);
+
+        // a partial frame, after which the buffer is replaced by a shorter one
+        let mut buffer = BytesMut::from(&b"abc"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        let mut buffer = BytesMut::from(&b"a\n"[..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &b"a"[..]);
+    }
+
+    #[test]
+    fn encode() {
+        let mut codec = DelimitedCodec::lines(16);
+        let mut buffer = BytesMut::new();
+
+        codec.encode_into(&b"abc"[..], &mut buffer).unwrap();
+        assert_eq!(&buffer[..], b"abc\n");
+
+        assert!(codec.encode_into(&b"a\nb"[..], &mut buffer).is_err());
+        assert_eq!(
+            codec.encode_into(&[b'a'; 17][..], &mut buffer).unwrap_err(),
+            error::LimitExceeded::new("frame length", 17, 16).into()
+        );
+        assert!(DelimitedCodec::new(&b""[..], 16).is_err());
+    }
+}
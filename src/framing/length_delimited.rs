@@ -0,0 +1,337 @@
+//! Length-delimited framing
+
+use super::{Decode, Decoded, Encode};
+use crate::{error, Endian, Error, SafeBuf};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use core::convert::TryFrom;
+
+/// The width of the length prefix of a [`LengthDelimited`] frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrefixWidth {
+    /// A 1-byte length prefix
+    U8,
+
+    /// A 2-byte length prefix
+    U16,
+
+    /// A 4-byte length prefix
+    U32,
+
+    /// An 8-byte length prefix
+    U64,
+}
+
+impl PrefixWidth {
+    /// The number of bytes in the prefix
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U8 => 1,
+            Self::U16 => 2,
+            Self::U32 => 4,
+            Self::U64 => 8,
+        }
+    }
+
+    /// The largest value which can be represented by the prefix
+    #[must_use]
+    pub const fn max_value(self) -> u64 {
+        match self {
+            Self::U8 => u8::MAX as u64,
+            Self::U16 => u16::MAX as u64,
+            Self::U32 => u32::MAX as u64,
+            Self::U64 => u64::MAX,
+        }
+    }
+
+    /// Read a prefix of this width from the front of the buffer, without
+    /// advancing the buffer
+    pub(crate) fn peek<B>(self, buffer: &B, endian: Endian) -> Result<u64, error::Truncated>
+    where
+        B: Buf,
+    {
+        Ok(match (self, endian) {
+            (Self::U8, _) => buffer.try_get_u8_at(0)?.into(),
+            (Self::U16, Endian::Big) => buffer.try_get_u16_at(0)?.into(),
+            (Self::U16, Endian::Little) => buffer.try_get_u16_le_at(0)?.into(),
+            (Self::U32, Endian::Big) => buffer.try_get_u32_at(0)?.into(),
+            (Self::U32, Endian::Little) => buffer.try_get_u32_le_at(0)?.into(),
+            (Self::U64, Endian::Big) => buffer.try_get_u64_at(0)?,
+            (Self::U64, Endian::Little) => buffer.try_get_u64_le_at(0)?,
+        })
+    }
+}
+
+/// A codec for frames consisting of a length prefix followed by a payload
+///
+/// The maximum frame size is mandatory, so that a hostile length prefix can
+/// never cause unbounded buffering.
+///
+/// ```
+/// use safer_bytes::{
+///     framing::{Decode, Encode, LengthDelimited, PrefixWidth},
+///     BytesMut,
+/// };
+///
+/// let mut codec = LengthDelimited::new(1024).prefix_width(PrefixWidth::U16);
+///
+/// let mut buffer = BytesMut::new();
+/// codec.encode_into(&b"hello"[..], &mut buffer).unwrap();
+/// assert_eq!(&buffer[..], b"\x00\x05hello");
+///
+/// let frame = codec.decode(&mut buffer).unwrap().unwrap();
+/// assert_eq!(frame, &b"hello"[..]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthDelimited {
+    max_frame_len: usize,
+    prefix_width: PrefixWidth,
+    endian: Endian,
+    length_includes_prefix: bool,
+}
+
+impl LengthDelimited {
+    /// Construct a new codec, accepting payloads of up to `max_frame_len` bytes
+    ///
+    /// By default, the length prefix is a 4-byte big-endian integer which
+    /// counts only the payload.
+    #[must_use]
+    pub fn new(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            prefix_width: PrefixWidth::U32,
+            endian: Endian::Big,
+            length_includes_prefix: false,
+        }
+    }
+
+    /// Set the width of the length prefix
+    #[must_use]
+    pub fn prefix_width(mut self, prefix_width: PrefixWidth) -> Self {
+        self.prefix_width = prefix_width;
+        self
+    }
+
+    /// Set the byte order of the length prefix
+    #[must_use]
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Set whether the length prefix counts its own bytes, as well as the
+    /// payload
+    #[must_use]
+    pub fn length_includes_prefix(mut self, length_includes_prefix: bool) -> Self {
+        self.length_includes_prefix = length_includes_prefix;
+        self
+    }
+
+    /// The maximum payload length accepted by this codec
+    #[must_use]
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    fn peek_prefix(&self, src: &BytesMut) -> Result<u64, error::Truncated> {
+        self.prefix_width.peek(src, self.endian)
+    }
+
+    fn payload_len(&self, prefix: u64) -> crate::Result<usize> {
+        let payload_len = if self.length_includes_prefix {
+            prefix
+                .checked_sub(self.prefix_width.size() as u64)
+                .ok_or_else(|| Error::invalid("frame length", prefix))?
+        } else {
+            prefix
+        };
+
+        // a length which doesn't fit in a `usize` is over any limit
+        let payload_len = usize::try_from(payload_len).unwrap_or(usize::MAX);
+        error::LimitExceeded::check("frame length", payload_len, self.max_frame_len)?;
+        Ok(payload_len)
+    }
+
+    fn put_prefix(&self, payload_len: usize, dst: &mut BytesMut) -> crate::Result<()> {
+        error::LimitExceeded::check("frame length", payload_len, self.max_frame_len)?;
+
+        let extra = if self.length_includes_prefix {
+            self.prefix_width.size() as u64
+        } else {
+            0
+        };
+        let prefix = (payload_len as u64)
+            .checked_add(extra)
+            .filter(|&prefix| prefix <= self.prefix_width.max_value())
+            .ok_or_else(|| {
+                let limit =
+                    usize::try_from(self.prefix_width.max_value() - extra).unwrap_or(usize::MAX);
+                error::LimitExceeded::new("frame length", payload_len, limit)
+            })?;
+
+        #[allow(clippy::cast_possible_truncation)]
+        match (self.prefix_width, self.endian) {
+            (PrefixWidth::U8, _) => dst.put_u8(prefix as u8),
+            (PrefixWidth::U16, Endian::Big) => dst.put_u16(prefix as u16),
+            (PrefixWidth::U16, Endian::Little) => dst.put_u16_le(prefix as u16),
+            (PrefixWidth::U32, Endian::Big) => dst.put_u32(prefix as u32),
+            (PrefixWidth::U32, Endian::Little) => dst.put_u32_le(prefix as u32),
+            (PrefixWidth::U64, Endian::Big) => dst.put_u64(prefix),
+            (PrefixWidth::U64, Endian::Little) => dst.put_u64_le(prefix),
+        }
+
+        Ok(())
+    }
+}
+
+impl Decode for LengthDelimited {
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        self.try_decode(src).map(Decoded::item)
+    }
+
+    fn try_decode(&mut self, src: &mut BytesMut) -> crate::Result<Decoded<Bytes>> {
+        let prefix = match self.peek_prefix(src) {
+            Ok(prefix) => prefix,
+            Err(truncated) => return Ok(Decoded::NeedMore(truncated.missing())),
+        };
+        let payload_len = self.payload_len(prefix)?;
+        let frame_len = self
+            .prefix_width
+            .size()
+            .checked_add(payload_len)
+            .ok_or_else(|| {
+                error::LimitExceeded::new(
+                    "frame length",
+                    payload_len,
+                    usize::MAX - self.prefix_width.size(),
+                )
+            })?;
+
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(Decoded::NeedMore(frame_len - src.len()));
+        }
+
+        src.advance(self.prefix_width.size());
+        Ok(Decoded::Item(src.split_to(payload_len).freeze()))
+    }
+}
+
+impl Encode<&[u8]> for LengthDelimited {
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        self.put_prefix(item.len(), dst)?;
+        dst.put_slice(item);
+        Ok(())
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        self.prefix_width.size().checked_add(item.len())
+    }
+}
+
+impl Encode<Bytes> for LengthDelimited {
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> crate::Result<()> {
+        self.encode(&item[..], dst)
+    }
+
+    fn encoded_len(&self, item: &Bytes) -> Option<usize> {
+        self.prefix_width.size().checked_add(item.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LengthDelimited, PrefixWidth};
+    use crate::{
+        error,
+        framing::{Decode, Decoded, Encode},
+        Endian,
+    };
+    use bytes::BytesMut;
+
+    #[test]
+    fn decode_partial() {
+        let mut codec = LengthDelimited::new(16);
+        let mut src = BytesMut::from(&[0, 0, 0][..]);
+
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&[3, 1, 2]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(&[3, 0xFF]);
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), &[1, 2, 3][..]);
+        assert_eq!(&src[..], &[0xFF]);
+    }
+
+    #[test]
+    fn need_more() {
+        let mut codec = LengthDelimited::new(16);
+        let mut src = BytesMut::from(&[0][..]);
+
+        assert_eq!(codec.try_decode(&mut src).unwrap(), Decoded::NeedMore(3));
+
+        src.extend_from_slice(&[0, 0, 3, 1]);
+        assert_eq!(codec.try_decode(&mut src).unwrap(), Decoded::NeedMore(2));
+    }
+
+    #[test]
+    fn max_frame_len() {
+        let mut codec = LengthDelimited::new(4).prefix_width(PrefixWidth::U8);
+
+        let mut src = BytesMut::from(&[5][..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap_err(),
+            error::LimitExceeded::new("frame length", 5, 4).into()
+        );
+
+        let mut dst = BytesMut::new();
+        assert_eq!(
+            codec.encode_into(&[0; 5][..], &mut dst).unwrap_err(),
+            error::LimitExceeded::new("frame length", 5, 4).into()
+        );
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn prefix_overflow() {
+        let mut codec = LengthDelimited::new(1024).prefix_width(PrefixWidth::U8);
+
+        let mut dst = BytesMut::new();
+        assert_eq!(
+            codec.encode_into(&[0; 256][..], &mut dst).unwrap_err(),
+            error::LimitExceeded::new("frame length", 256, 255).into()
+        );
+    }
+
+    #[test]
+    fn length_includes_prefix() {
+        let mut codec = LengthDelimited::new(16)
+            .prefix_width(PrefixWidth::U16)
+            .endian(Endian::Little)
+            .length_includes_prefix(true);
+
+        let mut buffer = BytesMut::new();
+        codec.encode_into(&[7, 8][..], &mut buffer).unwrap();
+        assert_eq!(&buffer[..], &[4, 0, 7, 8]);
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[7, 8][..]);
+
+        let mut src = BytesMut::from(&[1, 0][..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn frame_len_overflow() {
+        let mut codec = LengthDelimited::new(usize::MAX).prefix_width(PrefixWidth::U64);
+
+        let mut src = BytesMut::from(&[0xFF; 8][..]);
+        assert!(matches!(
+            codec.try_decode(&mut src).unwrap_err(),
+            crate::Error::LimitExceeded(_)
+        ));
+    }
+}
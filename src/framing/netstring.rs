@@ -0,0 +1,200 @@
+//! Netstring framing
+//!
+//! A netstring is a payload prefixed with its length in ASCII decimal and a
+//! colon, and followed by a comma. For example, `b"hello"` is encoded as
+//! `b"5:hello,"`.
+
+use super::{Decode, Encode};
+use crate::{error, Error};
+use alloc::string::ToString;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// The maximum number of bytes added by the encoding (up to 20 length digits,
+/// a colon, and a comma)
+const MAX_OVERHEAD: usize = 22;
+
+/// A codec for netstrings
+///
+/// The maximum payload length is mandatory. The length prefix is parsed
+/// without overflow, and is rejected as soon as it exceeds the maximum, so a
+/// hostile peer cannot cause unbounded buffering.
+///
+/// ```
+/// use safer_bytes::{
+///     framing::{Decode, Encode, Netstring},
+///     BytesMut,
+/// };
+///
+/// let mut codec = Netstring::new(1024);
+///
+/// let mut buffer = BytesMut::new();
+/// codec.encode_into(&b"hello"[..], &mut buffer).unwrap();
+/// assert_eq!(&buffer[..], b"5:hello,");
+///
+/// let payload = codec.decode(&mut buffer).unwrap().unwrap();
+/// assert_eq!(payload, &b"hello"[..]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Netstring {
+    max_len: usize,
+}
+
+impl Netstring {
+    /// Construct a new codec, accepting payloads of up to `max_len` bytes
+    #[must_use]
+    pub fn new(max_len: usize) -> Self {
+        Self { max_len }
+    }
+
+    /// Parse the length prefix, returning the length and the number of bytes
+    /// in the prefix (including the colon), or `None` if the prefix is
+    /// incomplete
+    fn parse_prefix(self, src: &[u8]) -> crate::Result<Option<(usize, usize)>> {
+        let mut len: usize = 0;
+
+        for (i, &byte) in src.iter().enumerate() {
+            match byte {
+                b':' if i > 0 => return Ok(Some((len, i + 1))),
+                b'0'..=b'9' if i == 1 && src[0] == b'0' => {
+                    return Err(Error::invalid("netstring length", &src[..=i]));
+                }
+                b'0'..=b'9' => {
+                    // a length which overflows is over any limit
+                    len = len
+                        .checked_mul(10)
+                        .and_then(|len| len.checked_add(usize::from(byte - b'0')))
+                        .unwrap_or(usize::MAX);
+                    error::LimitExceeded::check("netstring length", len, self.max_len)?;
+                }
+                other => return Err(Error::invalid("netstring length byte", other)),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Decode for Netstring {
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        let (len, prefix_len) = match self.parse_prefix(src)? {
+            Some(prefix) => prefix,
+            None => return Ok(None),
+        };
+
+        let frame_len = len.checked_add(prefix_len + 1).ok_or_else(|| {
+            error::LimitExceeded::new("frame length", len, usize::MAX - prefix_len - 1)
+        })?;
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+
+        let terminator = src[frame_len - 1];
+        if terminator != b',' {
+            return Err(Error::invalid("netstring terminator", terminator));
+        }
+
+        src.advance(prefix_len);
+        let payload = src.split_to(len).freeze();
+        src.advance(1);
+        Ok(Some(payload))
+    }
+}
+
+impl Encode<&[u8]> for Netstring {
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        error::LimitExceeded::check("netstring length", item.len(), self.max_len)?;
+
+        dst.put_slice(item.len().to_string().as_bytes());
+        dst.put_u8(b':');
+        dst.put_slice(item);
+        dst.put_u8(b',');
+        Ok(())
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        item.len().checked_add(MAX_OVERHEAD)
+    }
+}
+
+impl Encode<Bytes> for Netstring {
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> crate::Result<()> {
+        self.encode(&item[..], dst)
+    }
+
+    fn encoded_len(&self, item: &Bytes) -> Option<usize> {
+        item.len().checked_add(MAX_OVERHEAD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Netstring;
+    use crate::{
+        error,
+        framing::{Decode, Encode},
+    };
+    use bytes::BytesMut;
+
+    fn decode(codec: &mut Netstring, src: &[u8]) -> crate::Result<Option<Vec<u8>>> {
+        let mut src = BytesMut::from(src);
+        Ok(codec.decode(&mut src)?.map(|payload| payload.to_vec()))
+    }
+
+    #[test]
+    fn decode_partial() {
+        let mut codec = Netstring::new(16);
+
+        assert_eq!(decode(&mut codec, b"").unwrap(), None);
+        assert_eq!(decode(&mut codec, b"1").unwrap(), None);
+        assert_eq!(decode(&mut codec, b"12:hello").unwrap(), None);
+        assert_eq!(
+            decode(&mut codec, b"12:hello world!,").unwrap(),
+            Some(b"hello world!".to_vec())
+        );
+        assert_eq!(decode(&mut codec, b"0:,").unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut codec = Netstring::new(16);
+
+        assert!(decode(&mut codec, b":").is_err());
+        assert!(decode(&mut codec, b"01:a,").is_err());
+        assert!(decode(&mut codec, b"1a").is_err());
+        assert!(decode(&mut codec, b"1:a;").is_err());
+    }
+
+    #[test]
+    fn max_len() {
+        let mut codec = Netstring::new(16);
+
+        assert_eq!(
+            decode(&mut codec, b"17").unwrap_err(),
+            error::LimitExceeded::new("netstring length", 17, 16).into()
+        );
+        assert_eq!(
+            decode(&mut codec, b"99999999999999999999999999").unwrap_err(),
+            error::LimitExceeded::new("netstring length", 99, 16).into()
+        );
+
+        let mut dst = BytesMut::new();
+        assert_eq!(
+            codec.encode_into(&[0; 17][..], &mut dst).unwrap_err(),
+            error::LimitExceeded::new("netstring length", 17, 16).into()
+        );
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn frame_len_overflow() {
+        let mut codec = Netstring::new(usize::MAX);
+
+        assert!(matches!(
+            decode(&mut codec, b"18446744073709551615:abc").unwrap_err(),
+            crate::Error::LimitExceeded(_)
+        ));
+    }
+}
@@ -0,0 +1,177 @@
+//! Accumulation of partial reads into complete frames
+
+use super::Decode;
+use crate::error;
+use bytes::BytesMut;
+
+/// Accumulates arbitrarily-sized chunks of input and drains complete frames
+/// from them using a [`Decode`] implementation
+///
+/// The number of bytes buffered is capped by a high-watermark, so a peer that
+/// never completes a frame cannot cause unbounded memory use.
+///
+/// ```
+/// use safer_bytes::framing::{LengthDelimited, Reassembler};
+///
+/// let mut reassembler = Reassembler::new(LengthDelimited::new(16), 64);
+///
+/// reassembler.push(&[0, 0, 0, 2, 0xAA]).unwrap();
+/// assert_eq!(reassembler.next_frame().unwrap(), None);
+///
+/// reassembler.push(&[0xBB]).unwrap();
+/// assert_eq!(
+///     reassembler.next_frame().unwrap().unwrap(),
+///     &[0xAA, 0xBB][..]
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Reassembler<D> {
+    decoder: D,
+    buffer: BytesMut,
+    max_buffered: usize,
+}
+
+impl<D> Reassembler<D>
+where
+    D: Decode,
+{
+    /// Construct a new [`Reassembler`], which will buffer at most
+    /// `max_buffered` bytes
+    pub fn new(decoder: D, max_buffered: usize) -> Self {
+        Self {
+            decoder,
+            buffer: BytesMut::new(),
+            max_buffered,
+        }
+    }
+
+    /// Append a chunk of input
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if buffering the chunk would exceed
+    /// the high-watermark. In this case the chunk is not buffered.
+    pub fn push(&mut self, chunk: &[u8]) -> crate::Result<()> {
+        let buffered = self.buffer.len().saturating_add(chunk.len());
+        error::LimitExceeded::check("buffered length", buffered, self.max_buffered)?;
+
+        self.buffer.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// Decode the next complete frame from the buffered input, if there is
+    /// one
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffered input cannot be
+    /// decoded.
+    pub fn next_frame(&mut self) -> crate::Result<Option<D::Item>> {
+        self.decoder.decode(&mut self.buffer)
+    }
+
+    /// Decode the next frame once no more input will arrive
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffered input cannot be
+    /// decoded, or if an incomplete frame is left over.
+    pub fn next_frame_eof(&mut self) -> crate::Result<Option<D::Item>> {
+        self.decoder.decode_eof(&mut self.buffer)
+    }
+
+    /// An iterator which drains every complete frame from the buffered input
+    ///
+    /// Iteration stops at the first incomplete frame, or after the first
+    /// error.
+    pub fn frames(&mut self) -> Frames<'_, D> {
+        Frames {
+            reassembler: self,
+            done: false,
+        }
+    }
+
+    /// The number of bytes currently buffered
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The maximum number of bytes which will be buffered
+    #[must_use]
+    pub fn max_buffered(&self) -> usize {
+        self.max_buffered
+    }
+
+    /// A reference to the underlying decoder
+    pub fn decoder(&self) -> &D {
+        &self.decoder
+    }
+
+    /// A mutable reference to the underlying decoder
+    pub fn decoder_mut(&mut self) -> &mut D {
+        &mut self.decoder
+    }
+
+    /// Consume the [`Reassembler`], returning the decoder and any buffered
+    /// bytes
+    pub fn into_parts(self) -> (D, BytesMut) {
+        (self.decoder, self.buffer)
+    }
+}
+
+/// An iterator over the complete frames in a [`Reassembler`]
+///
+/// This is created by [`Reassembler::frames`].
+#[derive(Debug)]
+pub struct Frames<'a, D> {
+    reassembler: &'a mut Reassembler<D>,
+    done: bool,
+}
+
+impl<D> Iterator for Frames<'_, D>
+where
+    D: Decode,
+{
+    type Item = crate::Result<D::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.reassembler.next_frame().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reassembler;
+    use crate::framing::DelimitedCodec;
+
+    #[test]
+    fn frames() {
+        let mut reassembler = Reassembler::new(DelimitedCodec::lines(8), 16);
+
+        reassembler.push(b"a\nbc\nd").unwrap();
+        let frames: Vec<_> = reassembler.frames().map(Result::unwrap).collect();
+        assert_eq!(frames, vec![&b"a"[..], &b"bc"[..]]);
+        assert_eq!(reassembler.buffered(), 1);
+
+        reassembler.push(b"e").unwrap();
+        assert_eq!(reassembler.next_frame_eof().unwrap().unwrap(), &b"de"[..]);
+    }
+
+    #[test]
+    fn high_watermark() {
+        let mut reassembler = Reassembler::new(DelimitedCodec::lines(8), 4);
+
+        reassembler.push(b"abc").unwrap();
+        assert!(reassembler.push(b"de").is_err());
+        assert_eq!(reassembler.buffered(), 3);
+    }
+}
@@ -0,0 +1,260 @@
+//! Serial Line Internet Protocol (SLIP) framing, as described in RFC 1055
+//!
+//! SLIP terminates each frame with an `END` byte, and escapes any `END` or
+//! `ESC` bytes within the frame. [`encode`] and [`decode`] operate on single
+//! frames (without the terminator), while [`Slip`] is a streaming codec
+//! which handles the terminators.
+
+use super::{Decode, Encode};
+use crate::{error, Error};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Marks the end of a frame
+pub const END: u8 = 0xC0;
+
+/// Begins an escape sequence
+pub const ESC: u8 = 0xDB;
+
+/// An escaped `END` byte (follows `ESC`)
+pub const ESC_END: u8 = 0xDC;
+
+/// An escaped `ESC` byte (follows `ESC`)
+pub const ESC_ESC: u8 = 0xDD;
+
+/// The maximum length of the SLIP encoding of `len` bytes (excluding the
+/// terminator)
+///
+/// This saturates at `usize::MAX`.
+#[must_use]
+pub const fn max_encoded_len(len: usize) -> usize {
+    len.saturating_mul(2)
+}
+
+/// SLIP-encode a frame, appending it to `dst`
+///
+/// The terminator is not appended.
+pub fn encode(src: &[u8], dst: &mut BytesMut) {
+    dst.reserve(src.len());
+
+    for &byte in src {
+        match byte {
+            END => dst.put_slice(&[ESC, ESC_END]),
+            ESC => dst.put_slice(&[ESC, ESC_ESC]),
+            byte => dst.put_u8(byte),
+        }
+    }
+}
+
+/// Decode a SLIP-encoded frame
+///
+/// The frame should not include the terminator.
+///
+/// # Errors
+///
+/// This method will return an error if the frame contains an invalid escape
+/// sequence (an `ESC` byte followed by anything other than `ESC_END` or
+/// `ESC_ESC`, or at the end of the frame), or an unescaped `END` byte.
+pub fn decode(src: &[u8]) -> crate::Result<Bytes> {
+    let mut dst = BytesMut::with_capacity(src.len());
+    let mut bytes = src.iter().copied();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            ESC => match bytes.next() {
+                Some(ESC_END) => dst.put_u8(END),
+                Some(ESC_ESC) => dst.put_u8(ESC),
+                Some(other) => return Err(Error::invalid("SLIP escape sequence", [ESC, other])),
+                None => return Err(Error::invalid("SLIP escape sequence", [ESC])),
+            },
+            END => return Err(Error::invalid("SLIP frame byte", END)),
+            byte => dst.put_u8(byte),
+        }
+    }
+
+    Ok(dst.freeze())
+}
+
+/// A streaming SLIP codec
+///
+/// Each encoded frame is terminated by an `END` byte. Empty frames (such as
+/// those created by a leading `END` used to flush line noise) are skipped
+/// when decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slip {
+    max_frame_len: usize,
+    /// The offset at which to resume searching for the terminator, so that
+    /// partial frames are not rescanned after each read
+    next_index: usize,
+}
+
+impl Slip {
+    /// Construct a new codec, accepting frames of up to `max_frame_len` bytes
+    /// (before encoding)
+    #[must_use]
+    pub fn new(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            next_index: 0,
+        }
+    }
+}
+
+impl Decode for Slip {
+    type Item = Bytes;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Bytes>> {
+        loop {
+            // the buffer may have been cleared or replaced since the last call
+            if self.next_index > src.len() {
+                self.next_index = 0;
+            }
+
+            let end = match src[self.next_index..].iter().position(|&b| b == END) {
+                Some(i) => self.next_index + i,
+                None if src.len() > max_encoded_len(self.max_frame_len) => {
+                    self.next_index = 0;
+                    return Err(error::LimitExceeded::new(
+                        "encoded frame length",
+                        src.len(),
+                        max_encoded_len(self.max_frame_len),
+                    )
+                    .into());
+                }
+                None => {
+                    self.next_index = src.len();
+                    return Ok(None);
+                }
+            };
+
+            self.next_index = 0;
+            let frame = src.split_to(end);
+            src.advance(1);
+
+            if frame.is_empty() {
+                continue;
+            }
+
+            let decoded = decode(&frame)?;
+            error::LimitExceeded::check("frame length", decoded.len(), self.max_frame_len)?;
+            return Ok(Some(decoded));
+        }
+    }
+}
+
+impl Encode<&[u8]> for Slip {
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> crate::Result<()> {
+        error::LimitExceeded::check("frame length", item.len(), self.max_frame_len)?;
+        encode(item, dst);
+        dst.put_u8(END);
+        Ok(())
+    }
+
+    fn encoded_len(&self, item: &&[u8]) -> Option<usize> {
+        Some(max_encoded_len(item.len()).saturating_add(1))
+    }
+}
+
+impl Encode<Bytes> for Slip {
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> crate::Result<()> {
+        self.encode(&item[..], dst)
+    }
+
+    fn encoded_len(&self, item: &Bytes) -> Option<usize> {
+        Some(max_encoded_len(item.len()).saturating_add(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, max_encoded_len, Slip, END, ESC, ESC_END, ESC_ESC};
+    use crate::{
+        error,
+        framing::{Decode, Encode},
+    };
+    use bytes::BytesMut;
+
+    #[test]
+    fn round_trip() {
+        let input = [1, END, 2, ESC, 3];
+
+        let mut encoded = BytesMut::new();
+        encode(&input, &mut encoded);
+        assert_eq!(&encoded[..], &[1, ESC, ESC_END, 2, ESC, ESC_ESC, 3]);
+
+        assert_eq!(decode(&encoded).unwrap(), &input[..]);
+    }
+
+    #[test]
+    fn invalid_escape() {
+        assert!(decode(&[1, ESC, 0x42]).is_err());
+        assert!(decode(&[1, ESC]).is_err());
+        assert!(decode(&[1, END]).is_err());
+    }
+
+    #[test]
+    fn streaming() {
+        let mut codec = Slip::new(16);
+        let mut buffer = BytesMut::from(&[END][..]);
+
+        codec.encode_into(&[1, END][..], &mut buffer).unwrap();
+        buffer.extend_from_slice(&[2, 3]);
+
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[1, END][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+
+        buffer.extend_from_slice(&[END]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[2, 3][..]);
+    }
+
+    #[test]
+    fn max_frame_len() {
+        let mut codec = Slip::new(2);
+
+        let mut buffer = BytesMut::from(&[1, 2, 3, 4, 5][..]);
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap_err(),
+            error::LimitExceeded::new("encoded frame length", 5, 4).into()
+        );
+
+        let mut buffer = BytesMut::from(&[1, 2, 3, END][..]);
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap_err(),
+            error::LimitExceeded::new("frame length", 3, 2).into()
+        );
+
+        let mut dst = BytesMut::new();
+        assert_eq!(
+            codec.encode_into(&[1, 2, 3][..], &mut dst).unwrap_err(),
+            error::LimitExceeded::new("frame length", 3, 2).into()
+        );
+    }
+
+    #[test]
+    fn max_encoded_len_saturates() {
+        assert_eq!(max_encoded_len(3), 6);
+        assert_eq!(max_encoded_len(usize::MAX), usize::MAX);
+        assert_eq!(
+            Slip::new(16).encoded_len(&&[0_u8; 3][..]),
+            Some(max_encoded_len(3) + 1)
+        );
+    }
+
+    #[test]
+    fn resume_after_clear() {
+        let mut codec = Slip::new(16);
+
+        let mut buffer = BytesMut::from(&[1, 2, 3][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.extend_from_slice(&[4, END]);
+        assert_eq!(
+            codec.decode(&mut buffer).unwrap().unwrap(),
+            &[1, 2, 3, 4][..]
+        );
+
+        let mut buffer = BytesMut::from(&[1, 2, 3][..]);
+        assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+        buffer.clear();
+        buffer.extend_from_slice(&[5, END]);
+        assert_eq!(codec.decode(&mut buffer).unwrap().unwrap(), &[5][..]);
+    }
+}
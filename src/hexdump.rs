@@ -0,0 +1,112 @@
+//! Hexdump rendering for debugging buffer contents
+
+use bytes::Bytes;
+use core::fmt;
+
+/// The number of bytes rendered per line
+const BYTES_PER_LINE: usize = 16;
+
+/// A classic offset/hex/ASCII rendering of a sequence of bytes
+///
+/// ```
+/// use safer_bytes::HexDump;
+///
+/// let dump = HexDump::new(&b"hello, world"[..]);
+/// assert_eq!(
+///     dump.to_string(),
+///     "00000000  68 65 6c 6c 6f 2c 20 77  6f 72 6c 64              |hello, world|\n"
+/// );
+/// ```
+///
+/// This is created by
+/// [`SafeBuf::debug_remaining`](crate::SafeBuf::debug_remaining), or can be
+/// constructed directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexDump {
+    bytes: Bytes,
+    offset: usize,
+}
+
+impl HexDump {
+    /// Construct a new [`HexDump`] from a sequence of bytes
+    pub fn new(bytes: impl Into<Bytes>) -> Self {
+        Self {
+            bytes: bytes.into(),
+            offset: 0,
+        }
+    }
+
+    /// Set the offset which is displayed for the first byte
+    ///
+    /// This is useful when dumping a region from the middle of a stream.
+    #[must_use]
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// The bytes being rendered
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Display for HexDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.bytes.chunks(BYTES_PER_LINE).enumerate() {
+            write!(f, "{:08x}  ", self.offset + i * BYTES_PER_LINE)?;
+
+            for column in 0..BYTES_PER_LINE {
+                match line.get(column) {
+                    Some(byte) => write!(f, "{:02x} ", byte)?,
+                    None => f.write_str("   ")?,
+                }
+                if column == BYTES_PER_LINE / 2 - 1 {
+                    f.write_str(" ")?;
+                }
+            }
+
+            f.write_str(" |")?;
+            for &byte in line {
+                let c = if byte.is_ascii_graphic() || byte == b' ' {
+                    char::from(byte)
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            f.write_str("|\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexDump;
+    use crate::SafeBuf;
+
+    #[test]
+    fn multi_line() {
+        let bytes: Vec<u8> = (0..20).collect();
+        let dump = HexDump::new(bytes).with_offset(0x100);
+
+        assert_eq!(
+            dump.to_string(),
+            "00000100  00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f  \
+             |................|\n00000110  10 11 12 13                                       \
+             |....|\n"
+        );
+    }
+
+    #[test]
+    fn debug_remaining() {
+        let buffer = &b"abcdef"[..];
+
+        assert_eq!(buffer.debug_remaining(4).bytes(), b"abcd");
+        assert_eq!(buffer.debug_remaining(10).bytes(), b"abcdef");
+        assert_eq!(buffer.len(), 6);
+    }
+}
@@ -1,4 +1,25 @@
 //! A safe, non-panicking wrapper around [`bytes::Buf`]
+//!
+//! The crate is `no_std` compatible (requiring only `alloc`) when the default
+//! `std` feature is disabled. Without `std`, peeking and searching are limited
+//! to the first chunk of a non-contiguous buffer, since
+//! [`Buf::chunks_vectored`] is unavailable.
+//!
+//! # Allocation-free reads
+//!
+//! [`Bytes`] is reference counted, so methods returning it (such as
+//! [`SafeBuf::try_copy_to_bytes`] and [`SafeBuf::try_peek`]) may allocate. Hot
+//! paths and targets with strict memory budgets can use the methods which
+//! copy into caller-owned storage or borrow from the buffer instead:
+//!
+//! - [`SafeBuf::try_copy_to_slice`], [`SafeBuf::try_take_into`],
+//!   [`SafeBuf::try_take_const`] and [`SafeBuf::try_copy_to_uninit`] copy into
+//!   a slice or array
+//! - [`SafeBuf::try_peek_to_slice`] and [`SafeBuf::try_peek_const`] do the same
+//!   without advancing the buffer
+//! - [`SafeBuf::try_chunk_exact`] borrows directly from the current chunk
+//! - [`SliceReader::try_take`] and [`SliceReader::try_peek`] borrow from a
+//!   contiguous input
 
 #![deny(
     clippy::all,
@@ -8,25 +29,110 @@
     missing_debug_implementations
 )]
 #![warn(clippy::pedantic)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 use bytes::Buf;
 pub use bytes::{BufMut, Bytes, BytesMut};
 
+mod bit_reader;
+mod bit_writer;
+mod buffered;
+mod chunked_buf;
+pub mod const_fn;
+#[cfg(feature = "crc")]
+pub mod crc;
+mod cursor;
+mod depth_limited;
+pub mod dynamic;
+mod endian;
 pub mod error;
+#[cfg(feature = "bitflags")]
+pub mod flags;
+pub mod framing;
+mod hexdump;
+mod limited;
+mod lookahead;
+mod map;
+pub mod metrics;
+pub mod net;
+mod poisoning;
+mod quota;
+#[cfg(feature = "std")]
+mod read_buf;
+mod ring_buf;
 mod safe_buf;
+mod safe_buf_back;
+mod safe_split;
+mod search;
+#[cfg(feature = "std")]
+mod seek_buf;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub mod serde;
+mod slice_reader;
+mod strict;
+mod sub_buf;
+mod tee;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod text;
+mod traced;
+mod utf8;
+#[cfg(feature = "trusted")]
+mod validated;
+pub mod wire;
 
 /// Unchecked buffer reading methods
+///
+/// This module can be removed by disabling the `unchecked` feature (enabled by
+/// default), so that a dependency audit can confirm only the fallible reads
+/// are used.
+#[cfg(feature = "unchecked")]
 pub mod unchecked {
     pub use bytes::Buf;
 }
 
 #[doc(inline)]
-pub use error::Error;
+pub use error::{Error, ResultExt};
 
 /// Type alias for the return type of fallible functions in this crate
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
+pub use bit_reader::BitReader;
+pub use bit_writer::BitWriter;
+pub use buffered::Buffered;
+pub use chunked_buf::ChunkedBuf;
+pub use cursor::SafeCursor;
+pub use depth_limited::DepthLimited;
+pub use endian::Endian;
+pub use hexdump::HexDump;
+pub use limited::Limited;
+pub use lookahead::Lookahead;
+pub use map::{DuplicateKeys, Map};
+pub use net::{Eui64, MacAddr};
+pub use poisoning::Poisoning;
+pub use quota::Quota;
+#[cfg(feature = "std")]
+pub use read_buf::ReadBuf;
+pub use ring_buf::RingBuf;
 pub use safe_buf::SafeBuf;
+pub use safe_buf_back::SafeBufBack;
+pub use safe_split::SafeSplit;
+pub use search::{Delimiter, SplitOnPattern};
+#[cfg(feature = "std")]
+pub use seek_buf::SeekBuf;
+pub use slice_reader::SliceReader;
+pub use strict::Strict;
+pub use sub_buf::SubBuf;
+#[cfg(feature = "std")]
+pub use tee::IoSink;
+pub use tee::{Tee, TeeSink};
+pub use text::Base64Alphabet;
+pub use traced::{ReadTrace, TraceSpan, Traced};
+pub use utf8::Utf8Validator;
+#[cfg(feature = "trusted")]
+pub use validated::ValidatedBuf;
 
 /// Objects which implement [`FromBuf`] are capable of constructing themselves
 /// by reading bytes from a [`Buf`]
@@ -41,3 +147,20 @@ pub trait FromBuf: Sized {
     where
         B: Buf;
 }
+
+/// Objects which implement [`ToBuf`] are capable of writing themselves to a
+/// buffer
+///
+/// This is the counterpart to [`FromBuf`].
+pub trait ToBuf {
+    /// write `self` to a buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer does not have enough
+    /// space remaining (in which case nothing should have been written), or if
+    /// the object cannot be encoded.
+    fn to_buf<B>(&self, buffer: B) -> Result<()>
+    where
+        B: BufMut;
+}
@@ -0,0 +1,176 @@
+//! A [`bytes::Buf`] adapter which restricts reads to a fixed-size region
+
+use crate::error;
+#[cfg(feature = "std")]
+use crate::sub_buf::chunks_vectored_limited;
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A buffer adapter which restricts reads to the next `limit` bytes of the
+/// underlying buffer
+///
+/// This is created by [`SafeBuf::limit_safe`](crate::SafeBuf::limit_safe).
+/// Reads which would cross the end of the region fail with
+/// [`error::Truncated`], rather than spilling into whatever follows it, so a
+/// nested parser cannot consume bytes belonging to its parent. Use
+/// [`Limited::finish`] to check that the region was fully consumed.
+///
+/// Unlike [`SubBuf`](crate::SubBuf), the region need not be present in the
+/// underlying buffer up front, and the adapter owns the underlying buffer
+/// (which may be a `&mut` reference).
+///
+/// ```
+/// use safer_bytes::SafeBuf;
+///
+/// let mut buffer = &[0x00, 0x01, 0x02, 0x03][..];
+///
+/// let mut region = (&mut buffer).limit_safe(3);
+/// assert_eq!(region.try_get_u16()?, 0x0001);
+/// assert!(region.try_get_u16().is_err());
+/// assert_eq!(region.try_get_u8()?, 0x02);
+/// region.finish()?;
+///
+/// assert_eq!(buffer, &[0x03]);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Limited<B> {
+    inner: B,
+    limit: usize,
+}
+
+impl<B> Limited<B>
+where
+    B: Buf,
+{
+    pub(crate) fn new(inner: B, limit: usize) -> Self {
+        Self { inner, limit }
+    }
+
+    /// The number of bytes of the region which have not yet been read
+    ///
+    /// This may be more than [`Buf::remaining`] if the underlying buffer does
+    /// not contain the whole region.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// A reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the adapter, returning the underlying buffer regardless of
+    /// whether the region was fully consumed
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Consume the adapter, checking that the region was fully consumed, and
+    /// returning the underlying buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return [`error::ExtraneousBytes`] if part of the
+    /// region was left unread, or [`error::Truncated`] if the underlying
+    /// buffer ended before the end of the region.
+    pub fn finish(self) -> crate::Result<B> {
+        if self.limit == 0 {
+            return Ok(self.inner);
+        }
+
+        let available = self.inner.remaining();
+        if available < self.limit {
+            Err(error::Truncated::new(self.limit, available).into())
+        } else {
+            Err(error::ExtraneousBytes::from_buf(&self).into())
+        }
+    }
+}
+
+impl<B> Buf for Limited<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining().min(self.limit)
+    }
+
+    fn chunk(&self) -> &[u8] {
+        let chunk = self.inner.chunk();
+        &chunk[..chunk.len().min(self.limit)]
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        chunks_vectored_limited(&self.inner, self.limit, dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.limit,
+            "cannot advance past the end of the region"
+        );
+        self.inner.advance(cnt);
+        self.limit -= cnt;
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(
+            len <= self.limit,
+            "cannot advance past the end of the region"
+        );
+        self.limit -= len;
+        self.inner.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, SafeBuf};
+
+    #[test]
+    fn overrun() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        let mut region = (&mut buffer).limit_safe(4);
+        assert_eq!(region.try_get_u16().unwrap(), 0x0001);
+        assert!(region.try_get_u32().is_err());
+        assert_eq!(region.limit(), 2);
+
+        assert!(matches!(
+            region.finish().unwrap_err(),
+            Error::ExtraneousBytes(_)
+        ));
+        assert_eq!(buffer, &[2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn truncated_region() {
+        let buffer = &[0_u8, 1][..];
+
+        let mut region = buffer.limit_safe(4);
+        assert_eq!(region.try_get_u16().unwrap(), 0x0001);
+        assert!(matches!(region.finish().unwrap_err(), Error::Truncated(_)));
+    }
+
+    #[test]
+    fn finish() {
+        let buffer = &[0_u8, 1, 2][..];
+
+        let mut region = buffer.limit_safe(2);
+        assert_eq!(region.try_copy_to_bytes(2).unwrap(), &[0, 1][..]);
+        assert_eq!(region.finish().unwrap(), &[2]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn peek_across_chunks() {
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1][..], &[2_u8, 3, 4][..]);
+
+        let region = (&mut buffer).limit_safe(4);
+        assert_eq!(region.try_peek(4).unwrap(), &[0, 1, 2, 3][..]);
+        assert!(region.try_peek(5).is_err());
+    }
+}
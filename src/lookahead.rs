@@ -0,0 +1,188 @@
+//! A bounded lookahead window over a [`bytes::Buf`]
+
+use crate::{error, Error};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A wrapper around a [`Buf`] which can buffer up to a fixed number of bytes
+/// internally
+///
+/// Peeking at a buffer with [`SafeBuf::try_peek`](crate::SafeBuf::try_peek)
+/// only works if the requested bytes can be inspected without advancing the
+/// buffer. Chained or streaming sources may not be able to provide that, and
+/// may not be cloneable either, which rules out
+/// [`SafeBuf::transaction`](crate::SafeBuf::transaction).
+///
+/// A [`Lookahead`] moves bytes from the underlying buffer into a contiguous
+/// internal window on request (see [`Lookahead::try_fill`]), so that peeking
+/// and backtracking work within that window regardless of the underlying
+/// buffer.
+#[derive(Debug)]
+pub struct Lookahead<B> {
+    inner: B,
+    window: BytesMut,
+    capacity: usize,
+}
+
+impl<B> Lookahead<B>
+where
+    B: Buf,
+{
+    /// Wrap a buffer, allowing up to `capacity` bytes to be buffered
+    pub fn new(inner: B, capacity: usize) -> Self {
+        Self {
+            inner,
+            window: BytesMut::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// The maximum number of bytes which can be buffered
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The bytes which are currently buffered
+    pub fn buffered(&self) -> &[u8] {
+        &self.window
+    }
+
+    /// Ensure that (at least) the next `len` bytes are buffered, returning them
+    /// as a contiguous slice
+    ///
+    /// Once filled, peeking at these bytes with the [`SafeBuf`](crate::SafeBuf)
+    /// methods will always succeed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return [`Error::LimitExceeded`] if `len` exceeds the
+    /// capacity of the window, or [`Error::Truncated`] if the number of bytes
+    /// remaining in the buffer is insufficent
+    pub fn try_fill(&mut self, len: usize) -> crate::Result<&[u8]> {
+        error::LimitExceeded::check("lookahead length", len, self.capacity)?;
+        error::Truncated::check(len, self.remaining())?;
+
+        while self.window.len() < len {
+            let chunk = self.inner.chunk();
+            let count = chunk.len().min(len - self.window.len());
+            self.window.put_slice(&chunk[..count]);
+            self.inner.advance(count);
+        }
+
+        Ok(&self.window[..len])
+    }
+
+    /// Run a fallible parse against the next `len` bytes, only advancing the
+    /// buffer if the parse succeeds
+    ///
+    /// The closure receives a copy of the window, and the buffer is advanced by
+    /// however many bytes the closure consumed from it.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the window cannot be filled (see
+    /// [`Lookahead::try_fill`]), or any error returned by the closure
+    pub fn speculate<T, E, F>(&mut self, len: usize, f: F) -> core::result::Result<T, E>
+    where
+        E: From<Error>,
+        F: FnOnce(&mut Bytes) -> core::result::Result<T, E>,
+    {
+        let mut window = Bytes::copy_from_slice(self.try_fill(len)?);
+        let value = f(&mut window)?;
+        self.window.advance(len - window.len());
+        Ok(value)
+    }
+
+    /// Get a reference to the underlying buffer
+    ///
+    /// Note that this does not include any bytes which have been buffered.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B> Buf for Lookahead<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.window.len() + self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        if self.window.is_empty() {
+            self.inner.chunk()
+        } else {
+            &self.window
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        if self.window.is_empty() {
+            return self.inner.chunks_vectored(dst);
+        }
+
+        match dst.split_first_mut() {
+            Some((first, rest)) => {
+                *first = IoSlice::new(&self.window);
+                1 + self.inner.chunks_vectored(rest)
+            }
+            None => 0,
+        }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        let buffered = cnt.min(self.window.len());
+        self.window.advance(buffered);
+        self.inner.advance(cnt - buffered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lookahead;
+    use crate::{error, Error, SafeBuf};
+
+    fn chained() -> impl bytes::Buf {
+        let first = bytes::Buf::chain(&[0_u8, 1][..], &[2_u8, 3][..]);
+        bytes::Buf::chain(first, &[4_u8, 5, 6, 7][..])
+    }
+
+    #[test]
+    fn try_fill() {
+        let mut buffer = Lookahead::new(chained(), 6);
+
+        assert_eq!(buffer.try_fill(5).unwrap(), &[0, 1, 2, 3, 4]);
+        assert_eq!(buffer.try_peek(5).unwrap(), &[0, 1, 2, 3, 4][..]);
+        assert!(matches!(
+            buffer.try_fill(7).unwrap_err(),
+            Error::LimitExceeded(_)
+        ));
+
+        assert_eq!(buffer.try_get_u32().unwrap(), 0x0001_0203);
+        assert_eq!(buffer.try_get_u32().unwrap(), 0x0405_0607);
+        assert_eq!(
+            buffer.try_fill(1).unwrap_err(),
+            error::Truncated::new(1, 0).into()
+        );
+    }
+
+    #[test]
+    fn speculate() {
+        let mut buffer = Lookahead::new(chained(), 4);
+
+        let result: crate::Result<u32> = buffer.speculate(2, |window| {
+            window.try_get_u8()?;
+            Ok(window.try_get_u32()?)
+        });
+        assert!(result.is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 8);
+
+        let result: crate::Result<u16> = buffer.speculate(4, |window| Ok(window.try_get_u16()?));
+        assert_eq!(result.unwrap(), 0x0001);
+        assert_eq!(buffer.buffered(), &[2, 3]);
+        assert_eq!(buffer.try_get_u16().unwrap(), 0x0203);
+    }
+}
@@ -0,0 +1,61 @@
+//! Targets for [`SafeBuf::extract_map`](crate::SafeBuf::extract_map)
+
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// What to do when a key appears more than once in an encoded map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Return an error
+    Reject,
+
+    /// Keep the value of the first entry with the key
+    KeepFirst,
+
+    /// Keep the value of the last entry with the key
+    KeepLast,
+}
+
+/// A map which can be decoded with
+/// [`SafeBuf::extract_map`](crate::SafeBuf::extract_map)
+///
+/// This is implemented for [`BTreeMap`] and (with the `std` feature)
+/// `HashMap`.
+pub trait Map<K, V>: Default {
+    /// Whether the map contains an entry for `key`
+    fn contains_key(&self, key: &K) -> bool;
+
+    /// Insert an entry, replacing any existing entry with the same key
+    fn insert(&mut self, key: K, value: V);
+}
+
+impl<K, V> Map<K, V> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn contains_key(&self, key: &K) -> bool {
+        Self::contains_key(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        Self::insert(self, key, value);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> Map<K, V> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn contains_key(&self, key: &K) -> bool {
+        Self::contains_key(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        Self::insert(self, key, value);
+    }
+}
@@ -0,0 +1,393 @@
+//! Hooks for reporting parse metrics
+//!
+//! Long-running services often want to chart how many bytes and frames they
+//! have parsed, and how many parses failed (and why). Rather than wrapping
+//! every call site, the reading and framing layers can be wrapped once:
+//!
+//! - [`Metered`] wraps a [`Buf`], reporting the bytes consumed and the errors
+//!   returned by [`Metered::read_with`] and [`Metered::extract`]
+//! - [`MeteredDecoder`] wraps a [`Decode`] implementation, reporting each frame
+//!   decoded and each decoding error
+//!
+//! Each [`Event`] is passed to a [`Metrics`] implementation. This can be a
+//! closure (forwarding to a metrics library, for example), or a [`Counters`]
+//! which simply totals them up.
+//!
+//! ```
+//! use safer_bytes::{
+//!     error::ErrorCode,
+//!     metrics::{Counters, Metered},
+//!     SafeBuf,
+//! };
+//!
+//! let mut buffer = Metered::new(&[0x00, 0x01, 0x02][..], Counters::default());
+//!
+//! buffer.read_with(|b| Ok(b.try_get_u16()?))?;
+//! assert!(buffer.read_with(|b| Ok(b.try_get_u16()?)).is_err());
+//!
+//! let counters = buffer.metrics();
+//! assert_eq!(counters.bytes_consumed(), 2);
+//! assert_eq!(counters.errors(ErrorCode::Truncated), 1);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{
+    error::ErrorCode,
+    framing::{Decode, Decoded},
+    FromBuf,
+};
+use alloc::collections::BTreeMap;
+use bytes::{Buf, BytesMut};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// Something worth counting which happened while parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Event {
+    /// This many bytes were consumed from a buffer
+    Consumed(usize),
+
+    /// A frame was decoded, consuming this many bytes
+    FrameDecoded(usize),
+
+    /// A parse failed with an error of this kind
+    Failed(ErrorCode),
+}
+
+/// Objects which implement [`Metrics`] receive the [`Event`]s reported by
+/// [`Metered`] and [`MeteredDecoder`]
+///
+/// This is implemented for any `FnMut(Event)` closure.
+pub trait Metrics {
+    /// Record an event
+    fn record(&mut self, event: Event);
+}
+
+impl<F> Metrics for F
+where
+    F: FnMut(Event),
+{
+    fn record(&mut self, event: Event) {
+        self(event);
+    }
+}
+
+/// A [`Metrics`] implementation which totals up the events it receives
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counters {
+    bytes_consumed: u64,
+    frames_decoded: u64,
+    errors: BTreeMap<ErrorCode, u64>,
+}
+
+impl Counters {
+    /// The total number of bytes consumed from buffers
+    #[must_use]
+    pub fn bytes_consumed(&self) -> u64 {
+        self.bytes_consumed
+    }
+
+    /// The total number of frames decoded
+    #[must_use]
+    pub fn frames_decoded(&self) -> u64 {
+        self.frames_decoded
+    }
+
+    /// The number of errors of the given kind
+    #[must_use]
+    pub fn errors(&self, code: ErrorCode) -> u64 {
+        self.errors.get(&code).copied().unwrap_or_default()
+    }
+
+    /// The number of errors of each kind which has occurred, in order of
+    /// [`ErrorCode`]
+    pub fn errors_by_code(&self) -> impl Iterator<Item = (ErrorCode, u64)> + '_ {
+        self.errors.iter().map(|(&code, &count)| (code, count))
+    }
+
+    /// The total number of errors, of all kinds
+    #[must_use]
+    pub fn total_errors(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+impl Metrics for Counters {
+    fn record(&mut self, event: Event) {
+        match event {
+            Event::Consumed(len) => self.bytes_consumed += len as u64,
+            Event::FrameDecoded(_) => self.frames_decoded += 1,
+            Event::Failed(code) => *self.errors.entry(code).or_default() += 1,
+        }
+    }
+}
+
+/// A wrapper around a [`Buf`] which reports the bytes consumed from it, and
+/// the errors returned by parses run against it
+///
+/// Errors returned by the [`SafeBuf`](crate::SafeBuf) methods called directly
+/// on the wrapper are not reported, since these are often handled by the
+/// caller. Run a parse through [`Metered::read_with`] or
+/// [`Metered::extract`] to report its failure.
+#[derive(Debug, Clone)]
+pub struct Metered<B, M> {
+    inner: B,
+    metrics: M,
+}
+
+impl<B, M> Metered<B, M> {
+    /// Wrap a buffer, reporting to `metrics`
+    pub fn new(inner: B, metrics: M) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Get a reference to the metrics
+    pub fn metrics(&self) -> &M {
+        &self.metrics
+    }
+
+    /// Get a mutable reference to the metrics
+    ///
+    /// This is useful for resetting counters after they have been exported.
+    pub fn metrics_mut(&mut self) -> &mut M {
+        &mut self.metrics
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer and the metrics
+    pub fn into_parts(self) -> (B, M) {
+        (self.inner, self.metrics)
+    }
+}
+
+impl<B, M> Metered<B, M>
+where
+    B: Buf,
+    M: Metrics,
+{
+    /// Run a parse against the buffer, reporting its error if it fails
+    ///
+    /// # Errors
+    ///
+    /// This method will return any error returned by the parse.
+    pub fn read_with<T, F>(&mut self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&mut Self) -> crate::Result<T>,
+    {
+        f(self).map_err(|error| {
+            self.metrics.record(Event::Failed(error.code()));
+            error
+        })
+    }
+
+    /// Read a custom object from the buffer, reporting its error if it fails
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the type cannot be parsed from the
+    /// bytes.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.read_with(|buffer| T::from_buf(buffer))
+    }
+}
+
+impl<B, M> Buf for Metered<B, M>
+where
+    B: Buf,
+    M: Metrics,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.advance(cnt);
+        if cnt > 0 {
+            self.metrics.record(Event::Consumed(cnt));
+        }
+    }
+}
+
+/// A wrapper around a [`Decode`] implementation which reports each frame
+/// decoded, and each decoding error
+///
+/// ```
+/// use safer_bytes::{
+///     framing::{Decode, LengthDelimited, PrefixWidth},
+///     metrics::{Event, MeteredDecoder},
+///     BytesMut,
+/// };
+///
+/// let mut events = Vec::new();
+/// let mut decoder = MeteredDecoder::new(
+///     LengthDelimited::new(1024).prefix_width(PrefixWidth::U8),
+///     |event| events.push(event),
+/// );
+///
+/// let mut src = BytesMut::from(&[2, b'h', b'i', 5][..]);
+/// decoder.decode(&mut src)?;
+/// decoder.decode(&mut src)?;
+/// drop(decoder);
+///
+/// assert_eq!(events, [Event::FrameDecoded(3)]);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeteredDecoder<D, M> {
+    inner: D,
+    metrics: M,
+}
+
+impl<D, M> MeteredDecoder<D, M> {
+    /// Wrap a decoder, reporting to `metrics`
+    pub fn new(inner: D, metrics: M) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Get a reference to the metrics
+    pub fn metrics(&self) -> &M {
+        &self.metrics
+    }
+
+    /// Get a mutable reference to the metrics
+    ///
+    /// This is useful for resetting counters after they have been exported.
+    pub fn metrics_mut(&mut self) -> &mut M {
+        &mut self.metrics
+    }
+
+    /// Get a reference to the underlying decoder
+    pub fn get_ref(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying decoder and the metrics
+    pub fn into_parts(self) -> (D, M) {
+        (self.inner, self.metrics)
+    }
+}
+
+impl<D, M> MeteredDecoder<D, M>
+where
+    M: Metrics,
+{
+    /// Report the outcome of a decode, given the length of the buffer before
+    /// it
+    fn report<T>(
+        &mut self,
+        before: usize,
+        src: &BytesMut,
+        result: crate::Result<T>,
+        decoded: impl FnOnce(&T) -> bool,
+    ) -> crate::Result<T> {
+        match &result {
+            Ok(item) if decoded(item) => {
+                self.metrics
+                    .record(Event::FrameDecoded(before.saturating_sub(src.len())));
+            }
+            Ok(_) => {}
+            Err(e) => self.metrics.record(Event::Failed(e.code())),
+        }
+        result
+    }
+}
+
+impl<D, M> Decode for MeteredDecoder<D, M>
+where
+    D: Decode,
+    M: Metrics,
+{
+    type Item = D::Item;
+
+    fn decode(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>> {
+        let before = src.len();
+        let result = self.inner.decode(src);
+        self.report(before, src, result, Option::is_some)
+    }
+
+    fn try_decode(&mut self, src: &mut BytesMut) -> crate::Result<Decoded<Self::Item>> {
+        let before = src.len();
+        let result = self.inner.try_decode(src);
+        self.report(before, src, result, |decoded| {
+            matches!(decoded, Decoded::Item(_))
+        })
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> crate::Result<Option<Self::Item>> {
+        let before = src.len();
+        let result = self.inner.decode_eof(src);
+        self.report(before, src, result, Option::is_some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counters, Metered, MeteredDecoder};
+    use crate::{
+        error::ErrorCode,
+        framing::{Decode, Decoded, LengthDelimited, PrefixWidth},
+        BytesMut, Error, MacAddr, SafeBuf,
+    };
+
+    #[test]
+    fn metered_buffer() {
+        let mut buffer = Metered::new(&[1, 2, 3, 4, 5, 6, 7, 8][..], Counters::default());
+
+        buffer.extract::<MacAddr>().unwrap();
+        SafeBuf::try_advance(&mut buffer, 1).unwrap();
+        assert_eq!(
+            buffer
+                .read_with(|_| Err::<(), _>(Error::invalid("tag", 0_u8)))
+                .unwrap_err(),
+            Error::invalid("tag", 0_u8)
+        );
+        assert!(buffer.extract::<MacAddr>().is_err());
+
+        let (_, counters) = buffer.into_parts();
+        assert_eq!(counters.bytes_consumed(), 7);
+        assert_eq!(counters.errors(ErrorCode::InvalidValue), 1);
+        assert_eq!(counters.errors(ErrorCode::Truncated), 1);
+        assert_eq!(counters.errors(ErrorCode::Custom), 0);
+        assert_eq!(counters.total_errors(), 2);
+        assert_eq!(
+            counters.errors_by_code().collect::<Vec<_>>(),
+            [(ErrorCode::Truncated, 1), (ErrorCode::InvalidValue, 1)]
+        );
+    }
+
+    #[test]
+    fn metered_decoder() {
+        let mut decoder = MeteredDecoder::new(
+            LengthDelimited::new(2).prefix_width(PrefixWidth::U8),
+            Counters::default(),
+        );
+        let mut src = BytesMut::from(&[1, 0xAA, 2, 0xBB][..]);
+
+        assert!(decoder.decode(&mut src).unwrap().is_some());
+        assert_eq!(decoder.try_decode(&mut src).unwrap(), Decoded::NeedMore(1));
+        assert!(decoder.decode_eof(&mut src).is_err());
+        assert_eq!(decoder.metrics().frames_decoded(), 1);
+        assert_eq!(decoder.metrics().errors(ErrorCode::Truncated), 1);
+
+        let mut src = BytesMut::from(&[3, 0, 0, 0][..]);
+        assert!(decoder.decode(&mut src).is_err());
+        assert_eq!(decoder.metrics().errors(ErrorCode::LimitExceeded), 1);
+    }
+}
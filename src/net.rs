@@ -0,0 +1,310 @@
+//! Extraction of network address types
+//!
+//! [`FromBuf`] is implemented for `Ipv4Addr` and `Ipv6Addr` (in network byte
+//! order). Socket addresses have no single wire layout, so they are read with
+//! `try_get_socks5_addr` or `try_get_sockaddr` instead. These require the
+//! `std` feature.
+//!
+//! Link-layer addresses are represented by [`MacAddr`] and [`Eui64`].
+
+use crate::{error, FromBuf, SafeBuf, ToBuf};
+#[cfg(feature = "std")]
+use crate::{Endian, Error};
+use bytes::{Buf, BufMut};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+macro_rules! link_layer_addr {
+    ($name:ident, $len:literal, $description:literal) => {
+        #[doc = $description]
+        /// This is displayed as colon-separated lowercase hex octets.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            /// The octets of the address
+            #[must_use]
+            pub fn octets(self) -> [u8; $len] {
+                self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(octets: [u8; $len]) -> Self {
+                Self(octets)
+            }
+        }
+
+        impl FromBuf for $name {
+            fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+            where
+                B: Buf,
+            {
+                let mut octets = [0; $len];
+                SafeBuf::try_copy_to_slice(&mut buffer, &mut octets)?;
+                Ok(Self(octets))
+            }
+        }
+
+        impl ToBuf for $name {
+            fn to_buf<B>(&self, mut buffer: B) -> crate::Result<()>
+            where
+                B: BufMut,
+            {
+                if buffer.remaining_mut() < $len {
+                    return Err(error::Truncated::new($len, buffer.remaining_mut()).into());
+                }
+                buffer.put_slice(&self.0);
+                Ok(())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for (i, octet) in self.0.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(":")?;
+                    }
+                    write!(f, "{:02x}", octet)?;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+link_layer_addr!(MacAddr, 6, "A 48-bit MAC address (EUI-48)");
+link_layer_addr!(Eui64, 8, "A 64-bit extended unique identifier (EUI-64)");
+
+#[cfg(feature = "std")]
+impl FromBuf for Ipv4Addr {
+    /// Read an IPv4 address, in network byte order
+    fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+    where
+        B: Buf,
+    {
+        Ok(SafeBuf::try_get_u32(&mut buffer)?.into())
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromBuf for Ipv6Addr {
+    /// Read an IPv6 address, in network byte order
+    fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+    where
+        B: Buf,
+    {
+        Ok(SafeBuf::try_get_u128(&mut buffer)?.into())
+    }
+}
+
+#[cfg(feature = "std")]
+/// Read a socket address in the layout used by SOCKS5 (RFC 1928): an address
+/// type byte, the address, and a big-endian port
+///
+/// Only IPv4 (`0x01`) and IPv6 (`0x04`) addresses are supported, since a
+/// domain name (`0x03`) cannot be represented as a [`SocketAddr`].
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// address type is not supported
+pub fn try_get_socks5_addr<B>(buffer: &mut B) -> crate::Result<SocketAddr>
+where
+    B: Buf,
+{
+    let address_type = buffer.try_get_u8_at(0)?;
+    let len = match address_type {
+        0x01 => 1 + 4 + 2,
+        0x04 => 1 + 16 + 2,
+        _ => return Err(Error::invalid("SOCKS5 address type", address_type)),
+    };
+    let mut buffer = buffer.try_take_buf(len)?;
+    buffer.advance(1);
+
+    let ip = if address_type == 0x01 {
+        buffer.extract::<Ipv4Addr>()?.into()
+    } else {
+        buffer.extract::<Ipv6Addr>()?.into()
+    };
+    Ok(SocketAddr::new(ip, SafeBuf::try_get_u16(&mut buffer)?))
+}
+
+#[cfg(feature = "std")]
+/// The layout of a `sockaddr_in`/`sockaddr_in6` structure
+///
+/// The address family is stored in host byte order, and its values are
+/// platform-specific, so these are configurable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SockaddrLayout {
+    /// The byte order of the address family (and of the IPv6 scope ID)
+    pub endian: Endian,
+
+    /// The value of `AF_INET`
+    pub af_inet: u16,
+
+    /// The value of `AF_INET6`
+    pub af_inet6: u16,
+}
+
+#[cfg(feature = "std")]
+impl SockaddrLayout {
+    /// The layout used by Linux on little-endian architectures
+    pub const LINUX_LE: Self = Self {
+        endian: Endian::Little,
+        af_inet: 2,
+        af_inet6: 10,
+    };
+    /// The layout used by Windows on little-endian architectures
+    pub const WINDOWS_LE: Self = Self {
+        endian: Endian::Little,
+        af_inet: 2,
+        af_inet6: 23,
+    };
+}
+
+#[cfg(feature = "std")]
+/// Read a socket address in the layout of a `sockaddr_in` (16 bytes) or
+/// `sockaddr_in6` (28 bytes) structure
+///
+/// Both consist of the address family (see [`SockaddrLayout`]) and a
+/// big-endian port, followed by the IPv4 address and 8 bytes of padding, or
+/// by the flow information, the IPv6 address and the scope ID.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// address family is not recognised
+pub fn try_get_sockaddr<B>(buffer: &mut B, layout: SockaddrLayout) -> crate::Result<SocketAddr>
+where
+    B: Buf,
+{
+    let family = match layout.endian {
+        Endian::Big => buffer.try_get_u16_at(0)?,
+        Endian::Little => buffer.try_get_u16_le_at(0)?,
+    };
+
+    if family == layout.af_inet {
+        let mut buffer = buffer.try_take_buf(16)?;
+        buffer.advance(2);
+        let port = SafeBuf::try_get_u16(&mut buffer)?;
+        let ip = buffer.extract()?;
+        Ok(SocketAddrV4::new(ip, port).into())
+    } else if family == layout.af_inet6 {
+        let mut buffer = buffer.try_take_buf(28)?;
+        buffer.advance(2);
+        let port = SafeBuf::try_get_u16(&mut buffer)?;
+        let flow_info = SafeBuf::try_get_u32(&mut buffer)?;
+        let ip = buffer.extract()?;
+        let scope_id = match layout.endian {
+            Endian::Big => SafeBuf::try_get_u32(&mut buffer)?,
+            Endian::Little => SafeBuf::try_get_u32_le(&mut buffer)?,
+        };
+        Ok(SocketAddrV6::new(ip, port, flow_info, scope_id).into())
+    } else {
+        Err(Error::invalid("address family", family))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use super::{try_get_sockaddr, try_get_socks5_addr, SockaddrLayout};
+    use super::{Eui64, MacAddr};
+    #[cfg(feature = "std")]
+    use crate::Error;
+    use crate::{BytesMut, SafeBuf, ToBuf};
+    #[cfg(feature = "std")]
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    #[test]
+    fn link_layer_addrs() {
+        let mut buffer: &[u8] = &[
+            0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E, 0x02, 0, 0, 0xFF, 0xFE, 0, 0, 1,
+        ];
+
+        let mac = buffer.extract::<MacAddr>().unwrap();
+        assert_eq!(mac.to_string(), "00:1a:2b:3c:4d:5e");
+
+        let eui = buffer.extract::<Eui64>().unwrap();
+        assert_eq!(eui.to_string(), "02:00:00:ff:fe:00:00:01");
+
+        let mut output = BytesMut::new();
+        mac.to_buf(&mut output).unwrap();
+        eui.to_buf(&mut output).unwrap();
+        assert_eq!(
+            &output[..],
+            &[0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E, 0x02, 0, 0, 0xFF, 0xFE, 0, 0, 1]
+        );
+
+        let mut short = [0; 5];
+        assert!(mac.to_buf(&mut short[..]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn ip_addrs() {
+        let mut buffer: &[u8] = &[
+            192, 168, 0, 1, 0x20, 0x01, 0x0D, 0xB8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 10,
+        ];
+
+        assert_eq!(
+            buffer.extract::<Ipv4Addr>().unwrap(),
+            Ipv4Addr::new(192, 168, 0, 1)
+        );
+        assert_eq!(
+            buffer.extract::<Ipv6Addr>().unwrap(),
+            Ipv6Addr::new(0x2001, 0xDB8, 0, 0, 0, 0, 0, 1)
+        );
+        assert!(buffer.extract::<Ipv4Addr>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn socks5() {
+        let mut buffer: &[u8] = &[
+            0x01, 127, 0, 0, 1, 0x1F, 0x90, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            0, 80, 0x03,
+        ];
+
+        assert_eq!(
+            try_get_socks5_addr(&mut buffer).unwrap(),
+            "127.0.0.1:8080".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            try_get_socks5_addr(&mut buffer).unwrap(),
+            "[::1]:80".parse::<SocketAddr>().unwrap()
+        );
+        assert!(matches!(
+            try_get_socks5_addr(&mut buffer).unwrap_err(),
+            Error::InvalidValue(_)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn sockaddr() {
+        let mut buffer: &[u8] = &[
+            2, 0, 0x1F, 0x90, 10, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, //
+            10, 0, 0, 80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 3, 0, 0, 0,
+        ];
+
+        assert_eq!(
+            try_get_sockaddr(&mut buffer, SockaddrLayout::LINUX_LE).unwrap(),
+            "10.0.0.1:8080".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            try_get_sockaddr(&mut buffer, SockaddrLayout::LINUX_LE).unwrap(),
+            "[::1%3]:80".parse::<SocketAddr>().unwrap()
+        );
+        assert!(buffer.is_empty());
+
+        let mut truncated: &[u8] = &[2, 0, 0x1F, 0x90, 10, 0, 0, 1];
+        assert!(matches!(
+            try_get_sockaddr(&mut truncated, SockaddrLayout::LINUX_LE).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(truncated.len(), 8);
+    }
+}
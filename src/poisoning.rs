@@ -0,0 +1,191 @@
+//! A [`bytes::Buf`] wrapper which refuses further reads after a failure
+
+use crate::{Error, FromBuf};
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A wrapper around a [`Buf`] which is poisoned by the first failed parse
+///
+/// Once a parse has failed part-way through a message, the buffer is usually
+/// positioned at an arbitrary offset. Carrying on from there produces
+/// misleading errors further downstream (or worse, garbage values which happen
+/// to parse). Parses run through [`Poisoning::read_with`] or
+/// [`Poisoning::extract`] poison the wrapper if they fail, after which every
+/// parse fails immediately with a copy of the original error, and the
+/// [`Buf`] implementation reports no bytes remaining.
+///
+/// ```
+/// use safer_bytes::{Poisoning, SafeBuf};
+///
+/// let mut buffer = Poisoning::new(&[0x00, 0x01, 0x02][..]);
+///
+/// assert!(buffer.read_with(|b| Ok(b.try_get_u32()?)).is_err());
+/// assert!(buffer.is_poisoned());
+///
+/// // the bytes are still there, but cannot be read
+/// assert!(buffer.read_with(|b| Ok(b.try_get_u8()?)).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Poisoning<B> {
+    inner: B,
+    poison: Option<Error>,
+}
+
+impl<B> Poisoning<B> {
+    /// Wrap a buffer
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            poison: None,
+        }
+    }
+
+    /// Whether a parse has failed
+    pub fn is_poisoned(&self) -> bool {
+        self.poison.is_some()
+    }
+
+    /// The error which poisoned the wrapper, if any
+    pub fn poison(&self) -> Option<&Error> {
+        self.poison.as_ref()
+    }
+
+    /// Clear the poisoned state, returning the error which caused it
+    ///
+    /// This is useful once the caller has resynchronised the underlying
+    /// buffer (for example, by skipping to the next frame boundary).
+    pub fn clear_poison(&mut self) -> Option<Error> {
+        self.poison.take()
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying buffer
+    ///
+    /// This bypasses the poisoned state.
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> Poisoning<B>
+where
+    B: Buf,
+{
+    /// Run a parse against the buffer, poisoning the wrapper if it fails
+    ///
+    /// # Errors
+    ///
+    /// This method will return the error which poisoned the wrapper (without
+    /// running the parse) if it is already poisoned, or any error returned
+    /// by the parse.
+    pub fn read_with<T, F>(&mut self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&mut Self) -> crate::Result<T>,
+    {
+        if let Some(error) = &self.poison {
+            return Err(error.clone());
+        }
+
+        f(self).map_err(|error| {
+            self.poison = Some(error.clone());
+            error
+        })
+    }
+
+    /// Read a custom object from the buffer, poisoning the wrapper if it
+    /// fails
+    ///
+    /// See [`Poisoning::read_with`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return the error which poisoned the wrapper if it is
+    /// already poisoned, or an error if the type cannot be parsed from the
+    /// bytes.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.read_with(|buffer| T::from_buf(buffer))
+    }
+}
+
+impl<B> Buf for Poisoning<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        if self.is_poisoned() {
+            0
+        } else {
+            self.inner.remaining()
+        }
+    }
+
+    fn chunk(&self) -> &[u8] {
+        if self.is_poisoned() {
+            &[]
+        } else {
+            self.inner.chunk()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        if self.is_poisoned() {
+            0
+        } else {
+            self.inner.chunks_vectored(dst)
+        }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining(), "cannot advance past end of buffer");
+        self.inner.advance(cnt);
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(len <= self.remaining(), "cannot advance past end of buffer");
+        self.inner.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Poisoning;
+    use crate::{error, Error, SafeBuf};
+
+    #[test]
+    fn poisoned_by_first_error() {
+        let mut buffer = Poisoning::new(&[0_u8, 1, 2][..]);
+
+        assert_eq!(buffer.read_with(|b| Ok(b.try_get_u16()?)).unwrap(), 1);
+        assert!(!buffer.is_poisoned());
+
+        let truncated = Error::from(error::Truncated::new(2, 1));
+        assert_eq!(
+            buffer.read_with(|b| Ok(b.try_get_u16()?)).unwrap_err(),
+            truncated
+        );
+
+        // subsequent reads fail with the original error
+        assert_eq!(
+            buffer.read_with(|b| Ok(b.try_get_u8()?)).unwrap_err(),
+            truncated
+        );
+        assert!(buffer.try_get_u8().is_err());
+
+        assert_eq!(buffer.clear_poison(), Some(truncated));
+        assert_eq!(buffer.read_with(|b| Ok(b.try_get_u8()?)).unwrap(), 2);
+    }
+}
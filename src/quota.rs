@@ -0,0 +1,220 @@
+//! A [`bytes::Buf`] wrapper which caps the total number of bytes consumed
+
+#[cfg(feature = "std")]
+use crate::sub_buf::chunks_vectored_limited;
+use crate::{error, Error, FromBuf};
+use alloc::boxed::Box;
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A wrapper around a [`Buf`] which caps the total number of bytes that may be
+/// consumed from it
+///
+/// This is a defence-in-depth measure for long-lived connections or
+/// multi-message streams, bounding the total amount of work any sequence of
+/// parsers can do, regardless of how the individual messages are framed.
+///
+/// Reads which would exceed the quota fail as though the buffer were
+/// truncated. Parses run through [`Quota::read_with`] or [`Quota::extract`]
+/// report this as [`Error::LimitExceeded`] instead, so that running out of
+/// quota can be told apart from running out of input.
+///
+/// ```
+/// use safer_bytes::{Error, Quota, SafeBuf};
+///
+/// let mut buffer = Quota::new(&[0, 1, 2, 3, 4, 5][..], 4);
+///
+/// assert_eq!(buffer.read_with(|b| Ok(b.try_get_u16()?))?, 1);
+/// assert_eq!(buffer.read_with(|b| Ok(b.try_get_u8()?))?, 2);
+///
+/// let error = buffer.read_with(|b| Ok(b.try_get_u16()?)).unwrap_err();
+/// assert!(matches!(error, Error::LimitExceeded(_)));
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Quota<B> {
+    inner: B,
+    consumed: usize,
+    limit: usize,
+}
+
+impl<B> Quota<B> {
+    /// Wrap a buffer, permitting at most `quota` bytes to be consumed from it
+    pub fn new(inner: B, quota: usize) -> Self {
+        Self {
+            inner,
+            consumed: 0,
+            limit: quota,
+        }
+    }
+
+    /// The total number of bytes consumed so far
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// The total number of bytes which may be consumed
+    pub fn quota(&self) -> usize {
+        self.limit
+    }
+
+    /// The number of bytes which may still be consumed
+    pub fn remaining_quota(&self) -> usize {
+        self.limit - self.consumed
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> Quota<B>
+where
+    B: Buf,
+{
+    /// Run a parse against the buffer, reporting a read which fails because
+    /// the quota is exhausted as [`Error::LimitExceeded`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the parse fails, or if it would
+    /// exceed the quota.
+    pub fn read_with<T, F>(&mut self, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&mut Self) -> crate::Result<T>,
+    {
+        f(self).map_err(|e| self.classify(e))
+    }
+
+    /// Read a custom object from the buffer
+    ///
+    /// See [`Quota::read_with`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the type cannot be parsed from the
+    /// bytes, or if it would exceed the quota.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.read_with(|buffer| T::from_buf(buffer))
+    }
+
+    /// Replace a truncation error with [`Error::LimitExceeded`], if it was the
+    /// quota rather than the underlying buffer which ran out
+    fn classify(&self, error: Error) -> Error {
+        let truncated = match error.root() {
+            Error::Truncated(truncated) => *truncated,
+            _ => return error,
+        };
+        if self.inner.remaining() <= self.remaining_quota() {
+            return error;
+        }
+
+        let limit = error::LimitExceeded::new(
+            "consumed length",
+            self.consumed.saturating_add(truncated.needed),
+            self.limit,
+        );
+        match error {
+            Error::Context { path, .. } => Error::Context {
+                path,
+                source: Box::new(limit.into()),
+            },
+            _ => limit.into(),
+        }
+    }
+}
+
+impl<B> Buf for Quota<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining().min(self.remaining_quota())
+    }
+
+    fn chunk(&self) -> &[u8] {
+        let chunk = self.inner.chunk();
+        &chunk[..chunk.len().min(self.remaining_quota())]
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        chunks_vectored_limited(&self.inner, self.remaining_quota(), dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining_quota(),
+            "cannot advance past the quota"
+        );
+        self.inner.advance(cnt);
+        self.consumed += cnt;
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(
+            len <= self.remaining_quota(),
+            "cannot advance past the quota"
+        );
+        self.consumed += len;
+        self.inner.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Quota;
+    use crate::{error, Error, ResultExt, SafeBuf};
+
+    #[test]
+    fn quota() {
+        let mut buffer = Quota::new(&[0_u8, 1, 2, 3][..], 3);
+
+        assert_eq!(buffer.read_with(|b| Ok(b.try_get_u16()?)).unwrap(), 1);
+        assert_eq!(buffer.consumed(), 2);
+
+        let error = buffer
+            .read_with(|b| b.try_get_u16().context("length"))
+            .unwrap_err();
+        assert_eq!(
+            error.root(),
+            &error::LimitExceeded::new("consumed length", 4, 3).into()
+        );
+        assert_eq!(
+            error.to_string(),
+            "length: consumed length of 4 bytes exceeds the limit of 3 bytes"
+        );
+
+        assert_eq!(buffer.read_with(|b| Ok(b.try_get_u8()?)).unwrap(), 2);
+        assert_eq!(buffer.remaining_quota(), 0);
+    }
+
+    #[test]
+    fn truncated_input() {
+        let mut buffer = Quota::new(&[0_u8][..], 8);
+
+        assert!(matches!(
+            buffer.read_with(|b| Ok(b.try_get_u16()?)).unwrap_err(),
+            Error::Truncated(_)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn peek_across_chunks() {
+        let buffer = Quota::new(bytes::Buf::chain(&[0_u8, 1][..], &[2_u8, 3, 4][..]), 4);
+
+        assert_eq!(buffer.try_peek(4).unwrap(), &[0, 1, 2, 3][..]);
+        assert!(buffer.try_peek(5).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! A refillable buffer over a blocking [`std::io::Read`]
+
+use crate::{error, Error, FromBuf};
+use bytes::{Buf, Bytes, BytesMut};
+use std::io::{self, IoSlice, Read};
+
+const READ_CHUNK: usize = 8 * 1024;
+
+/// A buffer which is refilled on demand from a blocking [`Read`]er
+///
+/// A [`ReadBuf`] implements [`Buf`] over the bytes read so far, so the
+/// [`SafeBuf`](crate::SafeBuf) methods can be used as normal once enough bytes
+/// are buffered (see [`ReadBuf::fill`]). The [`ReadBuf::extract`] and
+/// [`ReadBuf::read_with`] methods go further, and transparently refill the
+/// buffer whenever a parse fails for want of bytes.
+///
+/// End of input is reported as [`Error::Truncated`]. Errors from the reader
+/// are reported as [`Error::Custom`], wrapping the [`io::Error`].
+///
+/// The number of bytes buffered is capped, so a corrupt length field cannot
+/// cause the whole of the reader to be slurped into memory.
+///
+/// ```
+/// use safer_bytes::ReadBuf;
+///
+/// let file: &[u8] = &[0x00, 0x2A, 0xFF];
+/// let mut reader = ReadBuf::new(file, 1024);
+///
+/// let value: u16 = reader.read_with(|buffer| {
+///     use safer_bytes::SafeBuf;
+///     Ok(buffer.try_get_u16()?)
+/// })?;
+/// assert_eq!(value, 42);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ReadBuf<R> {
+    reader: R,
+    buffer: BytesMut,
+    max_buffered: usize,
+    eof: bool,
+}
+
+impl<R> ReadBuf<R>
+where
+    R: Read,
+{
+    /// Wrap a reader, buffering at most `max_buffered` bytes at a time
+    pub fn new(reader: R, max_buffered: usize) -> Self {
+        Self {
+            reader,
+            buffer: BytesMut::new(),
+            max_buffered,
+            eof: false,
+        }
+    }
+
+    /// The maximum number of bytes which will be buffered
+    #[must_use]
+    pub fn max_buffered(&self) -> usize {
+        self.max_buffered
+    }
+
+    /// The bytes which are currently buffered
+    #[must_use]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Whether the underlying reader has reached the end of its input
+    ///
+    /// Bytes may still be buffered.
+    #[must_use]
+    pub fn is_eof(&self) -> bool {
+        self.eof
+    }
+
+    /// A reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume the [`ReadBuf`], returning the underlying reader and any
+    /// buffered bytes
+    pub fn into_parts(self) -> (R, BytesMut) {
+        (self.reader, self.buffer)
+    }
+
+    /// Ensure that (at least) `len` bytes are buffered
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `len` exceeds the maximum number of
+    /// buffered bytes, if the reader reaches the end of its input first, or if
+    /// the reader returns an error
+    pub fn fill(&mut self, len: usize) -> crate::Result<()> {
+        if self.fill_to(len)? {
+            Ok(())
+        } else {
+            Err(error::Truncated::new(len, self.buffer.len()).into())
+        }
+    }
+
+    /// Run a fallible parse against the buffered bytes, refilling the buffer
+    /// and retrying whenever the parse fails because they are truncated
+    ///
+    /// The buffer is only advanced if the parse succeeds, by however many bytes
+    /// the closure consumed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the parse fails for any other
+    /// reason, if the parse fails because a nested region is truncated (which
+    /// more input can't fix), if the reader reaches the end of its input (in
+    /// which case the parse error is returned), if the number of bytes needed
+    /// exceeds the maximum number of buffered bytes, or if the reader returns
+    /// an error
+    pub fn read_with<T, F>(&mut self, mut f: F) -> crate::Result<T>
+    where
+        F: FnMut(&mut &[u8]) -> crate::Result<T>,
+    {
+        loop {
+            let mut view = &self.buffer[..];
+            let error = match f(&mut view) {
+                Ok(value) => {
+                    let consumed = self.buffer.len() - view.len();
+                    self.buffer.advance(consumed);
+                    return Ok(value);
+                }
+                Err(e) => e,
+            };
+
+            let missing = match error.root() {
+                Error::Truncated(truncated) => truncated.missing().max(1),
+                _ => return Err(error),
+            };
+
+            // overrunning a nested region fails the same way however much
+            // input follows it, so reading more wouldn't help
+            let len = self.buffer.len();
+            self.buffer.extend_from_slice(&[0]);
+            let mut view = &self.buffer[..];
+            let retried = f(&mut view);
+            let overrun = !view.is_empty() && matches!(retried, Err(ref e) if *e == error);
+            self.buffer.truncate(len);
+            if overrun {
+                return Err(error);
+            }
+
+            if !self.fill_to(self.buffer.len().saturating_add(missing))? {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Deserialize an object, refilling the buffer as required
+    ///
+    /// # Errors
+    ///
+    /// See [`ReadBuf::read_with`]
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.read_with(|buffer| T::from_buf(buffer))
+    }
+
+    /// Read until `len` bytes are buffered, returning `false` if the reader
+    /// reaches the end of its input first
+    fn fill_to(&mut self, len: usize) -> crate::Result<bool> {
+        error::LimitExceeded::check("buffered length", len, self.max_buffered)?;
+
+        while self.buffer.len() < len && !self.eof {
+            let start = self.buffer.len();
+            let chunk = (len - start).max(READ_CHUNK).min(self.max_buffered - start);
+            self.buffer.resize(start + chunk, 0);
+
+            match self.reader.read(&mut self.buffer[start..]) {
+                Ok(count) => {
+                    self.buffer.truncate(start + count);
+                    self.eof = count == 0;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => self.buffer.truncate(start),
+                Err(e) => {
+                    self.buffer.truncate(start);
+                    return Err(Error::custom(e));
+                }
+            }
+        }
+
+        Ok(self.buffer.len() >= len)
+    }
+}
+
+impl<R> Buf for ReadBuf<R> {
+    fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.buffer.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.buffer.advance(cnt);
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        self.buffer.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadBuf;
+    use crate::{Error, SafeBuf};
+    use std::io::{self, Read};
+
+    /// A reader which returns at most one byte per call
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.0.len().min(buf.len()).min(1);
+            buf[..count].copy_from_slice(&self.0[..count]);
+            self.0 = &self.0[count..];
+            Ok(count)
+        }
+    }
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "broken"))
+        }
+    }
+
+    #[test]
+    fn refill() {
+        let mut reader = ReadBuf::new(Trickle(&[0, 0, 0, 7, 1, 2]), 16);
+
+        let value = reader
+            .read_with(|buffer| Ok(buffer.try_get_u32()?))
+            .unwrap();
+        assert_eq!(value, 7);
+
+        reader.fill(2).unwrap();
+        assert_eq!(reader.try_get_u16().unwrap(), 0x0102);
+    }
+
+    #[test]
+    fn eof() {
+        let mut reader = ReadBuf::new(Trickle(&[0, 1]), 16);
+
+        let error = reader
+            .read_with(|buffer| Ok(buffer.try_get_u32()?))
+            .unwrap_err();
+        assert!(matches!(error, Error::Truncated(_)));
+        assert!(reader.is_eof());
+        assert_eq!(reader.buffered(), &[0, 1]);
+    }
+
+    #[test]
+    fn overrun_region() {
+        let mut reader = ReadBuf::new(Trickle(&[3, 5, 1, 2, 3, 4, 5, 6, 7]), 16);
+
+        let error = reader
+            .read_with(|buffer| {
+                let len = buffer.try_get_u8()?;
+                let mut region = buffer.try_take_buf(len.into())?;
+                let len = region.try_get_u8()?;
+                Ok(region.try_copy_to_bytes(len.into())?)
+            })
+            .unwrap_err();
+        assert_eq!(error, crate::error::Truncated::new(5, 2).into());
+        assert_eq!(reader.buffered(), &[3, 5, 1, 2]);
+    }
+
+    #[test]
+    fn io_error() {
+        let mut reader = ReadBuf::new(Broken, 16);
+        assert!(matches!(reader.fill(1).unwrap_err(), Error::Custom(_)));
+    }
+
+    #[test]
+    fn max_buffered() {
+        let mut reader = ReadBuf::new(Trickle(&[0; 8]), 4);
+        assert!(matches!(
+            reader.fill(5).unwrap_err(),
+            Error::LimitExceeded(_)
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+//! A fixed-capacity ring buffer
+
+use alloc::{boxed::Box, vec};
+use bytes::{buf::UninitSlice, Buf, BufMut};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A fixed-capacity ring buffer which implements both [`Buf`] and [`BufMut`]
+///
+/// Long-running stream parsers can write incoming bytes into a [`RingBuf`]
+/// and parse them out again with the [`SafeBuf`](crate::SafeBuf) methods,
+/// reusing the same memory rather than repeatedly reallocating a
+/// [`BytesMut`](crate::BytesMut).
+///
+/// The storage is anything which can be viewed as a byte slice. By default it
+/// is allocated on the heap (see [`RingBuf::new`]), but a fixed-size array can
+/// be used instead (see [`RingBuf::from_storage`]) where allocation is not
+/// possible.
+///
+/// Note that the [`BufMut`] methods panic if the buffer does not have enough
+/// free space. Use [`RingBuf::try_put_slice`] where this cannot be ruled out.
+///
+/// ```
+/// use safer_bytes::{BufMut, RingBuf, SafeBuf};
+///
+/// let mut ring = RingBuf::from_storage([0; 4]);
+///
+/// ring.put_u16(0xCAFE);
+/// assert_eq!(ring.try_get_u8()?, 0xCA);
+///
+/// // wraps around the end of the storage
+/// ring.put_u16(0xF00D);
+/// assert_eq!(ring.try_get_u8()?, 0xFE);
+/// assert_eq!(ring.try_get_u16()?, 0xF00D);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+pub struct RingBuf<S = Box<[u8]>> {
+    storage: S,
+    head: usize,
+    len: usize,
+}
+
+impl RingBuf {
+    /// Construct a new, empty [`RingBuf`] with space for `capacity` bytes
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self::from_storage(vec![0; capacity].into_boxed_slice())
+    }
+}
+
+impl<S> RingBuf<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Construct a new, empty [`RingBuf`] using the given storage
+    ///
+    /// The existing contents of the storage are ignored.
+    pub fn from_storage(storage: S) -> Self {
+        Self {
+            storage,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// The total number of bytes the buffer can hold
+    pub fn capacity(&self) -> usize {
+        self.storage.as_ref().len()
+    }
+
+    /// The number of bytes currently held
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the buffer is full
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Discard the contents of the buffer
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Append a slice of bytes, if there is space for all of them
+    ///
+    /// # Errors
+    ///
+    /// If there is not enough free space, nothing is written and the number of
+    /// bytes of free space is returned as an error.
+    pub fn try_put_slice(&mut self, src: &[u8]) -> Result<(), usize> {
+        let free = self.capacity() - self.len;
+        if src.len() > free {
+            return Err(free);
+        }
+
+        self.put_slice(src);
+        Ok(())
+    }
+
+    /// Consume the buffer, returning the underlying storage
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// The index one past the last byte held, which may wrap to the start of
+    /// the storage
+    fn tail(&self) -> usize {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            0
+        } else {
+            (self.head + self.len) % capacity
+        }
+    }
+
+    /// The held bytes, as (up to) two contiguous slices
+    fn as_slices(&self) -> (&[u8], &[u8]) {
+        let storage = self.storage.as_ref();
+        let first_len = self.len.min(storage.len() - self.head);
+        (
+            &storage[self.head..self.head + first_len],
+            &storage[..self.len - first_len],
+        )
+    }
+}
+
+// the storage is omitted, since it may be large and is mostly stale
+#[allow(clippy::missing_fields_in_debug)]
+impl<S> fmt::Debug for RingBuf<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingBuf")
+            .field("capacity", &self.capacity())
+            .field("head", &self.head)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<S> Buf for RingBuf<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.as_slices().0
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let (first, second) = self.as_slices();
+        let mut count = 0;
+        for (slice, slot) in [first, second]
+            .iter()
+            .filter(|slice| !slice.is_empty())
+            .zip(dst.iter_mut())
+        {
+            *slot = IoSlice::new(slice);
+            count += 1;
+        }
+        count
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.len, "cannot advance past end of buffer");
+        self.len -= cnt;
+        self.head = if self.len == 0 {
+            0
+        } else {
+            (self.head + cnt) % self.capacity()
+        };
+    }
+}
+
+// SAFETY: `chunk_mut` only ever returns free (not held) bytes of initialised
+// storage, and `advance_mut` checks that `cnt` does not exceed them.
+unsafe impl<S> BufMut for RingBuf<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn remaining_mut(&self) -> usize {
+        self.capacity() - self.len
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining_mut(),
+            "cannot advance past end of buffer"
+        );
+        self.len += cnt;
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        let tail = self.tail();
+        let end = if tail < self.head || self.is_full() {
+            self.head
+        } else {
+            self.capacity()
+        };
+        UninitSlice::new(&mut self.storage.as_mut()[tail..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RingBuf;
+    use crate::{BufMut, SafeBuf};
+
+    #[test]
+    fn wrap_around() {
+        let mut ring = RingBuf::new(5);
+
+        ring.put_slice(&[1, 2, 3, 4]);
+        assert_eq!(ring.try_get_u16().unwrap(), 0x0102);
+
+        ring.put_slice(&[5, 6, 7]);
+        assert!(ring.is_full());
+        assert_eq!(bytes::Buf::chunk(&ring), &[3, 4, 5]);
+        #[cfg(feature = "std")]
+        assert_eq!(ring.try_peek(5).unwrap(), &[3, 4, 5, 6, 7][..]);
+        assert_eq!(ring.try_copy_to_bytes(5).unwrap(), &[3, 4, 5, 6, 7][..]);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn try_put_slice() {
+        let mut ring = RingBuf::from_storage([0; 4]);
+
+        ring.try_put_slice(&[1, 2, 3]).unwrap();
+        assert_eq!(ring.try_put_slice(&[4, 5]), Err(1));
+        assert_eq!(ring.len(), 3);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut ring = RingBuf::new(0);
+
+        assert_eq!(ring.try_put_slice(&[]), Ok(()));
+        assert_eq!(ring.try_put_slice(&[1]), Err(0));
+        assert!(ring.try_get_u8().is_err());
+    }
+}
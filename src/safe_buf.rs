@@ -1,19 +1,136 @@
 //! Extension traits for extracting custom objects from a [`bytes::Buf`]
 
-use crate::{error, FromBuf};
-use bytes::{Buf, Bytes};
+use crate::{error, Delimiter, FromBuf, HexDump, Limited, SplitOnPattern, SubBuf};
+use alloc::{vec, vec::Vec};
+use bytes::{Buf, Bytes, BytesMut};
+use core::{
+    convert::{TryFrom, TryInto},
+    mem::MaybeUninit,
+    ops::RangeInclusive,
+    time::Duration,
+};
 use paste::paste;
+#[cfg(feature = "std")]
+use std::io::{IoSlice, IoSliceMut};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of chunks inspected at once when peeking across a
+/// non-contiguous buffer
+#[cfg(feature = "std")]
+pub(crate) const MAX_PEEK_CHUNKS: usize = 64;
+
+/// Copy bytes from `buffer` into `dst`, starting `offset` bytes from the
+/// front, without advancing it.
+///
+/// Returns an error if the bytes cannot be inspected without advancing the
+/// buffer.
+fn peek_into<B>(buffer: &B, offset: usize, dst: &mut [u8]) -> Result<(), error::Truncated>
+where
+    B: Buf + ?Sized,
+{
+    let available = buffer.remaining().saturating_sub(offset);
+    error::Truncated::check(dst.len(), available)?;
+
+    if dst.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(src) = buffer.chunk().get(offset..offset + dst.len()) {
+        dst.copy_from_slice(src);
+        return Ok(());
+    }
+
+    peek_vectored(buffer, offset, dst)
+}
+
+/// Copy bytes which straddle chunk boundaries into `dst`, without advancing
+/// the buffer
+///
+/// If the buffer exposes more chunks than fit in the initial view, the view is
+/// grown until either the bytes are found or every chunk has been inspected.
+#[cfg(feature = "std")]
+fn peek_vectored<B>(buffer: &B, offset: usize, dst: &mut [u8]) -> Result<(), error::Truncated>
+where
+    B: Buf + ?Sized,
+{
+    let mut slices = [IoSlice::new(&[]); MAX_PEEK_CHUNKS];
+    let n = buffer.chunks_vectored(&mut slices);
+    let mut filled = gather(&slices[..n], offset, dst);
+
+    let mut capacity = MAX_PEEK_CHUNKS;
+    let mut exhausted = n < capacity;
+    while filled < dst.len() && !exhausted {
+        capacity *= 2;
+        let mut slices = vec![IoSlice::new(&[]); capacity];
+        let n = buffer.chunks_vectored(&mut slices);
+        filled = gather(&slices[..n], offset, dst);
+        exhausted = n < capacity;
+    }
+
+    if filled == dst.len() {
+        Ok(())
+    } else {
+        Err(error::Truncated::new(dst.len(), filled))
+    }
+}
+
+/// Copy bytes from `slices` into `dst`, skipping the first `offset` bytes.
+/// Returns the number of bytes copied.
+#[cfg(feature = "std")]
+fn gather(slices: &[IoSlice<'_>], offset: usize, dst: &mut [u8]) -> usize {
+    let mut skip = offset;
+    let mut filled = 0;
+    for slice in slices {
+        if skip >= slice.len() {
+            skip -= slice.len();
+            continue;
+        }
+
+        let slice = &slice[skip..];
+        skip = 0;
+
+        let count = slice.len().min(dst.len() - filled);
+        dst[filled..filled + count].copy_from_slice(&slice[..count]);
+        filled += count;
+        if filled == dst.len() {
+            break;
+        }
+    }
+    filled
+}
+
+/// Without `std` there is no [`Buf::chunks_vectored`], so only the first chunk
+/// of the buffer can be inspected
+#[cfg(not(feature = "std"))]
+fn peek_vectored<B>(buffer: &B, offset: usize, dst: &mut [u8]) -> Result<(), error::Truncated>
+where
+    B: Buf + ?Sized,
+{
+    let available = buffer.chunk().len().saturating_sub(offset);
+    Err(error::Truncated::new(dst.len(), available))
+}
 
 macro_rules! get_primitive_checked_be {
     ($t:ty, $width:literal) => {
         paste! {
             #[doc = "This method wraps [`Buf::get_" $t "`] with a bounds check to ensure there are enough bytes remaining, without panicking."]
-            fn [<try_get_ $t>](&mut self) -> std::result::Result<$t, error::Truncated> {
-                if self.remaining() >= $width {
-                    Ok(self.[<get_ $t>]())
-                } else {
-                    Err(error::Truncated)
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            #[inline]
+            fn [<try_get_ $t>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                // fast path: the value is contiguous in the current chunk
+                if let Some(bytes) = self.chunk().get(..$width) {
+                    let mut array = [0; $width];
+                    array.copy_from_slice(bytes);
+                    self.advance($width);
+                    return Ok($t::from_be_bytes(array));
                 }
+
+                error::Truncated::check($width, self.remaining())?;
+                Ok(self.[<get_ $t>]())
             }
         }
     };
@@ -23,12 +140,127 @@ macro_rules! get_primitive_checked_le {
     ($t:ty, $width:literal) => {
         paste! {
             #[doc = "This method wraps [`Buf::get_" $t "_le`] with a bounds check to ensure there are enough bytes remaining, without panicking."]
-            fn [<try_get_ $t _le>](&mut self) -> std::result::Result<$t, error::Truncated> {
-                if self.remaining() >= $width {
-                    Ok(self.[<get_ $t _le>]())
-                } else {
-                    Err(error::Truncated)
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            #[inline]
+            fn [<try_get_ $t _le>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                // fast path: the value is contiguous in the current chunk
+                if let Some(bytes) = self.chunk().get(..$width) {
+                    let mut array = [0; $width];
+                    array.copy_from_slice(bytes);
+                    self.advance($width);
+                    return Ok($t::from_le_bytes(array));
+                }
+
+                error::Truncated::check($width, self.remaining())?;
+                Ok(self.[<get_ $t _le>]())
+            }
+        }
+    };
+}
+
+macro_rules! peek_primitive_at_be {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a big-endian `" $t "` starting `offset` bytes from the front of the buffer, without advancing the buffer."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn [<try_get_ $t _at>](&self, offset: usize) -> core::result::Result<$t, error::Truncated> {
+                let mut bytes = [0; $width];
+                self.try_peek_at_to_slice(offset, &mut bytes)?;
+                Ok($t::from_be_bytes(bytes))
+            }
+        }
+    };
+}
+
+macro_rules! peek_primitive_at_le {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a little-endian `" $t "` starting `offset` bytes from the front of the buffer, without advancing the buffer."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn [<try_get_ $t _le_at>](&self, offset: usize) -> core::result::Result<$t, error::Truncated> {
+                let mut bytes = [0; $width];
+                self.try_peek_at_to_slice(offset, &mut bytes)?;
+                Ok($t::from_le_bytes(bytes))
+            }
+        }
+    };
+}
+
+macro_rules! get_primitive_slice_checked {
+    ($t:ty, $width:literal, be) => {
+        paste! {
+            get_primitive_slice_checked!(@impl $t, $width, "big", [<try_copy_to_ $t _slice>], [<try_get_ $t _slice>], [<get_ $t>], from_be_bytes);
+        }
+    };
+    ($t:ty, $width:literal, le) => {
+        paste! {
+            get_primitive_slice_checked!(@impl $t, $width, "little", [<try_copy_to_ $t _slice_le>], [<try_get_ $t _slice_le>], [<get_ $t _le>], from_le_bytes);
+        }
+    };
+    (@impl $t:ty, $width:literal, $endian:literal, $copy:ident, $get:ident, $get_one:ident, $from_bytes:ident) => {
+        paste! {
+            #[doc = "Read " $endian "-endian `" $t "` values from the buffer until `dst` is full, with a check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "Values are decoded a whole chunk at a time, which is considerably faster than reading them one by one. The buffer is left untouched if there are not enough bytes remaining."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn $copy(&mut self, dst: &mut [$t]) -> core::result::Result<(), error::Truncated> {
+                let len = dst.len().checked_mul($width).unwrap_or(usize::MAX);
+                error::Truncated::check(len, self.remaining())?;
+
+                let mut filled = 0;
+                while filled < dst.len() {
+                    let chunk = self.chunk();
+                    let count = (chunk.len() / $width).min(dst.len() - filled);
+
+                    // a value straddles the chunk boundary
+                    if count == 0 {
+                        dst[filled] = self.$get_one();
+                        filled += 1;
+                        continue;
+                    }
+
+                    for (value, bytes) in dst[filled..filled + count]
+                        .iter_mut()
+                        .zip(chunk.chunks_exact($width))
+                    {
+                        let mut array = [0; $width];
+                        array.copy_from_slice(bytes);
+                        *value = <$t>::$from_bytes(array);
+                    }
+
+                    self.advance(count * $width);
+                    filled += count;
                 }
+
+                Ok(())
+            }
+
+            #[doc = "Read `count` " $endian "-endian `" $t "` values from the buffer into a [`Vec`], with a check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "See [`SafeBuf::" $copy "`] for details. The length is checked before anything is allocated."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn $get(&mut self, count: usize) -> core::result::Result<Vec<$t>, error::Truncated> {
+                let len = count.checked_mul($width).unwrap_or(usize::MAX);
+                error::Truncated::check(len, self.remaining())?;
+
+                let mut values = vec![0; count];
+                self.$copy(&mut values)?;
+                Ok(values)
             }
         }
     };
@@ -43,9 +275,9 @@ pub trait SafeBuf: Buf {
     ///
     /// This method will return an error if the number of bytes remaining in the
     /// buffer is insufficent
-    fn try_copy_to_bytes(&mut self, len: usize) -> std::result::Result<Bytes, error::Truncated> {
+    fn try_copy_to_bytes(&mut self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
         if self.remaining() < len {
-            Err(error::Truncated)
+            Err(error::Truncated::new(len, self.remaining()))
         } else {
             Ok(self.copy_to_bytes(len))
         }
@@ -58,94 +290,2651 @@ pub trait SafeBuf: Buf {
     ///
     /// This method will return an error if the number of bytes remaining in the
     /// buffer is insufficent
-    fn try_copy_to_slice(&mut self, dst: &mut [u8]) -> std::result::Result<(), error::Truncated> {
+    fn try_copy_to_slice(&mut self, dst: &mut [u8]) -> core::result::Result<(), error::Truncated> {
         if self.remaining() < dst.len() {
-            Err(error::Truncated)
+            Err(error::Truncated::new(dst.len(), self.remaining()))
         } else {
             self.copy_to_slice(dst);
             Ok(())
         }
     }
 
-    /// Read a custom object from a buffer
+    /// Take a given number of bytes from the buffer into the front of a
+    /// caller-provided scratch buffer, returning the filled part of it
+    ///
+    /// This allows a hot path to reuse one buffer for variable-length fields,
+    /// rather than producing a new [`Bytes`] for each. The buffer is left
+    /// untouched if either check fails.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// let mut scratch = [0; 16];
+    /// let mut buffer = &[3, b'a', b'b', b'c', 2, b'd', b'e'][..];
+    ///
+    /// let len = buffer.try_get_u8()?.into();
+    /// assert_eq!(buffer.try_take_into(&mut scratch, len)?, b"abc");
+    /// let len = buffer.try_get_u8()?.into();
+    /// assert_eq!(buffer.try_take_into(&mut scratch, len)?, b"de");
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return
+    /// [`Error::LimitExceeded`](crate::Error::LimitExceeded) if `len` is larger
+    /// than `dst`, or an error if the number of bytes remaining in the buffer
+    /// is insufficent
+    fn try_take_into<'a>(&mut self, dst: &'a mut [u8], len: usize) -> crate::Result<&'a [u8]> {
+        error::LimitExceeded::check("scratch buffer", len, dst.len())?;
+        let dst = &mut dst[..len];
+        SafeBuf::try_copy_to_slice(self, dst)?;
+        Ok(dst)
+    }
+
+    /// Copy bytes from the buffer into several destination slices in turn
+    /// (a scatter read), with a check to ensure there are enough remaining to
+    /// fill all of them
+    ///
+    /// The total length is checked up front, so the buffer is left untouched
+    /// if there are not enough bytes remaining.
     ///
     /// # Errors
     ///
     /// This method will return an error if the number of bytes remaining in the
-    /// buffer is insufficent, or if the type cannot be parsed from the bytes.
-    fn extract<T>(&mut self) -> crate::Result<T>
+    /// buffer is insufficent
+    #[cfg(feature = "std")]
+    fn try_copy_to_slices(
+        &mut self,
+        dst: &mut [IoSliceMut<'_>],
+    ) -> core::result::Result<(), error::Truncated> {
+        let len = dst
+            .iter()
+            .fold(0_usize, |len, slice| len.saturating_add(slice.len()));
+        error::Truncated::check(len, self.remaining())?;
+
+        for slice in dst {
+            self.copy_to_slice(slice);
+        }
+        Ok(())
+    }
+
+    /// Take a given number of bytes from the buffer, with a check to ensure
+    /// there are enough remaining and that `len` is within an allocation
+    /// budget
+    ///
+    /// Use this when `len` comes from an untrusted length prefix. The budget is
+    /// checked first, so an oversized length is reported as
+    /// [`Error::LimitExceeded`](crate::Error::LimitExceeded) regardless of how
+    /// many bytes remain.
+    ///
+    /// ```
+    /// use safer_bytes::{Error, SafeBuf};
+    ///
+    /// let mut buffer = &[0xFF, 0xFF, 0xFF, 0xFF, 1, 2, 3][..];
+    /// let len = buffer.try_get_u32()? as usize;
+    ///
+    /// let error = buffer.try_copy_to_bytes_limited(len, 1024).unwrap_err();
+    /// assert!(matches!(error, Error::LimitExceeded(_)));
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `len` exceeds `max_alloc`, or if the
+    /// number of bytes remaining in the buffer is insufficent
+    fn try_copy_to_bytes_limited(&mut self, len: usize, max_alloc: usize) -> crate::Result<Bytes> {
+        error::LimitExceeded::check("allocation", len, max_alloc)?;
+        Ok(self.try_copy_to_bytes(len)?)
+    }
+
+    /// Take bytes from the buffer and write them to a slice of uninitialised
+    /// memory, with a check to ensure there are enough remaining
+    ///
+    /// This allows fresh allocations or stack scratch space to be filled
+    /// without zeroing them first. On success, the now-initialised slice is
+    /// returned. The buffer is left untouched if there are not enough bytes
+    /// remaining.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut buffer = &[1_u8, 2, 3, 4][..];
+    /// let mut scratch = [MaybeUninit::<u8>::uninit(); 3];
+    ///
+    /// assert_eq!(buffer.try_copy_to_uninit(&mut scratch)?, &[1, 2, 3]);
+    /// assert_eq!(buffer, &[4]);
+    /// # Ok::<(), safer_bytes::error::Truncated>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the buffer returns an empty chunk before
+    /// it is exhausted (in which case it is left advanced past the bytes
+    /// copied so far)
+    fn try_copy_to_uninit<'a>(
+        &mut self,
+        dst: &'a mut [MaybeUninit<u8>],
+    ) -> core::result::Result<&'a mut [u8], error::Truncated> {
+        error::Truncated::check(dst.len(), self.remaining())?;
+
+        let mut filled = 0;
+        while filled < dst.len() {
+            let chunk = self.chunk();
+            let cnt = chunk.len().min(dst.len() - filled);
+            if cnt == 0 {
+                return Err(error::Truncated::new(dst.len(), filled));
+            }
+
+            for (dst, src) in dst[filled..filled + cnt].iter_mut().zip(chunk) {
+                *dst = MaybeUninit::new(*src);
+            }
+
+            self.advance(cnt);
+            filled += cnt;
+        }
+
+        // SAFETY: every element of `dst` has been initialised above, and
+        // `MaybeUninit<u8>` has the same layout as `u8`.
+        Ok(unsafe { &mut *(dst as *mut [MaybeUninit<u8>] as *mut [u8]) })
+    }
+
+    /// Take a fixed number of bytes from the buffer into an array, with a check
+    /// to ensure there are enough remaining
+    ///
+    /// This is the consuming counterpart to [`SafeBuf::try_peek_const`]. It
+    /// performs a single bounds check and a single copy, reading directly from
+    /// the current chunk where possible.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_take_const<const N: usize>(
+        &mut self,
+    ) -> core::result::Result<[u8; N], error::Truncated> {
+        let mut array = [0; N];
+
+        if let Some(src) = self.chunk().get(..N) {
+            array.copy_from_slice(src);
+        } else {
+            error::Truncated::check(N, self.remaining())?;
+            self.copy_to_slice(&mut array);
+            return Ok(array);
+        }
+
+        self.advance(N);
+        Ok(array)
+    }
+
+    /// Take a bounded view over the next `len` bytes of the buffer, with a
+    /// check to ensure there are enough remaining
+    ///
+    /// Unlike [`SafeBuf::try_copy_to_bytes`], this does not copy the region.
+    /// The returned [`SubBuf`] implements [`Buf`] (and therefore
+    /// [`SafeBuf`]) and cannot read past the end of the region. Once it is
+    /// dropped, this buffer is positioned directly after the region. Use
+    /// [`SubBuf::finish`] to check that the region was fully consumed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_take_buf(
+        &mut self,
+        len: usize,
+    ) -> core::result::Result<SubBuf<'_, Self>, error::Truncated> {
+        if self.remaining() < len {
+            Err(error::Truncated::new(len, self.remaining()))
+        } else {
+            Ok(SubBuf::new(self, len))
+        }
+    }
+
+    /// Restrict reads to the next `limit` bytes of the buffer
+    ///
+    /// Unlike [`Buf::take`], reads which would cross the end of the region
+    /// are reported as errors, and [`Limited::finish`] checks that the whole
+    /// region was consumed. The region need not be present in the buffer up
+    /// front; see [`SafeBuf::try_take_buf`] for a borrowed alternative which
+    /// checks this.
+    ///
+    /// Pass `&mut buffer` to retain access to the buffer afterwards.
+    fn limit_safe(self, limit: usize) -> Limited<Self>
+    where
+        Self: Sized,
+    {
+        Limited::new(self, limit)
+    }
+
+    /// Read a length prefix, and run a parser against a region of the buffer
+    /// restricted to that many bytes
+    ///
+    /// Once the parser returns, the region is checked to have been consumed
+    /// exactly, which is easy to forget when parsing nested structures by hand.
+    /// The buffer is left untouched if the prefix or the region is truncated.
+    /// Otherwise, it is positioned directly after the region regardless of the
+    /// outcome.
+    ///
+    /// ```
+    /// use safer_bytes::{framing::PrefixWidth, Endian, SafeBuf};
+    ///
+    /// let mut buffer = &[0x00, 0x03, 0x01, 0x00, 0x02, 0xFF][..];
+    ///
+    /// let (a, b) = buffer.with_length_prefix(PrefixWidth::U16, Endian::Big, |region| {
+    ///     Ok((region.try_get_u8()?, region.try_get_u16()?))
+    /// })?;
+    /// assert_eq!((a, b), (1, 2));
+    /// assert_eq!(buffer, &[0xFF]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the parser fails, or if the parser does not
+    /// consume the whole region.
+    fn with_length_prefix<T, F>(
+        &mut self,
+        width: crate::framing::PrefixWidth,
+        endian: crate::Endian,
+        f: F,
+    ) -> crate::Result<T>
+    where
+        Self: Sized,
+        F: FnOnce(&mut SubBuf<'_, Self>) -> crate::Result<T>,
+    {
+        let prefix = width.peek(self, endian)?;
+        let len = usize::try_from(prefix)
+            .ok()
+            .and_then(|len| len.checked_add(width.size()))
+            .ok_or_else(|| crate::Error::invalid("length prefix", prefix))?;
+        error::Truncated::check(len, self.remaining())?;
+
+        self.advance(width.size());
+        let mut region = SubBuf::new(self, len - width.size());
+        let value = f(&mut region)?;
+        region.finish()?;
+        Ok(value)
+    }
+
+    /// Read a length prefix, and parse a custom object from exactly that many
+    /// bytes
+    ///
+    /// This is [`SafeBuf::with_length_prefix`] with [`SafeBuf::extract`] as the
+    /// parser, for the common case of a length-delimited nested message.
+    ///
+    /// ```
+    /// use safer_bytes::{framing::PrefixWidth, Endian, MacAddr, SafeBuf};
+    ///
+    /// let mut buffer = &[0x06, 0x00, 0x1B, 0x44, 0x11, 0x3A, 0xB7, 0xFF][..];
+    ///
+    /// let address: MacAddr = buffer.take_prefixed_and_parse(PrefixWidth::U8, Endian::Big)?;
+    /// assert_eq!(address.to_string(), "00:1b:44:11:3a:b7");
+    /// assert_eq!(buffer, &[0xFF]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the type cannot be parsed from the region, or
+    /// if the object does not occupy the whole region.
+    fn take_prefixed_and_parse<T>(
+        &mut self,
+        width: crate::framing::PrefixWidth,
+        endian: crate::Endian,
+    ) -> crate::Result<T>
     where
+        Self: Sized,
         T: FromBuf,
     {
-        T::from_buf(self)
+        self.with_length_prefix(width, endian, |region| region.extract())
     }
 
-    /// Check whether this reader is exhausted (out of bytes).
+    /// Read a version field, and dispatch to the parser for that version
+    ///
+    /// Versions outside of `supported` are rejected before the parser is run,
+    /// leaving the buffer untouched. Otherwise, the buffer is advanced past the
+    /// version field, and the parser is passed the version and the buffer.
+    ///
+    /// ```
+    /// use safer_bytes::{framing::PrefixWidth, Endian, Error, SafeBuf};
+    ///
+    /// fn parse_header(mut buffer: &[u8]) -> safer_bytes::Result<(u16, u32)> {
+    ///     buffer.extract_versioned(PrefixWidth::U8, Endian::Big, 1..=2, |version, buffer| {
+    ///         match version {
+    ///             // version 1 had a 16-bit length
+    ///             1 => Ok((buffer.try_get_u16()?, buffer.try_get_u16()?.into())),
+    ///             _ => Ok((buffer.try_get_u16()?, buffer.try_get_u32()?)),
+    ///         }
+    ///     })
+    /// }
+    ///
+    /// assert_eq!(parse_header(&[1, 0x00, 0x07, 0x00, 0x10])?, (7, 16));
+    /// assert_eq!(
+    ///     parse_header(&[2, 0x00, 0x07, 0x00, 0x00, 0x00, 0x10])?,
+    ///     (7, 16)
+    /// );
+    /// assert!(matches!(
+    ///     parse_header(&[3, 0x00, 0x07]),
+    ///     Err(Error::UnsupportedVersion(_))
+    /// ));
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
     ///
     /// # Errors
     ///
-    /// this method will return [`error::ExtraneousBytes`] if there are bytes
-    /// left in the buffer.
-    fn should_be_exhausted(&self) -> std::result::Result<(), error::ExtraneousBytes> {
-        if self.has_remaining() {
-            Err(error::ExtraneousBytes)
-        } else {
-            Ok(())
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the version is not supported, or if the
+    /// parser fails.
+    fn extract_versioned<T, F>(
+        &mut self,
+        width: crate::framing::PrefixWidth,
+        endian: crate::Endian,
+        supported: RangeInclusive<u64>,
+        f: F,
+    ) -> crate::Result<T>
+    where
+        Self: Sized,
+        F: FnOnce(u64, &mut Self) -> crate::Result<T>,
+    {
+        let version = width.peek(self, endian)?;
+        if !supported.contains(&version) {
+            return Err(error::UnsupportedVersion::new(version, supported).into());
         }
+
+        self.advance(width.size());
+        f(version, self)
     }
 
-    get_primitive_checked_be!(u8, 1);
-    get_primitive_checked_be!(i8, 1);
+    /// Take a payload of `len` bytes, followed by its CRC-32, verifying the
+    /// checksum
+    ///
+    /// The checksum is [`crc::crc32`](crate::crc::crc32), and is read in the
+    /// given byte order (for example, big-endian for PNG chunks, or
+    /// little-endian for an Ethernet frame check sequence). The buffer is left
+    /// untouched on failure.
+    ///
+    /// ```
+    /// use safer_bytes::{Endian, Error, SafeBuf};
+    ///
+    /// let mut buffer = &b"123456789\xCB\xF4\x39\x26"[..];
+    /// let payload = buffer.try_take_with_crc32(9, Endian::Big)?;
+    /// assert_eq!(payload, &b"123456789"[..]);
+    ///
+    /// let mut buffer = &b"123456780\xCB\xF4\x39\x26"[..];
+    /// let error = buffer.try_take_with_crc32(9, Endian::Big).unwrap_err();
+    /// assert!(matches!(error, Error::ChecksumMismatch(_)));
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the checksum does not match
+    #[cfg(feature = "crc")]
+    fn try_take_with_crc32(&mut self, len: usize, endian: crate::Endian) -> crate::Result<Bytes> {
+        let frame = self.try_peek(len.saturating_add(4))?;
+        let expected = match endian {
+            crate::Endian::Big => frame.try_get_u32_at(len)?,
+            crate::Endian::Little => frame.try_get_u32_le_at(len)?,
+        };
+        let actual = crate::crc::crc32(&frame[..len]);
+        if expected != actual {
+            return Err(error::ChecksumMismatch::new(expected, actual).into());
+        }
 
-    get_primitive_checked_be!(u16, 2);
-    get_primitive_checked_be!(i16, 2);
-    get_primitive_checked_be!(u32, 4);
-    get_primitive_checked_be!(i32, 4);
-    get_primitive_checked_be!(u64, 8);
-    get_primitive_checked_be!(i64, 8);
-    get_primitive_checked_be!(u128, 16);
-    get_primitive_checked_be!(i128, 16);
+        self.advance(len + 4);
+        Ok(frame.slice(..len))
+    }
 
-    get_primitive_checked_le!(u16, 2);
-    get_primitive_checked_le!(i16, 2);
-    get_primitive_checked_le!(u32, 4);
-    get_primitive_checked_le!(i32, 4);
-    get_primitive_checked_le!(u64, 8);
-    get_primitive_checked_le!(i64, 8);
-    get_primitive_checked_le!(u128, 16);
-    get_primitive_checked_le!(i128, 16);
-}
+    /// Take a payload of `len` bytes, followed by its CRC-16/CCITT, verifying
+    /// the checksum
+    ///
+    /// The checksum is [`crc::crc16_ccitt`](crate::crc::crc16_ccitt), and is
+    /// read in the given byte order. The buffer is left untouched on failure.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the checksum does not match
+    #[cfg(feature = "crc")]
+    fn try_take_with_crc16(&mut self, len: usize, endian: crate::Endian) -> crate::Result<Bytes> {
+        let frame = self.try_peek(len.saturating_add(2))?;
+        let expected = match endian {
+            crate::Endian::Big => frame.try_get_u16_at(len)?,
+            crate::Endian::Little => frame.try_get_u16_le_at(len)?,
+        };
+        let actual = crate::crc::crc16_ccitt(&frame[..len]);
+        if expected != actual {
+            return Err(error::ChecksumMismatch::new(expected.into(), actual.into()).into());
+        }
 
-impl<T> SafeBuf for T where T: Buf {}
+        self.advance(len + 2);
+        Ok(frame.slice(..len))
+    }
 
-#[cfg(test)]
-mod tests {
-    use bytes::BytesMut;
-    use paste::paste;
+    /// Read a Type-Length-Value record, returning its tag and value
+    ///
+    /// The widths and byte orders of the tag and length are given by the type
+    /// parameters (see [`wire::tlv`](crate::wire::tlv)). Values longer than
+    /// `max_len` are rejected before anything is read, so a corrupt length
+    /// cannot cause a large allocation. The buffer is left untouched on
+    /// failure.
+    ///
+    /// ```
+    /// use safer_bytes::{wire::tlv::Be, SafeBuf};
+    ///
+    /// let mut buffer = &[0x00, 0x01, 0x00, 0x02, b'h', b'i'][..];
+    /// let (tag, value) = buffer.try_get_tlv::<Be<u16>, Be<u16>>(1024)?;
+    /// assert_eq!(tag, 1);
+    /// assert_eq!(value, &b"hi"[..]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the value is longer than
+    /// `max_len`, or if the number of bytes remaining in the buffer is
+    /// insufficent
+    fn try_get_tlv<T, L>(&mut self, max_len: usize) -> crate::Result<(T::Value, Bytes)>
+    where
+        Self: Sized,
+        T: crate::wire::tlv::Field,
+        L: crate::wire::tlv::Field,
+        L::Value: TryInto<usize>,
+    {
+        let tag = T::peek(self, 0)?;
+        let len = L::peek(self, T::WIDTH)?.try_into().unwrap_or(usize::MAX);
+        error::LimitExceeded::check("TLV length", len, max_len)?;
 
-    use super::SafeBuf;
-    use crate::BufMut;
+        let header_len = T::WIDTH + L::WIDTH;
+        error::Truncated::check(header_len.saturating_add(len), self.remaining())?;
 
-    #[test]
-    fn try_copy_to_bytes() {
-        let mut bytes = BytesMut::new();
-        bytes.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        self.advance(header_len);
+        Ok((tag, self.copy_to_bytes(len)))
+    }
 
-        assert!(bytes.try_copy_to_bytes(4).is_ok());
-        assert!(bytes.try_copy_to_bytes(4).is_ok());
-        assert!(bytes.try_copy_to_bytes(4).is_err());
+    /// Iterate over the Type-Length-Value records in the rest of the buffer
+    ///
+    /// Each record is read with [`SafeBuf::try_get_tlv`]. Since the records
+    /// must end exactly at the end of the buffer, this is usually called on a
+    /// region of a larger buffer (see [`SafeBuf::limit_safe`] or
+    /// [`SafeBuf::try_take_buf`]). A partial record at the end of the region
+    /// is reported as an error.
+    ///
+    /// Pass `&mut buffer` to retain access to the buffer afterwards.
+    ///
+    /// ```
+    /// use safer_bytes::{wire::tlv::Be, SafeBuf};
+    ///
+    /// let mut buffer = &[0x00, 0x01, 0x01, 0xAA, 0x00, 0x02, 0x00, 0xFF][..];
+    /// let region = buffer.try_take_buf(7)?;
+    ///
+    /// let mut tags = Vec::new();
+    /// for record in region.tlv_records::<Be<u16>, u8>(1024) {
+    ///     let (tag, _value) = record?;
+    ///     tags.push(tag);
+    /// }
+    /// assert_eq!(tags, [1, 2]);
+    /// assert_eq!(buffer, &[0xFF]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    fn tlv_records<T, L>(self, max_len: usize) -> crate::wire::tlv::Records<T, L, Self>
+    where
+        Self: Sized,
+        T: crate::wire::tlv::Field,
+        L: crate::wire::tlv::Field,
+        L::Value: TryInto<usize>,
+    {
+        crate::wire::tlv::Records::new(self, max_len)
     }
 
-    #[test]
-    fn try_copy_to_slice() {
-        let mut bytes = BytesMut::new();
-        bytes.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// Check that the next `len` bytes are present, returning a record over
+    /// them which can be read without further bounds checks
+    ///
+    /// See [`ValidatedBuf`](crate::ValidatedBuf) for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    #[cfg(feature = "trusted")]
+    fn validated(
+        &mut self,
+        len: usize,
+    ) -> core::result::Result<crate::ValidatedBuf<'_, Self>, error::Truncated> {
+        self.try_take_buf(len).map(crate::ValidatedBuf::new)
+    }
 
-        let dst = &mut [0_u8; 4];
+    /// Take all of the remaining bytes from the buffer
+    ///
+    /// This is the natural terminal operation for protocols where the rest of
+    /// the message is an opaque payload. The buffer is exhausted afterwards.
+    fn take_rest(&mut self) -> Bytes {
+        let len = self.remaining();
+        self.copy_to_bytes(len)
+    }
 
-        assert!(bytes.try_copy_to_slice(dst).is_ok());
-        assert!(bytes.try_copy_to_slice(dst).is_ok());
-        assert!(bytes.try_copy_to_slice(dst).is_err());
+    /// Take all of the remaining bytes from the buffer, with a check to ensure
+    /// there are no more than `max` bytes remaining
+    ///
+    /// The buffer is left untouched if there are too many bytes remaining.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if there are more than `max` bytes
+    /// remaining in the buffer
+    fn try_take_rest(&mut self, max: usize) -> core::result::Result<Bytes, error::LimitExceeded> {
+        error::LimitExceeded::check("remaining length", self.remaining(), max)?;
+        Ok(self.take_rest())
+    }
+
+    /// Find the offset of the first occurrence of `needle` in the buffer,
+    /// without advancing it
+    ///
+    /// Like [`SafeBuf::try_take_until`], the search is accelerated with SIMD
+    /// where available, and finds occurrences which straddle chunk boundaries.
+    /// An empty needle is found at offset zero.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// let buffer = &b"Content-Length: 5\r\n\r\nhello"[..];
+    /// let header_len = buffer.try_find(b"\r\n\r\n").unwrap() + 4;
+    /// assert_eq!(header_len, 21);
+    /// assert_eq!(buffer.try_find(b"\r\n\r\n\r\n"), None);
+    /// ```
+    fn try_find(&self, needle: &[u8]) -> Option<usize> {
+        crate::search::find(self, needle)
+    }
+
+    /// Take `len` bytes of UTF-8 text from the buffer, validating them as the
+    /// next piece of a longer text
+    ///
+    /// The bytes may start or end part-way through a code point, which the
+    /// `validator` carries over to the next call. This allows text arriving
+    /// over several reads to be validated (and passed on) piece by piece. Call
+    /// [`Utf8Validator::finish`](crate::Utf8Validator::finish) once the text is
+    /// complete. The buffer and the validator are left untouched on failure.
+    ///
+    /// ```
+    /// use safer_bytes::{SafeBuf, Utf8Validator};
+    ///
+    /// let mut validator = Utf8Validator::new();
+    ///
+    /// let mut first = &b"na\xC3"[..];
+    /// let mut second = &b"\xAFve"[..];
+    /// first.try_take_utf8_stream(3, &mut validator)?;
+    /// second.try_take_utf8_stream(3, &mut validator)?;
+    ///
+    /// validator.finish()?;
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes are not valid UTF-8
+    fn try_take_utf8_stream(
+        &mut self,
+        len: usize,
+        validator: &mut crate::Utf8Validator,
+    ) -> crate::Result<Bytes> {
+        let text = self.try_peek(len)?;
+        validator.feed(&text)?;
+        self.advance(len);
+        Ok(text)
+    }
+
+    /// Take `len` ASCII hex digits from the buffer, and decode them into bytes
+    ///
+    /// Digits of either case are accepted. The buffer is left untouched on
+    /// failure.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// // an Intel HEX record
+    /// let mut buffer = &b":0300300002337A1E"[..];
+    /// buffer.try_expect_bytes(b":")?;
+    ///
+    /// let len = buffer.try_take_hex(2)?[0];
+    /// let address = buffer.try_take_hex(4)?;
+    /// let _record_type = buffer.try_take_hex(2)?;
+    /// let data = buffer.try_take_hex(usize::from(len) * 2)?;
+    ///
+    /// assert_eq!(address, &[0x00, 0x30][..]);
+    /// assert_eq!(data, &[0x02, 0x33, 0x7A][..]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if `len` is odd, or if any of the bytes is not a
+    /// hex digit
+    fn try_take_hex(&mut self, len: usize) -> crate::Result<Bytes> {
+        let text = self.try_peek(len)?;
+        let bytes = crate::text::decode_hex(&text)?;
+        self.advance(len);
+        Ok(bytes.into())
+    }
+
+    /// Take `len` characters of base64 from the buffer, and decode them
+    ///
+    /// Padding is optional, but if present must extend the field to a multiple
+    /// of four characters. Unused bits in the final character must be zero.
+    /// The buffer is left untouched on failure.
+    ///
+    /// ```
+    /// use safer_bytes::{Base64Alphabet, SafeBuf};
+    ///
+    /// let mut buffer = &b"AGFsaWNlAHNlY3JldA==\r\n"[..];
+    ///
+    /// let credentials = buffer.try_take_base64(20, Base64Alphabet::Standard)?;
+    /// assert_eq!(credentials, &b"\0alice\0secret"[..]);
+    /// assert_eq!(buffer, b"\r\n");
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if any of the characters is not in the alphabet,
+    /// or if the field is incorrectly padded
+    fn try_take_base64(
+        &mut self,
+        len: usize,
+        alphabet: crate::Base64Alphabet,
+    ) -> crate::Result<Bytes> {
+        let text = self.try_peek(len)?;
+        let bytes = crate::text::decode_base64(&text, alphabet)?;
+        self.advance(len);
+        Ok(bytes.into())
+    }
+
+    /// Take base64 up to the next occurrence of `delimiter`, and decode it
+    ///
+    /// The delimiter is consumed, but not returned. Like
+    /// [`SafeBuf::try_take_until`], this returns `None` if the delimiter is not
+    /// found, so that streaming callers can retry once more bytes have arrived.
+    /// See [`SafeBuf::try_take_base64`] for details of the decoding.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if any of the characters before the
+    /// delimiter is not in the alphabet, or if the field is incorrectly padded.
+    /// The buffer is left untouched on failure.
+    fn try_take_base64_until(
+        &mut self,
+        delimiter: u8,
+        alphabet: crate::Base64Alphabet,
+    ) -> crate::Result<Option<Bytes>> {
+        let len = match crate::search::find(self, &[delimiter]) {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        let bytes = self.try_take_base64(len, alphabet)?;
+        self.advance(1);
+        Ok(Some(bytes))
+    }
+
+    /// Take the bytes up to the next occurrence of `delimiter`, consuming (but
+    /// not returning) the delimiter itself
+    ///
+    /// The search is accelerated with SIMD where available, and covers
+    /// delimiters in any chunk of the buffer. If the delimiter is not found,
+    /// this returns `None` and the buffer is left untouched, so that streaming
+    /// callers can retry once more bytes have arrived.
+    fn try_take_until(&mut self, delimiter: u8) -> Option<Bytes> {
+        let position = crate::search::find(self, &[delimiter])?;
+        let bytes = self.copy_to_bytes(position);
+        self.advance(1);
+        Some(bytes)
+    }
+
+    /// Take the bytes up to the next occurrence of a multi-byte `delimiter`
+    /// (such as `\r\n\r\n`)
+    ///
+    /// The delimiter is never included in the returned bytes, and is consumed
+    /// or retained according to `mode`. Like [`SafeBuf::try_take_until`], this
+    /// returns `None` if the delimiter is not found, and leaves the buffer
+    /// untouched so that streaming callers can retry once more bytes have
+    /// arrived.
+    ///
+    /// ```
+    /// use safer_bytes::{Delimiter, SafeBuf};
+    ///
+    /// let mut buffer = &b"Host: example.com\r\n\r\nbody"[..];
+    ///
+    /// let headers = buffer.try_take_until_pattern(b"\r\n\r\n", Delimiter::Consume);
+    /// assert_eq!(headers.unwrap(), &b"Host: example.com"[..]);
+    /// assert_eq!(buffer, b"body");
+    ///
+    /// assert_eq!(
+    ///     buffer.try_take_until_pattern(b"\r\n", Delimiter::Consume),
+    ///     None
+    /// );
+    /// assert_eq!(buffer, b"body");
+    /// ```
+    fn try_take_until_pattern(&mut self, delimiter: &[u8], mode: Delimiter) -> Option<Bytes> {
+        let position = crate::search::find(self, delimiter)?;
+        let bytes = self.copy_to_bytes(position);
+        if mode == Delimiter::Consume {
+            self.advance(delimiter.len());
+        }
+        Some(bytes)
+    }
+
+    /// Iterate over the records in the buffer which are terminated by
+    /// `pattern`, consuming each record and its terminator
+    ///
+    /// Patterns which straddle chunk boundaries are found, as with
+    /// [`SafeBuf::try_take_until_pattern`]. Iteration stops once no further
+    /// pattern is found, leaving any trailing partial record in the buffer
+    /// (see [`SplitOnPattern::partial_len`]). An empty pattern yields no
+    /// records.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// let mut buffer = &b"a=1\r\nb=2\r\nc="[..];
+    ///
+    /// let mut records = buffer.split_on_pattern(b"\r\n");
+    /// assert_eq!(records.next().unwrap(), &b"a=1"[..]);
+    /// assert_eq!(records.next().unwrap(), &b"b=2"[..]);
+    /// assert_eq!(records.next(), None);
+    /// assert_eq!(records.partial_len(), 2);
+    /// ```
+    fn split_on_pattern<'a>(&'a mut self, pattern: &'a [u8]) -> SplitOnPattern<'a, Self>
+    where
+        Self: Sized,
+    {
+        SplitOnPattern::new(self, pattern)
+    }
+
+    /// Discard bytes up to the next occurrence of `marker`, returning the
+    /// number of bytes discarded (not counting the marker itself)
+    ///
+    /// This lets parsers of lossy transports recover from a corrupt frame by
+    /// skipping to the next sync marker, rather than aborting the whole
+    /// stream. The marker itself is consumed or retained according to
+    /// `delimiter`.
+    ///
+    /// If the marker is not found, this returns `None`, but the buffer is
+    /// *not* left untouched. Bytes which cannot be the start of a marker are
+    /// still discarded, so that they need not be searched again once more
+    /// bytes have arrived, and only the last `marker.len() - 1` bytes are kept
+    /// in case they hold the start of a marker. Callers which need to count
+    /// the bytes discarded in this case can compare [`Buf::remaining`] before
+    /// and after the call.
+    ///
+    /// ```
+    /// use safer_bytes::{Delimiter, SafeBuf};
+    ///
+    /// // MPEG transport stream packets start with the sync byte 0x47
+    /// let mut buffer = &[0x12, 0x00, 0x47, 0x40, 0x11][..];
+    ///
+    /// assert_eq!(buffer.try_resync_to(&[0x47], Delimiter::Retain), Some(2));
+    /// assert_eq!(buffer, &[0x47, 0x40, 0x11]);
+    ///
+    /// // no marker, so everything but a possible partial marker is discarded
+    /// let mut buffer = &b"noise\0\0"[..];
+    /// assert_eq!(buffer.try_resync_to(b"\0\0\x01", Delimiter::Retain), None);
+    /// assert_eq!(buffer, b"\0\0");
+    /// ```
+    fn try_resync_to(&mut self, marker: &[u8], delimiter: Delimiter) -> Option<usize> {
+        let mut discarded = 0;
+        loop {
+            if let Some(position) = crate::search::find(self, marker) {
+                self.advance(position);
+                if delimiter == Delimiter::Consume {
+                    self.advance(marker.len());
+                }
+                return Some(discarded + position);
+            }
+
+            // heavily fragmented buffers are searched a few chunks at a time
+            let skip = crate::search::searched_len(self).saturating_sub(marker.len() - 1);
+            if skip == 0 {
+                return None;
+            }
+            self.advance(skip);
+            discarded += skip;
+        }
+    }
+
+    /// Advance the buffer by a given number of bytes, with a check to ensure
+    /// there are enough remaining
+    ///
+    /// This is a non-panicking alternative to [`Buf::advance`]. The buffer is
+    /// left untouched if there are not enough bytes remaining.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_advance(&mut self, cnt: usize) -> core::result::Result<(), error::Truncated> {
+        if self.remaining() < cnt {
+            Err(error::Truncated::new(cnt, self.remaining()))
+        } else {
+            self.advance(cnt);
+            Ok(())
+        }
+    }
+
+    /// Skip a given number of bytes, reporting how many bytes were actually
+    /// available if there are not enough remaining
+    ///
+    /// The buffer is left untouched if there are not enough bytes remaining.
+    ///
+    /// # Errors
+    ///
+    /// If the number of bytes remaining in the buffer is insufficent, this
+    /// method returns the number of bytes that were available.
+    fn try_skip_exact(&mut self, len: usize) -> core::result::Result<(), usize> {
+        let available = self.remaining();
+        if available < len {
+            Err(available)
+        } else {
+            self.advance(len);
+            Ok(())
+        }
+    }
+
+    /// Skip the padding needed to reach the next `alignment`-byte boundary
+    ///
+    /// `position` is the current offset of the buffer within the enclosing
+    /// stream, which the alignment is computed relative to. An `alignment` of
+    /// zero or one never requires padding. Returns the number of padding bytes
+    /// that were skipped.
+    ///
+    /// The buffer is left untouched if there are not enough bytes remaining.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_align_to(
+        &mut self,
+        position: usize,
+        alignment: usize,
+    ) -> core::result::Result<usize, error::Truncated> {
+        let padding = match position.checked_rem(alignment) {
+            Some(0) | None => 0,
+            Some(rem) => alignment - rem,
+        };
+        self.try_advance(padding)?;
+        Ok(padding)
+    }
+
+    /// Copy bytes from the front of the buffer into a slice, without advancing
+    /// the buffer
+    ///
+    /// Peeking never consumes data, regardless of the underlying [`Buf`]
+    /// implementation. For non-contiguous buffers, the bytes are gathered
+    /// from [`Buf::chunks_vectored`]. The wrappers in this crate all forward
+    /// it, but a [`Buf`] which doesn't (or any buffer, without the `std`
+    /// feature) only exposes its current chunk.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    fn try_peek_to_slice(&self, dst: &mut [u8]) -> core::result::Result<(), error::Truncated> {
+        peek_into(self, 0, dst)
+    }
+
+    /// Borrow a given number of bytes directly from the current chunk, without
+    /// copying and without advancing the buffer
+    ///
+    /// This returns `Ok(None)` if there are enough bytes remaining, but they
+    /// are not contiguous in the current chunk (for example, in a chained
+    /// buffer). Callers can fall back to [`SafeBuf::try_peek`] in this
+    /// case.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// let mut buffer: &[u8] = &[1, 2, 3];
+    /// if let Some(chunk) = buffer.try_chunk_exact(2)? {
+    ///     assert_eq!(chunk, &[1, 2]);
+    /// }
+    /// # Ok::<(), safer_bytes::error::Truncated>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_chunk_exact(&self, len: usize) -> core::result::Result<Option<&[u8]>, error::Truncated> {
+        let chunk = self.chunk();
+        if chunk.len() >= len {
+            Ok(Some(&chunk[..len]))
+        } else {
+            error::Truncated::check(len, self.remaining())?;
+            Ok(None)
+        }
+    }
+
+    /// Copy a given number of bytes from the front of the buffer, without
+    /// advancing the buffer
+    ///
+    /// See [`SafeBuf::try_peek_to_slice`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    fn try_peek(&self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
+        self.try_peek_at(0, len)
+    }
+
+    /// Copy a fixed number of bytes from the front of the buffer into an
+    /// array, without advancing the buffer
+    ///
+    /// See [`SafeBuf::try_peek_to_slice`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    fn try_peek_const<const N: usize>(&self) -> core::result::Result<[u8; N], error::Truncated> {
+        let mut array = [0; N];
+        self.try_peek_to_slice(&mut array)?;
+        Ok(array)
+    }
+
+    /// Copy bytes from the buffer into a slice, starting `offset` bytes from
+    /// the front, without advancing the buffer
+    ///
+    /// See [`SafeBuf::try_peek_to_slice`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    fn try_peek_at_to_slice(
+        &self,
+        offset: usize,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated> {
+        peek_into(self, offset, dst)
+    }
+
+    /// Copy a given number of bytes from the buffer, starting `offset` bytes
+    /// from the front, without advancing the buffer
+    ///
+    /// This allows fields to be read by absolute position (for example, from
+    /// an offset table in a file header) without cloning the buffer. See
+    /// [`SafeBuf::try_peek_to_slice`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    fn try_peek_at(
+        &self,
+        offset: usize,
+        len: usize,
+    ) -> core::result::Result<Bytes, error::Truncated> {
+        if let Some(src) = offset
+            .checked_add(len)
+            .and_then(|end| self.chunk().get(offset..end))
+        {
+            return Ok(Bytes::copy_from_slice(src));
+        }
+
+        // check before allocating, since `len` may come from the input
+        error::Truncated::check(len, self.remaining().saturating_sub(offset))?;
+        let mut bytes = BytesMut::zeroed(len);
+        self.try_peek_at_to_slice(offset, &mut bytes)?;
+        Ok(bytes.freeze())
+    }
+
+    /// Render up to `max` of the remaining bytes as a hexdump, without
+    /// advancing the buffer
+    ///
+    /// This is intended for debugging. If the buffer is not contiguous, only
+    /// the bytes which can be inspected without advancing the buffer are
+    /// rendered.
+    fn debug_remaining(&self, max: usize) -> HexDump {
+        let len = max.min(self.remaining());
+        let bytes = self
+            .try_peek(len)
+            .or_else(|e| self.try_peek(e.available))
+            .unwrap_or_default();
+        HexDump::new(bytes)
+    }
+
+    /// Read a custom object from a buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the type cannot be parsed from the bytes.
+    fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        T::from_buf(self)
+    }
+
+    /// Read a custom object from a buffer, checking that the object occupies
+    /// the whole buffer
+    ///
+    /// This is intended for top-level messages, where trailing bytes
+    /// usually indicate a framing bug or a malformed message rather than
+    /// something which can be ignored.
+    ///
+    /// ```
+    /// use safer_bytes::{net::MacAddr, Error, SafeBuf};
+    ///
+    /// let mut buffer = &[0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0xFF][..];
+    /// let error = buffer.extract_strict::<MacAddr>().unwrap_err();
+    /// assert!(matches!(error, Error::ExtraneousBytes(_)));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the type cannot be parsed from the bytes, or
+    /// if there are bytes left over.
+    fn extract_strict<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        let value = self.extract()?;
+        self.should_be_exhausted()?;
+        Ok(value)
+    }
+
+    /// Read a custom object from a buffer, restoring the buffer to its original
+    /// position if the object cannot be parsed
+    ///
+    /// This allows "try format A, else format B" logic without corrupting the
+    /// stream. See [`SafeBuf::transaction`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the type cannot be parsed from the bytes.
+    fn try_extract<T>(&mut self) -> crate::Result<T>
+    where
+        Self: Clone + Sized,
+        T: FromBuf,
+    {
+        self.transaction(SafeBuf::extract)
+    }
+
+    /// Read a count-prefixed map, made up of that many key-value pairs
+    ///
+    /// The count is checked against `max_entries` before anything is read, so
+    /// a corrupt count cannot cause a long-running parse. Keys which appear
+    /// more than once are handled according to `duplicates`. Errors from
+    /// parsing an entry are annotated with its index.
+    ///
+    /// The map is read entry by entry, so the buffer is left part-way through
+    /// the map on failure. Use [`SafeBuf::transaction`] to restore it.
+    ///
+    /// ```
+    /// use safer_bytes::{framing::PrefixWidth, DuplicateKeys, Endian, MacAddr, SafeBuf};
+    /// use std::{collections::HashMap, net::Ipv4Addr};
+    ///
+    /// // an ARP table
+    /// let mut buffer = &[
+    ///     0x01, // one entry
+    ///     192, 168, 0, 1, // key
+    ///     0x00, 0x1B, 0x44, 0x11, 0x3A, 0xB7, // value
+    /// ][..];
+    ///
+    /// let table: HashMap<Ipv4Addr, MacAddr> =
+    ///     buffer.extract_map(PrefixWidth::U8, Endian::Big, 256, DuplicateKeys::Reject)?;
+    /// assert_eq!(
+    ///     table[&Ipv4Addr::new(192, 168, 0, 1)].to_string(),
+    ///     "00:1b:44:11:3a:b7"
+    /// );
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the count exceeds `max_entries`,
+    /// if any of the keys or values cannot be parsed, or if a key is repeated
+    /// and `duplicates` is
+    /// [`DuplicateKeys::Reject`](crate::DuplicateKeys::Reject)
+    /// (in which case the offending value is the index of the repeated entry).
+    fn extract_map<K, V, M>(
+        &mut self,
+        width: crate::framing::PrefixWidth,
+        endian: crate::Endian,
+        max_entries: usize,
+        duplicates: crate::DuplicateKeys,
+    ) -> crate::Result<M>
+    where
+        Self: Sized,
+        K: FromBuf,
+        V: FromBuf,
+        M: crate::Map<K, V>,
+    {
+        use crate::{error::ResultExt, DuplicateKeys};
+        use alloc::format;
+
+        let count = width.peek(self, endian)?;
+        error::LimitExceeded::check(
+            "map length",
+            usize::try_from(count).unwrap_or(usize::MAX),
+            max_entries,
+        )?;
+        self.advance(width.size());
+
+        let mut map = M::default();
+        for index in 0..count {
+            let key = K::from_buf(&mut *self).with_context(|| format!("[{}]", index))?;
+            let value = V::from_buf(&mut *self).with_context(|| format!("[{}]", index))?;
+
+            match duplicates {
+                _ if !map.contains_key(&key) => map.insert(key, value),
+                DuplicateKeys::Reject => {
+                    return Err(crate::Error::invalid("duplicate map key", index));
+                }
+                DuplicateKeys::KeepFirst => {}
+                DuplicateKeys::KeepLast => map.insert(key, value),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Read an array of fixed-size custom objects from a buffer, parsing the
+    /// records in parallel
+    ///
+    /// The next `count * record_size` bytes are split into `count` records of
+    /// `record_size` bytes, and each record is parsed independently using
+    /// [`FromBuf::from_buf`]. A record need not be fully consumed by its
+    /// parser, so trailing padding is permitted.
+    ///
+    /// The region is parsed in place if it lies within the current chunk,
+    /// otherwise it is copied first. The buffer is only advanced if every
+    /// record is parsed successfully.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if any of the records cannot be parsed. The
+    /// error is annotated with the index of the offending record.
+    #[cfg(feature = "rayon")]
+    fn extract_n_parallel<T>(&mut self, count: usize, record_size: usize) -> crate::Result<Vec<T>>
+    where
+        T: FromBuf + Send,
+    {
+        use crate::error::ResultExt;
+        use alloc::format;
+        use rayon::prelude::*;
+
+        if record_size == 0 {
+            return Err(crate::Error::invalid("record size", 0));
+        }
+        let len = count
+            .checked_mul(record_size)
+            .ok_or_else(|| crate::Error::invalid("record count", count as u64))?;
+
+        let parse = |region: &[u8]| -> crate::Result<Vec<T>> {
+            region
+                .par_chunks(record_size)
+                .enumerate()
+                .map(|(index, record)| T::from_buf(record).with_context(|| format!("[{}]", index)))
+                .collect()
+        };
+
+        let records = match self.try_chunk_exact(len)? {
+            Some(region) => parse(region)?,
+            None => parse(&self.try_peek(len)?)?,
+        };
+        self.advance(len);
+        Ok(records)
+    }
+
+    /// Run a fallible parse against the buffer, restoring the buffer to its
+    /// original position if the parse fails
+    ///
+    /// This allows alternative parsers to be tried in turn without corrupting
+    /// the stream. The buffer is cloned before running the closure, so this is
+    /// best suited to buffers which are cheap to clone, such as [`Bytes`] or
+    /// `&[u8]`.
+    ///
+    /// # Errors
+    ///
+    /// This method will return any error returned by the closure
+    fn transaction<T, E, F>(&mut self, f: F) -> core::result::Result<T, E>
+    where
+        Self: Clone + Sized,
+        F: FnOnce(&mut Self) -> core::result::Result<T, E>,
+    {
+        let checkpoint = self.clone();
+        let result = f(self);
+        if result.is_err() {
+            *self = checkpoint;
+        }
+        result
+    }
+
+    /// Check whether this reader is exhausted (out of bytes).
+    ///
+    /// # Errors
+    ///
+    /// this method will return [`error::ExtraneousBytes`] if there are bytes
+    /// left in the buffer. The error reports how many bytes were left, along
+    /// with a preview of the first few.
+    fn should_be_exhausted(&self) -> core::result::Result<(), error::ExtraneousBytes> {
+        if self.has_remaining() {
+            Err(error::ExtraneousBytes::from_buf(self))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that the next bytes in the buffer match an expected sequence
+    /// (such as a magic number), advancing past them if they do
+    ///
+    /// The buffer is left untouched on failure. Use
+    /// [`SafeBuf::try_expect_bytes_ct`] to compare against a secret.
+    ///
+    /// ```
+    /// use safer_bytes::{error::Mismatch, Error, SafeBuf};
+    ///
+    /// const PNG_SIGNATURE: [u8; 8] = *b"\x89PNG\r\n\x1a\n";
+    ///
+    /// let mut buffer = &b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR"[..];
+    /// buffer.try_expect_bytes(&PNG_SIGNATURE)?;
+    /// assert_eq!(buffer.try_get_u32()?, 13);
+    ///
+    /// let mut buffer = &b"GIF89a\x01\x00"[..];
+    /// assert_eq!(
+    ///     buffer.try_expect_bytes(&PNG_SIGNATURE).unwrap_err(),
+    ///     Error::Mismatch(Mismatch::new(&PNG_SIGNATURE[..], &b"GIF89a\x01\x00"[..]))
+    /// );
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the bytes cannot be inspected without
+    /// advancing the buffer, or if the bytes do not match.
+    fn try_expect_bytes(&mut self, expected: &[u8]) -> crate::Result<()> {
+        if !starts_with(self, expected)? {
+            let mut found = vec![0; expected.len()];
+            self.try_peek_to_slice(&mut found)?;
+            return Err(error::Mismatch::new(Bytes::copy_from_slice(expected), found).into());
+        }
+
+        self.advance(expected.len());
+        Ok(())
+    }
+
+    /// Consume a known prefix if the buffer starts with it, returning whether
+    /// it did
+    ///
+    /// If `required` is `false`, a missing prefix is not an error, and the
+    /// buffer is left untouched. If `required` is `true`, this behaves like
+    /// [`SafeBuf::try_expect_bytes`].
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// // an optional UTF-8 byte order mark
+    /// let mut buffer = &b"\xEF\xBB\xBFname"[..];
+    /// assert!(buffer.try_strip_prefix(b"\xEF\xBB\xBF", false)?);
+    /// assert!(!buffer.try_strip_prefix(b"\xEF\xBB\xBF", false)?);
+    /// assert_eq!(buffer, b"name");
+    ///
+    /// assert!(buffer.try_strip_prefix(b"\xEF\xBB\xBF", true).is_err());
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If `required` is `true`, this method will return an error if the number
+    /// of bytes remaining in the buffer is insufficent, if the bytes cannot be
+    /// inspected without advancing the buffer, or if the bytes do not match.
+    fn try_strip_prefix(&mut self, prefix: &[u8], required: bool) -> crate::Result<bool> {
+        if required {
+            self.try_expect_bytes(prefix)?;
+            return Ok(true);
+        }
+
+        if starts_with(self, prefix).unwrap_or(false) {
+            self.advance(prefix.len());
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Check that the next bytes in the buffer match an expected secret (such
+    /// as a MAC tag or an authentication token) in constant time, advancing
+    /// past them if they do
+    ///
+    /// The comparison always inspects every byte, so its duration does not
+    /// depend on where the first mismatch occurs. The error does not reveal
+    /// the bytes which were found. The length of `expected` is not treated as
+    /// secret. The buffer is left untouched on failure.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// let tag = [0x8a, 0x31, 0xfe, 0x07];
+    ///
+    /// let mut buffer = &[0x8a, 0x31, 0xfe, 0x07, 0x01][..];
+    /// assert!(buffer.try_expect_bytes_ct(&tag).is_ok());
+    /// assert_eq!(buffer, &[0x01]);
+    ///
+    /// let mut buffer = &[0x8a, 0x31, 0xfe, 0x00][..];
+    /// assert!(buffer.try_expect_bytes_ct(&tag).is_err());
+    /// assert_eq!(buffer.len(), 4);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the bytes cannot be inspected without
+    /// advancing the buffer, or if the bytes do not match.
+    fn try_expect_bytes_ct(&mut self, expected: &[u8]) -> crate::Result<()> {
+        const BLOCK_LEN: usize = 64;
+
+        error::Truncated::check(expected.len(), self.remaining())?;
+
+        let mut diff = 0;
+        let mut block = [0; BLOCK_LEN];
+        for (i, expected) in expected.chunks(BLOCK_LEN).enumerate() {
+            let block = &mut block[..expected.len()];
+            self.try_peek_at_to_slice(i * BLOCK_LEN, block)?;
+            diff |= constant_time_diff(block, expected);
+        }
+
+        if diff != 0 {
+            return Err(crate::Error::Deserialization("secret mismatch"));
+        }
+        self.advance(expected.len());
+        Ok(())
+    }
+
+    /// Read a big-endian `u64` count of seconds since the UNIX epoch
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the time cannot be represented as a
+    /// [`SystemTime`]. In either case the buffer is not advanced.
+    #[cfg(feature = "std")]
+    fn try_get_unix_secs(&mut self) -> crate::Result<SystemTime> {
+        let secs = self.try_get_u64_at(0)?;
+        let time = UNIX_EPOCH
+            .checked_add(Duration::from_secs(secs))
+            .ok_or_else(|| crate::Error::invalid("UNIX timestamp", secs))?;
+        self.advance(8);
+        Ok(time)
+    }
+
+    /// Read a big-endian `u64` count of milliseconds since the UNIX epoch
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the time cannot be represented as a
+    /// [`SystemTime`]. In either case the buffer is not advanced.
+    #[cfg(feature = "std")]
+    fn try_get_unix_millis(&mut self) -> crate::Result<SystemTime> {
+        let millis = self.try_get_u64_at(0)?;
+        let time = UNIX_EPOCH
+            .checked_add(Duration::from_millis(millis))
+            .ok_or_else(|| crate::Error::invalid("UNIX timestamp (milliseconds)", millis))?;
+        self.advance(8);
+        Ok(time)
+    }
+
+    /// Read a 64-bit NTP timestamp (big-endian seconds since 1900, followed by
+    /// a big-endian binary fraction of a second)
+    ///
+    /// The timestamp is assumed to fall in NTP era 0 (1900 to 2036).
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the time cannot be represented as a
+    /// [`SystemTime`]. In either case the buffer is not advanced.
+    #[cfg(feature = "std")]
+    fn try_get_ntp64(&mut self) -> crate::Result<SystemTime> {
+        /// Seconds from the NTP epoch (1900) to the UNIX epoch (1970)
+        const NTP_TO_UNIX_SECS: u64 = 2_208_988_800;
+
+        let timestamp = self.try_get_u64_at(0)?;
+        let secs = timestamp >> 32;
+        #[allow(clippy::cast_possible_truncation)]
+        let nanos = (((timestamp & 0xFFFF_FFFF) * 1_000_000_000) >> 32) as u32;
+
+        let time = if secs >= NTP_TO_UNIX_SECS {
+            UNIX_EPOCH.checked_add(Duration::new(secs - NTP_TO_UNIX_SECS, nanos))
+        } else {
+            UNIX_EPOCH
+                .checked_sub(Duration::from_secs(NTP_TO_UNIX_SECS - secs))
+                .and_then(|time| time.checked_add(Duration::new(0, nanos)))
+        }
+        .ok_or_else(|| crate::Error::invalid("NTP timestamp", timestamp))?;
+
+        self.advance(8);
+        Ok(time)
+    }
+
+    /// Read a duration encoded as a big-endian `u64` count of seconds followed
+    /// by a big-endian `u32` count of nanoseconds
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the nanoseconds are not less than one
+    /// second. In either case the buffer is not advanced.
+    fn try_get_duration_secs_nanos(&mut self) -> crate::Result<Duration> {
+        let secs = self.try_get_u64_at(0)?;
+        let nanos = self.try_get_u32_at(8)?;
+        duration_from_secs_nanos(self, secs, nanos)
+    }
+
+    /// Read a duration encoded as a little-endian `u64` count of seconds
+    /// followed by a little-endian `u32` count of nanoseconds
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the nanoseconds are not less than one
+    /// second. In either case the buffer is not advanced.
+    fn try_get_duration_secs_nanos_le(&mut self) -> crate::Result<Duration> {
+        let secs = self.try_get_u64_le_at(0)?;
+        let nanos = self.try_get_u32_le_at(8)?;
+        duration_from_secs_nanos(self, secs, nanos)
+    }
+
+    /// Read a big-endian number of any primitive type, with a check to ensure
+    /// there are enough bytes remaining
+    ///
+    /// This allows numeric-generic code (for example, a format with a
+    /// configurable sample width) to read values without matching over every
+    /// width.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBuf;
+    ///
+    /// fn samples<T>(mut buffer: &[u8], count: usize) -> safer_bytes::Result<Vec<T>>
+    /// where
+    ///     T: num_traits::FromBytes,
+    ///     T::Bytes: Default,
+    /// {
+    ///     (0..count)
+    ///         .map(|_| Ok(buffer.try_get_primitive()?))
+    ///         .collect()
+    /// }
+    ///
+    /// assert_eq!(samples::<u16>(&[0, 1, 0, 2], 2)?, vec![1, 2]);
+    /// assert_eq!(samples::<f32>(&[0x3F, 0x80, 0, 0], 1)?, vec![1.0]);
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    #[cfg(feature = "num-traits")]
+    fn try_get_primitive<T>(&mut self) -> core::result::Result<T, error::Truncated>
+    where
+        T: num_traits::FromBytes,
+        T::Bytes: Default,
+    {
+        let mut bytes = T::Bytes::default();
+        SafeBuf::try_copy_to_slice(self, bytes.as_mut())?;
+        Ok(T::from_be_bytes(&bytes))
+    }
+
+    /// Read a little-endian number of any primitive type, with a check to
+    /// ensure there are enough bytes remaining
+    ///
+    /// See [`SafeBuf::try_get_primitive`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    #[cfg(feature = "num-traits")]
+    fn try_get_primitive_le<T>(&mut self) -> core::result::Result<T, error::Truncated>
+    where
+        T: num_traits::FromBytes,
+        T::Bytes: Default,
+    {
+        let mut bytes = T::Bytes::default();
+        SafeBuf::try_copy_to_slice(self, bytes.as_mut())?;
+        Ok(T::from_le_bytes(&bytes))
+    }
+
+    /// Read a big-endian flags field, handling bits which don't correspond to
+    /// a defined flag according to `unknown`
+    ///
+    /// See the [`flags`](crate::flags) module for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the field contains unknown bits and
+    /// `unknown` is [`UnknownBits::Reject`](crate::flags::UnknownBits::Reject)
+    #[cfg(feature = "bitflags")]
+    fn extract_flags<F>(&mut self, unknown: crate::flags::UnknownBits) -> crate::Result<F>
+    where
+        Self: Sized,
+        F: crate::flags::Flags,
+    {
+        use crate::flags::FlagBits;
+        F::from_bits_with(F::Bits::try_get(self)?, unknown)
+    }
+
+    /// Read a little-endian flags field, handling bits which don't correspond
+    /// to a defined flag according to `unknown`
+    ///
+    /// See [`SafeBuf::extract_flags`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the field contains unknown bits and
+    /// `unknown` is [`UnknownBits::Reject`](crate::flags::UnknownBits::Reject)
+    #[cfg(feature = "bitflags")]
+    fn extract_flags_le<F>(&mut self, unknown: crate::flags::UnknownBits) -> crate::Result<F>
+    where
+        Self: Sized,
+        F: crate::flags::Flags,
+    {
+        use crate::flags::FlagBits;
+        F::from_bits_with(F::Bits::try_get_le(self)?, unknown)
+    }
+
+    get_primitive_checked_be!(u8, 1);
+    get_primitive_checked_be!(i8, 1);
+
+    get_primitive_checked_be!(u16, 2);
+    get_primitive_checked_be!(i16, 2);
+    get_primitive_checked_be!(u32, 4);
+    get_primitive_checked_be!(i32, 4);
+    get_primitive_checked_be!(u64, 8);
+    get_primitive_checked_be!(i64, 8);
+    get_primitive_checked_be!(u128, 16);
+    get_primitive_checked_be!(i128, 16);
+
+    get_primitive_checked_le!(u16, 2);
+    get_primitive_checked_le!(i16, 2);
+    get_primitive_checked_le!(u32, 4);
+    get_primitive_checked_le!(i32, 4);
+    get_primitive_checked_le!(u64, 8);
+    get_primitive_checked_le!(i64, 8);
+    get_primitive_checked_le!(u128, 16);
+    get_primitive_checked_le!(i128, 16);
+
+    peek_primitive_at_be!(u8, 1);
+    peek_primitive_at_be!(i8, 1);
+
+    peek_primitive_at_be!(u16, 2);
+    peek_primitive_at_be!(i16, 2);
+    peek_primitive_at_be!(u32, 4);
+    peek_primitive_at_be!(i32, 4);
+    peek_primitive_at_be!(u64, 8);
+    peek_primitive_at_be!(i64, 8);
+    peek_primitive_at_be!(u128, 16);
+    peek_primitive_at_be!(i128, 16);
+
+    peek_primitive_at_le!(u16, 2);
+    peek_primitive_at_le!(i16, 2);
+    peek_primitive_at_le!(u32, 4);
+    peek_primitive_at_le!(i32, 4);
+    peek_primitive_at_le!(u64, 8);
+    peek_primitive_at_le!(i64, 8);
+    peek_primitive_at_le!(u128, 16);
+    peek_primitive_at_le!(i128, 16);
+
+    get_primitive_slice_checked!(u16, 2, be);
+    get_primitive_slice_checked!(i16, 2, be);
+    get_primitive_slice_checked!(u32, 4, be);
+    get_primitive_slice_checked!(i32, 4, be);
+    get_primitive_slice_checked!(u64, 8, be);
+    get_primitive_slice_checked!(i64, 8, be);
+    get_primitive_slice_checked!(u128, 16, be);
+    get_primitive_slice_checked!(i128, 16, be);
+
+    get_primitive_slice_checked!(u16, 2, le);
+    get_primitive_slice_checked!(i16, 2, le);
+    get_primitive_slice_checked!(u32, 4, le);
+    get_primitive_slice_checked!(i32, 4, le);
+    get_primitive_slice_checked!(u64, 8, le);
+    get_primitive_slice_checked!(i64, 8, le);
+    get_primitive_slice_checked!(u128, 16, le);
+    get_primitive_slice_checked!(i128, 16, le);
+}
+
+impl<T> SafeBuf for T where T: Buf {}
+
+/// Combine the differences between two equal-length slices, without
+/// branching on their contents
+///
+/// Returns zero if and only if the slices are equal.
+#[inline(never)]
+fn constant_time_diff(a: &[u8], b: &[u8]) -> u8 {
+    a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b))
+}
+
+/// Whether the buffer starts with `expected`, without advancing it
+fn starts_with<B>(buffer: &B, expected: &[u8]) -> core::result::Result<bool, error::Truncated>
+where
+    B: SafeBuf + ?Sized,
+{
+    const BLOCK_LEN: usize = 64;
+
+    error::Truncated::check(expected.len(), buffer.remaining())?;
+
+    let mut block = [0; BLOCK_LEN];
+    for (i, expected) in expected.chunks(BLOCK_LEN).enumerate() {
+        let block = &mut block[..expected.len()];
+        buffer.try_peek_at_to_slice(i * BLOCK_LEN, block)?;
+        if block != expected {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Validate a duration read by [`SafeBuf::try_get_duration_secs_nanos`],
+/// advancing the buffer past it if it is valid
+fn duration_from_secs_nanos<B>(buffer: &mut B, secs: u64, nanos: u32) -> crate::Result<Duration>
+where
+    B: Buf + ?Sized,
+{
+    if nanos >= 1_000_000_000 {
+        return Err(crate::Error::invalid("duration nanoseconds", nanos));
+    }
+
+    buffer.advance(12);
+    Ok(Duration::new(secs, nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use paste::paste;
+
+    use super::SafeBuf;
+    use crate::{error, BufMut, FromBuf};
+
+    #[test]
+    fn try_copy_to_bytes() {
+        let mut bytes = BytesMut::new();
+        bytes.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        assert!(bytes.try_copy_to_bytes(4).is_ok());
+        assert!(bytes.try_copy_to_bytes(4).is_ok());
+        assert!(bytes.try_copy_to_bytes(4).is_err());
+    }
+
+    #[test]
+    fn try_copy_to_slice() {
+        let mut bytes = BytesMut::new();
+        bytes.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let dst = &mut [0_u8; 4];
+
+        assert!(bytes.try_copy_to_slice(dst).is_ok());
+        assert!(bytes.try_copy_to_slice(dst).is_ok());
+        assert!(bytes.try_copy_to_slice(dst).is_err());
+    }
+
+    #[test]
+    fn truncated_reports_counts() {
+        let mut buffer = &[0_u8, 1, 2][..];
+
+        let err = buffer.try_get_u64().unwrap_err();
+        assert_eq!(err, error::Truncated::new(8, 3));
+        assert_eq!(err.missing(), 5);
+
+        assert_eq!(
+            buffer.try_peek_at(2, 4).unwrap_err(),
+            error::Truncated::new(4, 1)
+        );
+    }
+
+    #[test]
+    fn should_be_exhausted() {
+        let mut buffer = &[0_u8, 1, 2][..];
+
+        let err = buffer.should_be_exhausted().unwrap_err();
+        assert_eq!(err.remaining, 3);
+        assert_eq!(err.preview(), &[0, 1, 2]);
+
+        buffer.try_advance(3).unwrap();
+        assert!(buffer.should_be_exhausted().is_ok());
+    }
+
+    #[test]
+    fn take_rest() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        assert_eq!(
+            buffer.try_take_rest(5).unwrap_err(),
+            error::LimitExceeded::new("remaining length", 6, 5)
+        );
+        assert_eq!(buffer.len(), 6);
+
+        assert_eq!(buffer.try_take_rest(6).unwrap(), &[0, 1, 2, 3, 4, 5][..]);
+        assert!(buffer.is_empty());
+        assert!(buffer.take_rest().is_empty());
+    }
+
+    #[test]
+    fn extract_map() {
+        use crate::{framing::PrefixWidth, DuplicateKeys, Endian, Eui64, MacAddr};
+        use alloc::collections::BTreeMap;
+
+        let mut bytes = vec![0x00, 0x03];
+        for (key, value) in &[(1, 1), (2, 2), (1, 3)] {
+            bytes.extend_from_slice(&[*key; 6]);
+            bytes.extend_from_slice(&[*value; 8]);
+        }
+        let extract = |duplicates| {
+            (&bytes[..]).extract_map::<_, _, BTreeMap<MacAddr, Eui64>>(
+                PrefixWidth::U16,
+                Endian::Big,
+                3,
+                duplicates,
+            )
+        };
+
+        assert_eq!(
+            extract(DuplicateKeys::Reject).unwrap_err(),
+            crate::Error::invalid("duplicate map key", 2_u64)
+        );
+        let map = extract(DuplicateKeys::KeepFirst).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&MacAddr([1; 6])], Eui64([1; 8]));
+        let map = extract(DuplicateKeys::KeepLast).unwrap();
+        assert_eq!(map[&MacAddr([1; 6])], Eui64([3; 8]));
+
+        let error = (&bytes[..bytes.len() - 1])
+            .extract_map::<MacAddr, Eui64, BTreeMap<_, _>>(
+                PrefixWidth::U16,
+                Endian::Big,
+                3,
+                DuplicateKeys::KeepLast,
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[2]: object truncated (needed 8 bytes, but only 7 available)"
+        );
+
+        assert_eq!(
+            (&bytes[..])
+                .extract_map::<MacAddr, Eui64, BTreeMap<_, _>>(
+                    PrefixWidth::U16,
+                    Endian::Big,
+                    2,
+                    DuplicateKeys::KeepLast,
+                )
+                .unwrap_err(),
+            error::LimitExceeded::new("map length", 3, 2).into()
+        );
+    }
+
+    #[test]
+    fn transaction() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        let result = buffer.transaction(|buf| {
+            buf.try_get_u32()?;
+            buf.try_get_u32()
+        });
+        assert!(result.is_err());
+        assert_eq!(buffer.len(), 6);
+
+        let result = buffer.transaction(SafeBuf::try_get_u32);
+        assert_eq!(result, Ok(0x0001_0203));
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn try_extract() {
+        #[derive(Debug, PartialEq)]
+        struct Pair(u16, u16);
+
+        impl FromBuf for Pair {
+            fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+            where
+                B: bytes::Buf,
+            {
+                Ok(Self(
+                    SafeBuf::try_get_u16(&mut buffer)?,
+                    SafeBuf::try_get_u16(&mut buffer)?,
+                ))
+            }
+        }
+
+        let mut buffer = &[0_u8, 1, 2][..];
+
+        assert!(buffer.try_extract::<Pair>().is_err());
+        assert_eq!(buffer.len(), 3);
+
+        assert!(buffer.extract::<Pair>().is_err());
+        assert_eq!(buffer.len(), 1);
+
+        let mut buffer = &[0_u8, 1, 0, 2][..];
+        assert_eq!(buffer.try_extract::<Pair>().unwrap(), Pair(1, 2));
+
+        let mut buffer = &[0_u8, 1, 0, 2][..];
+        assert_eq!(buffer.extract_strict::<Pair>().unwrap(), Pair(1, 2));
+
+        let mut buffer = &[0_u8, 1, 0, 2, 0][..];
+        assert!(matches!(
+            buffer.extract_strict::<Pair>().unwrap_err(),
+            crate::Error::ExtraneousBytes(_)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn extract_n_parallel() {
+        #[derive(Debug, PartialEq)]
+        struct Record(u16);
+
+        impl FromBuf for Record {
+            fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+            where
+                B: bytes::Buf,
+            {
+                let value = SafeBuf::try_get_u16(&mut buffer)?;
+                if value == 0xFFFF {
+                    return Err(crate::Error::invalid("record", u64::from(value)));
+                }
+                Ok(Self(value))
+            }
+        }
+
+        // three records of three bytes each (the last byte is padding)
+        let mut buffer = &[0_u8, 1, 0, 0, 2, 0, 0, 3, 0, 9][..];
+        assert_eq!(
+            buffer.extract_n_parallel::<Record>(3, 3).unwrap(),
+            vec![Record(1), Record(2), Record(3)]
+        );
+        assert_eq!(buffer, &[9]);
+
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1, 0][..], &[0xFF_u8, 0xFF, 0][..]);
+        assert!(buffer.extract_n_parallel::<Record>(2, 3).is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 6);
+
+        let mut buffer = &[0_u8, 1][..];
+        assert!(buffer.extract_n_parallel::<Record>(2, 2).is_err());
+        assert!(buffer.extract_n_parallel::<Record>(1, 0).is_err());
+        assert_eq!(buffer.extract_n_parallel::<Record>(0, 2).unwrap(), vec![]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_expect_bytes() {
+        let expected: Vec<u8> = (0..100).collect();
+
+        let mut buffer = bytes::Buf::chain(&expected[..70], &[0xFF_u8][..]);
+        assert!(matches!(
+            buffer.try_expect_bytes(&expected).unwrap_err(),
+            crate::Error::Truncated(_)
+        ));
+
+        let mut received = expected.clone();
+        received[99] ^= 1;
+        let mut buffer = bytes::Buf::chain(&received[..70], &received[70..]);
+        assert_eq!(
+            buffer.try_expect_bytes(&expected).unwrap_err(),
+            error::Mismatch::new(expected.clone(), received.clone()).into()
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 100);
+
+        let mut buffer = bytes::Buf::chain(&expected[..70], &expected[70..]);
+        buffer.try_expect_bytes(&expected).unwrap();
+        assert_eq!(bytes::Buf::remaining(&buffer), 0);
+    }
+
+    #[test]
+    fn try_strip_prefix() {
+        let mut buffer = bytes::Buf::chain(&b"GE"[..], &b"T /"[..]);
+
+        assert!(!buffer.try_strip_prefix(b"POST", false).unwrap());
+        assert!(!buffer.try_strip_prefix(b"GET / HTTP", false).unwrap());
+        assert_eq!(
+            buffer.try_strip_prefix(b"GET / HTTP", true).unwrap_err(),
+            error::Truncated::new(10, 5).into()
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 5);
+
+        if cfg!(feature = "std") {
+            assert!(buffer.try_strip_prefix(b"GET ", true).unwrap());
+            assert_eq!(bytes::Buf::chunk(&buffer), b"/");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_expect_bytes_ct() {
+        let secret: Vec<u8> = (0..100).collect();
+
+        let mut buffer = bytes::Buf::chain(&secret[..70], &[0xFF_u8][..]);
+        assert!(matches!(
+            buffer.try_expect_bytes_ct(&secret).unwrap_err(),
+            crate::Error::Truncated(_)
+        ));
+
+        let mut received = secret.clone();
+        received[99] ^= 1;
+        let mut buffer = bytes::Buf::chain(&received[..70], &received[70..]);
+        assert_eq!(
+            buffer.try_expect_bytes_ct(&secret).unwrap_err(),
+            crate::Error::Deserialization("secret mismatch")
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 100);
+
+        let mut buffer = bytes::Buf::chain(&secret[..70], &secret[70..]);
+        buffer.try_expect_bytes_ct(&secret).unwrap();
+        assert_eq!(bytes::Buf::remaining(&buffer), 0);
+    }
+
+    #[test]
+    fn try_advance() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        assert!(buffer.try_advance(4).is_ok());
+        assert!(buffer.try_advance(4).is_err());
+        assert_eq!(buffer, &[4, 5]);
+
+        assert_eq!(buffer.try_skip_exact(3), Err(2));
+        assert_eq!(buffer.try_skip_exact(2), Ok(()));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn try_align_to() {
+        let mut buffer = &[0_u8; 8][..];
+
+        assert_eq!(buffer.try_align_to(0, 4), Ok(0));
+        assert_eq!(buffer.try_align_to(5, 4), Ok(3));
+        assert_eq!(buffer.try_align_to(3, 0), Ok(0));
+        assert_eq!(buffer.len(), 5);
+
+        assert!(buffer.try_align_to(1, 8).is_err());
+        assert_eq!(buffer.len(), 5);
+    }
+
+    #[test]
+    fn try_peek_does_not_advance() {
+        let mut bytes = BytesMut::new();
+        bytes.extend_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        assert_eq!(bytes.try_peek(4).unwrap(), &[0, 1, 2, 3][..]);
+        assert_eq!(bytes.try_peek_const::<4>().unwrap(), [0, 1, 2, 3]);
+        assert_eq!(bytes.len(), 10);
+
+        assert!(bytes.try_peek(11).is_err());
+        assert!(bytes.try_peek_const::<11>().is_err());
+        assert_eq!(bytes.len(), 10);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_peek_across_chunks() {
+        let buffer = bytes::Buf::chain(&[0_u8, 1, 2][..], &[3_u8, 4, 5][..]);
+
+        assert_eq!(buffer.try_peek(5).unwrap(), &[0, 1, 2, 3, 4][..]);
+        assert_eq!(buffer.try_peek_const::<6>().unwrap(), [0, 1, 2, 3, 4, 5]);
+        assert!(buffer.try_peek(7).is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 6);
+    }
+
+    #[test]
+    fn try_peek_huge_len() {
+        let buffer = &[0_u8, 1, 2][..];
+
+        assert_eq!(
+            buffer.try_peek(usize::MAX).unwrap_err(),
+            error::Truncated::new(usize::MAX, 3)
+        );
+        assert_eq!(
+            buffer.try_peek(usize::MAX >> 1).unwrap_err(),
+            error::Truncated::new(usize::MAX >> 1, 3)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_peek_within_region() {
+        let mut buffer = bytes::Buf::chain(&[1_u8, 2, 3][..], &[4_u8, 5, 6][..]);
+
+        let region = buffer.try_take_buf(6).unwrap();
+        assert_eq!(region.try_peek(5).unwrap(), &[1, 2, 3, 4, 5][..]);
+        assert_eq!(region.try_peek_at(2, 4).unwrap(), &[3, 4, 5, 6][..]);
+        assert_eq!(region.try_peek(7).unwrap_err(), error::Truncated::new(7, 6));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_peek_across_many_chunks() {
+        use std::io::IoSlice;
+
+        /// A buffer made of one-byte chunks
+        struct Bytewise(Vec<u8>);
+
+        impl bytes::Buf for Bytewise {
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+
+            fn chunk(&self) -> &[u8] {
+                &self.0[..self.0.len().min(1)]
+            }
+
+            fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+                let n = dst.len().min(self.0.len());
+                for (dst, byte) in dst.iter_mut().zip(self.0.chunks(1)) {
+                    *dst = IoSlice::new(byte);
+                }
+                n
+            }
+
+            fn advance(&mut self, cnt: usize) {
+                self.0.drain(..cnt);
+            }
+        }
+
+        let data: Vec<u8> = (0..=255).collect();
+        let buffer = Bytewise(data.clone());
+
+        assert_eq!(buffer.try_peek(256).unwrap(), &data[..]);
+        assert_eq!(buffer.try_peek_at(200, 50).unwrap(), &data[200..250]);
+        assert!(buffer.try_peek(257).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_peek_at() {
+        let buffer = bytes::Buf::chain(&[0_u8, 1, 2][..], &[3_u8, 4, 5][..]);
+
+        assert_eq!(buffer.try_peek_at(1, 2).unwrap(), &[1, 2][..]);
+        assert_eq!(buffer.try_peek_at(2, 3).unwrap(), &[2, 3, 4][..]);
+        assert_eq!(buffer.try_peek_at(6, 0).unwrap(), &[][..]);
+        assert!(buffer.try_peek_at(4, 3).is_err());
+        assert!(buffer.try_peek_at(usize::MAX, 1).is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 6);
+    }
+
+    #[test]
+    fn try_peek_at_huge_len() {
+        let buffer = &[0_u8, 1, 2][..];
+
+        assert_eq!(
+            buffer.try_peek_at(2, usize::MAX).unwrap_err(),
+            error::Truncated::new(usize::MAX, 1)
+        );
+        assert_eq!(
+            buffer.try_peek_at(1, usize::MAX >> 1).unwrap_err(),
+            error::Truncated::new(usize::MAX >> 1, 2)
+        );
+        assert_eq!(
+            buffer.try_peek_at(usize::MAX, usize::MAX).unwrap_err(),
+            error::Truncated::new(usize::MAX, 0)
+        );
+    }
+
+    #[test]
+    fn try_get_at() {
+        let buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        assert_eq!(buffer.try_get_u8_at(5).unwrap(), 5);
+        assert_eq!(buffer.try_get_u16_at(1).unwrap(), 0x0102);
+        assert_eq!(buffer.try_get_u32_le_at(2).unwrap(), 0x0504_0302);
+        assert!(buffer.try_get_u32_at(3).is_err());
+        assert_eq!(buffer.len(), 6);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_copy_to_slices() {
+        use std::io::IoSliceMut;
+
+        let mut buffer = bytes::Buf::chain(&[1, 2, 3][..], &[4, 5][..]);
+        let mut header = [0; 2];
+        let mut body = [0; 3];
+
+        {
+            let mut too_long = [0; 4];
+            let mut slices = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut too_long)];
+            assert!(buffer.try_copy_to_slices(&mut slices).is_err());
+            assert_eq!(bytes::Buf::remaining(&buffer), 5);
+        }
+
+        let mut slices = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)];
+        buffer.try_copy_to_slices(&mut slices).unwrap();
+        assert_eq!(header, [1, 2]);
+        assert_eq!(body, [3, 4, 5]);
+    }
+
+    #[test]
+    fn try_take_into() {
+        let mut scratch = [0; 4];
+        let mut buffer = bytes::Buf::chain(&[1, 2, 3][..], &[4, 5, 6][..]);
+
+        assert_eq!(
+            buffer.try_take_into(&mut scratch, 4).unwrap(),
+            &[1, 2, 3, 4]
+        );
+        assert!(matches!(
+            buffer.try_take_into(&mut scratch, 5).unwrap_err(),
+            crate::Error::LimitExceeded(_)
+        ));
+        assert!(matches!(
+            buffer.try_take_into(&mut scratch, 3).unwrap_err(),
+            crate::Error::Truncated(_)
+        ));
+        assert_eq!(bytes::Buf::remaining(&buffer), 2);
+    }
+
+    #[test]
+    fn try_copy_to_bytes_limited() {
+        let mut buffer = &[0_u8, 1, 2, 3][..];
+
+        assert_eq!(
+            buffer.try_copy_to_bytes_limited(8, 4).unwrap_err(),
+            error::LimitExceeded::new("allocation", 8, 4).into()
+        );
+        assert!(matches!(
+            buffer.try_copy_to_bytes_limited(6, 8).unwrap_err(),
+            crate::Error::Truncated(_)
+        ));
+        assert_eq!(
+            buffer.try_copy_to_bytes_limited(4, 4).unwrap(),
+            &[0, 1, 2, 3][..]
+        );
+    }
+
+    #[test]
+    fn with_length_prefix() {
+        use crate::{framing::PrefixWidth, Endian};
+
+        let parse = |buffer: &mut &[u8]| {
+            buffer.with_length_prefix(PrefixWidth::U8, Endian::Big, |region| {
+                Ok(region.try_get_u16_le()?)
+            })
+        };
+
+        let mut buffer = &[2_u8, 1, 0, 9][..];
+        assert_eq!(parse(&mut buffer).unwrap(), 1);
+        assert_eq!(buffer, &[9]);
+
+        // region not fully consumed
+        let mut buffer = &[3_u8, 1, 0, 0, 9][..];
+        assert!(matches!(
+            parse(&mut buffer).unwrap_err(),
+            crate::Error::ExtraneousBytes(_)
+        ));
+        assert_eq!(buffer, &[9]);
+
+        // parser overruns the region
+        let mut buffer = &[1_u8, 1, 0, 9][..];
+        assert!(parse(&mut buffer).is_err());
+        assert_eq!(buffer, &[0, 9]);
+
+        // region truncated
+        let mut buffer = &[4_u8, 1, 0][..];
+        assert!(parse(&mut buffer).is_err());
+        assert_eq!(buffer.len(), 3);
+    }
+
+    #[test]
+    fn take_prefixed_and_parse() {
+        use crate::{framing::PrefixWidth, Endian, MacAddr};
+
+        let mut buffer = &[
+            0x00, 0x06, 1, 2, 3, 4, 5, 6, 0x00, 0x07, 1, 2, 3, 4, 5, 6, 7, 9,
+        ][..];
+
+        assert_eq!(
+            buffer
+                .take_prefixed_and_parse::<MacAddr>(PrefixWidth::U16, Endian::Big)
+                .unwrap(),
+            MacAddr([1, 2, 3, 4, 5, 6])
+        );
+        assert!(matches!(
+            buffer
+                .take_prefixed_and_parse::<MacAddr>(PrefixWidth::U16, Endian::Big)
+                .unwrap_err(),
+            crate::Error::ExtraneousBytes(_)
+        ));
+        assert_eq!(buffer, &[9]);
+    }
+
+    #[test]
+    fn extract_versioned() {
+        use crate::{framing::PrefixWidth, Endian};
+
+        let parse = |buffer: &mut &[u8]| {
+            buffer.extract_versioned(
+                PrefixWidth::U16,
+                Endian::Little,
+                2..=3,
+                |version, buffer| Ok((version, buffer.try_get_u8()?)),
+            )
+        };
+
+        let mut buffer = &[0x03, 0x00, 0xAA, 0x01, 0x00, 0xBB][..];
+        assert_eq!(parse(&mut buffer).unwrap(), (3, 0xAA));
+        assert_eq!(
+            parse(&mut buffer).unwrap_err(),
+            error::UnsupportedVersion::new(1, 2..=3).into()
+        );
+        assert_eq!(buffer, &[0x01, 0x00, 0xBB]);
+    }
+
+    #[test]
+    #[cfg(feature = "crc")]
+    fn try_take_with_crc() {
+        use crate::Endian;
+
+        let mut buffer = &b"123456789\xB1\x29123456789\x26\x39\xF4\xCB!"[..];
+        assert_eq!(
+            buffer.try_take_with_crc16(9, Endian::Big).unwrap_err(),
+            error::ChecksumMismatch::new(0xB129, 0x29B1).into()
+        );
+        assert_eq!(
+            buffer.try_take_with_crc16(9, Endian::Little).unwrap(),
+            &b"123456789"[..]
+        );
+        assert!(matches!(
+            buffer.try_take_with_crc32(10, Endian::Little).unwrap_err(),
+            crate::Error::ChecksumMismatch(_)
+        ));
+        assert_eq!(
+            buffer.try_take_with_crc32(9, Endian::Little).unwrap(),
+            &b"123456789"[..]
+        );
+        assert_eq!(
+            buffer.try_take_with_crc32(0, Endian::Little).unwrap_err(),
+            error::Truncated::new(4, 1).into()
+        );
+        assert_eq!(buffer, b"!");
+    }
+
+    #[test]
+    fn try_copy_to_uninit() {
+        use core::mem::MaybeUninit;
+
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1, 2][..], &[3_u8, 4, 5][..]);
+        let mut dst = [MaybeUninit::<u8>::uninit(); 5];
+
+        assert_eq!(
+            buffer.try_copy_to_uninit(&mut dst).unwrap(),
+            &[0, 1, 2, 3, 4]
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+
+        let error = buffer.try_copy_to_uninit(&mut dst).unwrap_err();
+        assert_eq!(error, error::Truncated::new(5, 1));
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+    }
+
+    #[test]
+    fn try_copy_to_uninit_empty_chunk() {
+        use core::mem::MaybeUninit;
+
+        /// A buffer which claims more bytes than its chunks hold
+        struct Overstated<'a>(&'a [u8]);
+
+        impl bytes::Buf for Overstated<'_> {
+            fn remaining(&self) -> usize {
+                self.0.len() + 2
+            }
+
+            fn chunk(&self) -> &[u8] {
+                self.0
+            }
+
+            fn advance(&mut self, cnt: usize) {
+                self.0 = &self.0[cnt..];
+            }
+        }
+
+        let mut buffer = Overstated(&[0, 1, 2]);
+        let mut dst = [MaybeUninit::<u8>::uninit(); 5];
+        let error = buffer.try_copy_to_uninit(&mut dst).unwrap_err();
+        assert_eq!(error, error::Truncated::new(5, 3));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_find() {
+        let buffer = bytes::Buf::chain(&b"--boundary\r"[..], &b"\n--end"[..]);
+
+        assert_eq!(buffer.try_find(b"\r\n--"), Some(10));
+        assert_eq!(buffer.try_find(b"--"), Some(0));
+        assert_eq!(buffer.try_find(b"end"), Some(14));
+        assert_eq!(buffer.try_find(b"--start"), None);
+        assert_eq!(bytes::Buf::remaining(&buffer), 17);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_take_until_pattern() {
+        use crate::Delimiter;
+
+        let mut buffer = bytes::Buf::chain(&b"\0\0\x01ab\0"[..], &b"\0\x01c"[..]);
+
+        let unit = buffer.try_take_until_pattern(b"\0\0\x01", Delimiter::Retain);
+        assert_eq!(unit.unwrap(), &b""[..]);
+        assert_eq!(bytes::Buf::remaining(&buffer), 9);
+
+        bytes::Buf::advance(&mut buffer, 3);
+        let unit = buffer.try_take_until_pattern(b"\0\0\x01", Delimiter::Consume);
+        assert_eq!(unit.unwrap(), &b"ab"[..]);
+        assert_eq!(
+            buffer.try_take_until_pattern(b"\0\0\x01", Delimiter::Consume),
+            None
+        );
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn split_on_pattern() {
+        let mut buffer = bytes::Buf::chain(&b"one\r\ntwo\r"[..], &b"\n\r\nthr"[..]);
+
+        let mut records = buffer.split_on_pattern(b"\r\n");
+        assert_eq!(records.next().unwrap(), &b"one"[..]);
+        assert_eq!(records.next().unwrap(), &b"two"[..]);
+        assert_eq!(records.next().unwrap(), &b""[..]);
+        assert_eq!(records.next(), None);
+        assert_eq!(records.partial_len(), 3);
+        assert_eq!(buffer.split_on_pattern(b"").count(), 0);
+
+        let mut buffer = &b"x--y--"[..];
+        let records: Vec<_> = buffer.split_on_pattern(b"--").collect();
+        assert_eq!(records, [&b"x"[..], &b"y"[..]]);
+        assert!(buffer.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_resync_to() {
+        use crate::Delimiter;
+
+        let mut buffer = bytes::Buf::chain(&b"garbage\0"[..], &b"\0\x01frame"[..]);
+        assert_eq!(
+            buffer.try_resync_to(b"\0\0\x01", Delimiter::Consume),
+            Some(7)
+        );
+        assert_eq!(bytes::Buf::chunk(&buffer), b"frame");
+
+        // the end of the buffer might be the start of a marker
+        let mut buffer = bytes::Buf::chain(&b"noise"[..], &b"\0\0"[..]);
+        assert_eq!(buffer.try_resync_to(b"\0\0\x01", Delimiter::Retain), None);
+        assert_eq!(bytes::Buf::remaining(&buffer), 2);
+
+        let mut buffer = &b"abc"[..];
+        assert_eq!(buffer.try_resync_to(b"", Delimiter::Retain), Some(0));
+        assert_eq!(buffer.try_resync_to(b"x", Delimiter::Retain), None);
+        assert!(buffer.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_resync_to_not_found_discards() {
+        use crate::Delimiter;
+
+        // a miss still discards everything which cannot start a marker
+        let mut buffer = &b"abcde\0\0"[..];
+        assert_eq!(buffer.try_resync_to(b"\0\0\x01", Delimiter::Consume), None);
+        assert_eq!(buffer, b"\0\0");
+
+        // once the rest of the marker arrives, only the kept bytes are counted
+        let mut buffer = bytes::Buf::chain(buffer, &b"\x01frame"[..]);
+        assert_eq!(
+            buffer.try_resync_to(b"\0\0\x01", Delimiter::Consume),
+            Some(0)
+        );
+        assert_eq!(bytes::Buf::chunk(&buffer), b"frame");
+
+        let mut buffer = &b"abc"[..];
+        assert_eq!(buffer.try_resync_to(b"\0\0\x01", Delimiter::Retain), None);
+        assert_eq!(buffer, b"bc");
+    }
+
+    #[test]
+    fn try_take_utf8_stream() {
+        use crate::Utf8Validator;
+
+        // each read ends part-way through a code point
+        let text = "\u{1f980} \u{e9}t\u{e9}".as_bytes();
+        let mut validator = Utf8Validator::new();
+        for piece in text.chunks(3) {
+            let mut buffer = piece;
+            assert_eq!(
+                buffer
+                    .try_take_utf8_stream(piece.len(), &mut validator)
+                    .unwrap(),
+                piece
+            );
+        }
+        validator.finish().unwrap();
+
+        let mut buffer = &b"\xF0\x9F\xA6\x80\xF0\x28"[..];
+        let mut validator = Utf8Validator::new();
+        buffer.try_take_utf8_stream(5, &mut validator).unwrap();
+        assert!(buffer.try_take_utf8_stream(1, &mut validator).is_err());
+        assert_eq!(buffer, b"\x28");
+        assert_eq!(validator.valid_len(), 4);
+    }
+
+    #[test]
+    fn try_take_hex() {
+        let mut buffer = &b"00fFa9xyz"[..];
+
+        assert_eq!(
+            buffer.try_take_hex(3).unwrap_err(),
+            crate::Error::invalid("hex length", 3_u64)
+        );
+        assert_eq!(
+            buffer.try_take_hex(8).unwrap_err(),
+            crate::Error::invalid("hex digit", &b"x"[..])
+        );
+        assert_eq!(
+            buffer.try_take_hex(10).unwrap_err(),
+            error::Truncated::new(10, 9).into()
+        );
+        assert_eq!(buffer.try_take_hex(6).unwrap(), &[0x00, 0xFF, 0xA9][..]);
+        assert_eq!(buffer.try_take_hex(0).unwrap(), &[][..]);
+        assert_eq!(buffer, b"xyz");
+    }
+
+    #[test]
+    fn try_take_base64() {
+        use crate::Base64Alphabet::{Standard, UrlSafe};
+
+        let decode = |text: &[u8], alphabet| (&text[..]).try_take_base64(text.len(), alphabet);
+
+        assert_eq!(decode(b"", Standard).unwrap(), &b""[..]);
+        assert_eq!(decode(b"Zg==", Standard).unwrap(), &b"f"[..]);
+        assert_eq!(decode(b"Zm8", Standard).unwrap(), &b"fo"[..]);
+        assert_eq!(decode(b"Zm9vYmFy", Standard).unwrap(), &b"foobar"[..]);
+        assert_eq!(decode(b"-_8=", UrlSafe).unwrap(), &[0xFB, 0xFF][..]);
+
+        assert_eq!(
+            decode(b"-_8=", Standard).unwrap_err(),
+            crate::Error::invalid("base64 character", &b"-"[..])
+        );
+        assert_eq!(
+            decode(b"Zm9=vg==", Standard).unwrap_err(),
+            crate::Error::invalid("base64 character", &b"="[..])
+        );
+        assert_eq!(
+            decode(b"Zm8==", Standard).unwrap_err(),
+            crate::Error::invalid("base64 padding", &b"=="[..])
+        );
+        assert_eq!(
+            decode(b"Zm9vY", Standard).unwrap_err(),
+            crate::Error::invalid("base64 length", 5_u64)
+        );
+        assert_eq!(
+            decode(b"Zh==", Standard).unwrap_err(),
+            crate::Error::invalid("base64 trailing bits", &b"Zh"[..])
+        );
+    }
+
+    #[test]
+    fn try_take_base64_until() {
+        let mut buffer = &b"Zm9v Y!Fy"[..];
+
+        let foo = buffer.try_take_base64_until(b' ', crate::Base64Alphabet::Standard);
+        assert_eq!(foo.unwrap().unwrap(), &b"foo"[..]);
+        assert!(buffer
+            .try_take_base64_until(b'\n', crate::Base64Alphabet::Standard)
+            .unwrap()
+            .is_none());
+        assert!(buffer
+            .try_take_base64_until(b'y', crate::Base64Alphabet::Standard)
+            .is_err());
+        assert_eq!(buffer, b"Y!Fy");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_take_until() {
+        let mut buffer = bytes::Buf::chain(&b"ab\ncd"[..], &b"e\nf"[..]);
+
+        assert_eq!(buffer.try_take_until(b'\n').unwrap(), &b"ab"[..]);
+        assert_eq!(buffer.try_take_until(b'\n').unwrap(), &b"cde"[..]);
+        assert_eq!(buffer.try_take_until(b'\n'), None);
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+    }
+
+    #[test]
+    fn try_take_const() {
+        let mut chained = bytes::Buf::chain(&[1, 2][..], &[3, 4][..]);
+
+        assert_eq!(chained.try_take_const::<1>().unwrap(), [1]);
+        assert_eq!(chained.try_take_const::<2>().unwrap(), [2, 3]);
+        assert!(chained.try_take_const::<2>().is_err());
+        assert_eq!(chained.try_take_const::<1>().unwrap(), [4]);
+    }
+
+    #[test]
+    fn try_chunk_exact() {
+        let chained = bytes::Buf::chain(&[1, 2][..], &[3, 4][..]);
+
+        assert_eq!(chained.try_chunk_exact(2).unwrap(), Some(&[1, 2][..]));
+        assert_eq!(chained.try_chunk_exact(3).unwrap(), None);
+        assert!(chained.try_chunk_exact(5).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "num-traits")]
+    fn try_get_primitive() {
+        let mut buffer = bytes::Buf::chain(&[0xFF, 0xFE, 0x01][..], &[0x00, 0x80][..]);
+
+        assert_eq!(buffer.try_get_primitive::<i16>().unwrap(), -2);
+        assert_eq!(buffer.try_get_primitive_le::<u16>().unwrap(), 1);
+        assert_eq!(
+            buffer.try_get_primitive::<u32>().unwrap_err(),
+            error::Truncated::new(4, 1)
+        );
+        assert_eq!(buffer.try_get_primitive::<u8>().unwrap(), 0x80);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timestamps() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut buffer: &[u8] = &[0, 0, 0, 0, 0, 0, 0x01, 0x00];
+        assert_eq!(
+            buffer.try_get_unix_secs().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(256)
+        );
+
+        let mut buffer: &[u8] = &[0, 0, 0, 0, 0, 0, 0x03, 0xE9];
+        assert_eq!(
+            buffer.try_get_unix_millis().unwrap(),
+            UNIX_EPOCH + Duration::from_millis(1001)
+        );
+
+        // 1970-01-01T00:00:01.5 and 1969-12-31T23:59:59.5
+        let mut buffer: &[u8] = &[
+            0x83, 0xAA, 0x7E, 0x81, 0x80, 0, 0, 0, 0x83, 0xAA, 0x7E, 0x7F, 0x80, 0, 0, 0,
+        ];
+        assert_eq!(
+            buffer.try_get_ntp64().unwrap(),
+            UNIX_EPOCH + Duration::from_millis(1500)
+        );
+        assert_eq!(
+            buffer.try_get_ntp64().unwrap(),
+            UNIX_EPOCH - Duration::from_millis(500)
+        );
+
+        let mut buffer: &[u8] = &[0xFF; 8];
+        assert!(buffer.try_get_unix_secs().is_err());
+        assert_eq!(buffer.len(), 8);
+    }
+
+    #[test]
+    fn duration() {
+        use std::time::Duration;
+
+        let mut buffer: &[u8] = &[
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        assert_eq!(
+            buffer.try_get_duration_secs_nanos().unwrap(),
+            Duration::new(1, 2)
+        );
+        assert_eq!(
+            buffer.try_get_duration_secs_nanos_le().unwrap(),
+            Duration::new(1, 2)
+        );
+
+        let mut buffer: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0, 0x3B, 0x9A, 0xCA, 0x00];
+        assert!(buffer.try_get_duration_secs_nanos().is_err());
+        assert_eq!(buffer.len(), 12);
+    }
+
+    #[test]
+    fn try_get_slice() {
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1, 0, 2, 0][..], &[3_u8, 4, 0, 5][..]);
+
+        assert!(buffer.try_get_u16_slice(5).is_err());
+        assert_eq!(bytes::Buf::remaining(&buffer), 9);
+
+        assert_eq!(buffer.try_get_u16_slice(3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(buffer.try_get_u16_slice_le(1).unwrap(), vec![4]);
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+
+        let mut buffer = &[1_u8, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF][..];
+        let mut dst = [0_i32; 2];
+        buffer.try_copy_to_i32_slice_le(&mut dst).unwrap();
+        assert_eq!(dst, [1, -1]);
+        assert!(buffer.is_empty());
+
+        assert!(buffer.try_get_u64_slice(usize::MAX).is_err());
     }
 
     macro_rules! round_trip {
@@ -0,0 +1,247 @@
+//! Extension trait for reading from the back of contiguous buffers
+
+use crate::{error, SafeBuf};
+use bytes::{Bytes, BytesMut};
+use paste::paste;
+
+macro_rules! get_primitive_back_be {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a big-endian `" $t "` from the back of the buffer, with a bounds check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn [<try_get_ $t _back>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                let mut bytes = [0; $width];
+                self.try_copy_back_to_slice(&mut bytes)?;
+                Ok($t::from_be_bytes(bytes))
+            }
+        }
+    };
+}
+
+macro_rules! get_primitive_back_le {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a little-endian `" $t "` from the back of the buffer, with a bounds check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            fn [<try_get_ $t _le_back>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                let mut bytes = [0; $width];
+                self.try_copy_back_to_slice(&mut bytes)?;
+                Ok($t::from_le_bytes(bytes))
+            }
+        }
+    };
+}
+
+/// Extension trait for buffers which can be consumed from the back
+///
+/// Many containers (ZIP archives, checksum trailers, TLV footers) are parsed
+/// from the end. This trait is implemented for contiguous buffers, where the
+/// back of the buffer is directly accessible (so
+/// [`Buf::chunk`](bytes::Buf::chunk) returns every remaining byte).
+pub trait SafeBufBack: SafeBuf {
+    /// Take a given number of bytes from the back of the buffer, with a check
+    /// to ensure there are enough remaining
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_take_back(&mut self, len: usize) -> core::result::Result<Bytes, error::Truncated>;
+
+    /// Take a given number of bytes from the back of the buffer and write to a
+    /// slice, with a check to ensure there are enough remaining
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn try_copy_back_to_slice(
+        &mut self,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated>;
+
+    /// Trim a known suffix from the back of the buffer if the buffer ends with
+    /// it, returning whether it did
+    ///
+    /// If `required` is `false`, a missing suffix is not an error, and the
+    /// buffer is left untouched.
+    ///
+    /// ```
+    /// use safer_bytes::SafeBufBack;
+    ///
+    /// let mut buffer = &b"payload\r\n"[..];
+    /// assert!(buffer.try_strip_suffix(b"\r\n", true)?);
+    /// assert!(!buffer.try_strip_suffix(b"\r\n", false)?);
+    /// assert_eq!(buffer, b"payload");
+    ///
+    /// assert!(buffer.try_strip_suffix(b"\r\n", true).is_err());
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If `required` is `true`, this method will return an error if the number
+    /// of bytes remaining in the buffer is insufficent, or if the bytes do not
+    /// match.
+    fn try_strip_suffix(&mut self, suffix: &[u8], required: bool) -> crate::Result<bool> {
+        let data = self.chunk();
+        if data.ends_with(suffix) {
+            self.try_take_back(suffix.len())?;
+            return Ok(true);
+        }
+        if !required {
+            return Ok(false);
+        }
+
+        error::Truncated::check(suffix.len(), data.len())?;
+        let found = &data[data.len() - suffix.len()..];
+        Err(error::Mismatch::new(
+            Bytes::copy_from_slice(suffix),
+            Bytes::copy_from_slice(found),
+        )
+        .into())
+    }
+
+    get_primitive_back_be!(u8, 1);
+    get_primitive_back_be!(i8, 1);
+
+    get_primitive_back_be!(u16, 2);
+    get_primitive_back_be!(i16, 2);
+    get_primitive_back_be!(u32, 4);
+    get_primitive_back_be!(i32, 4);
+    get_primitive_back_be!(u64, 8);
+    get_primitive_back_be!(i64, 8);
+    get_primitive_back_be!(u128, 16);
+    get_primitive_back_be!(i128, 16);
+
+    get_primitive_back_le!(u16, 2);
+    get_primitive_back_le!(i16, 2);
+    get_primitive_back_le!(u32, 4);
+    get_primitive_back_le!(i32, 4);
+    get_primitive_back_le!(u64, 8);
+    get_primitive_back_le!(i64, 8);
+    get_primitive_back_le!(u128, 16);
+    get_primitive_back_le!(i128, 16);
+}
+
+impl SafeBufBack for Bytes {
+    fn try_take_back(&mut self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
+        error::Truncated::check(len, self.len())?;
+        let at = self.len() - len;
+        Ok(self.split_off(at))
+    }
+
+    fn try_copy_back_to_slice(
+        &mut self,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated> {
+        error::Truncated::check(dst.len(), self.len())?;
+        let at = self.len() - dst.len();
+        dst.copy_from_slice(&self[at..]);
+        self.truncate(at);
+        Ok(())
+    }
+}
+
+impl SafeBufBack for BytesMut {
+    fn try_take_back(&mut self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
+        error::Truncated::check(len, self.len())?;
+        let at = self.len() - len;
+        Ok(self.split_off(at).freeze())
+    }
+
+    fn try_copy_back_to_slice(
+        &mut self,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated> {
+        error::Truncated::check(dst.len(), self.len())?;
+        let at = self.len() - dst.len();
+        dst.copy_from_slice(&self[at..]);
+        self.truncate(at);
+        Ok(())
+    }
+}
+
+impl SafeBufBack for &[u8] {
+    fn try_take_back(&mut self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
+        error::Truncated::check(len, self.len())?;
+        let at = self.len() - len;
+        let (front, back) = self.split_at(at);
+        *self = front;
+        Ok(Bytes::copy_from_slice(back))
+    }
+
+    fn try_copy_back_to_slice(
+        &mut self,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated> {
+        error::Truncated::check(dst.len(), self.len())?;
+        let at = self.len() - dst.len();
+        let (front, back) = self.split_at(at);
+        dst.copy_from_slice(back);
+        *self = front;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SafeBufBack;
+    use crate::{error, SafeBuf};
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn try_take_back() {
+        let mut bytes = Bytes::from_static(&[0, 1, 2, 3, 4, 5]);
+
+        assert_eq!(bytes.try_take_back(2).unwrap(), &[4, 5][..]);
+        assert!(bytes.try_take_back(5).is_err());
+        assert_eq!(bytes, &[0, 1, 2, 3][..]);
+    }
+
+    #[test]
+    fn try_get_back() {
+        let mut slice = &[0_u8, 1, 2, 3, 4, 5, 6][..];
+
+        assert_eq!(slice.try_get_u32_back().unwrap(), 0x0304_0506);
+        assert_eq!(slice.try_get_u16_le_back().unwrap(), 0x0201);
+        assert!(slice.try_get_u16_back().is_err());
+        assert_eq!(slice.try_get_u8().unwrap(), 0);
+        assert!(slice.is_empty());
+    }
+
+    #[test]
+    fn bytes_mut_trailer() {
+        let mut bytes = BytesMut::from(&[0xAA, 0xBB, 0, 0, 0, 2][..]);
+
+        let len = bytes.try_get_u32_back().unwrap();
+        assert_eq!(len, 2);
+        assert_eq!(bytes.try_take_back(2).unwrap(), &[0xAA, 0xBB][..]);
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn try_strip_suffix() {
+        let mut bytes = Bytes::from_static(b"<msg>hi</msg>");
+
+        assert!(bytes.try_strip_suffix(b"</msg>", true).unwrap());
+        assert!(!bytes.try_strip_suffix(b"</msg>", false).unwrap());
+        assert_eq!(
+            bytes.try_strip_suffix(b"</msg>", true).unwrap_err(),
+            error::Mismatch::new(&b"</msg>"[..], &b"<msg>hi"[1..]).into()
+        );
+        assert_eq!(
+            bytes
+                .try_strip_suffix(b"too long a suffix", true)
+                .unwrap_err(),
+            error::Truncated::new(17, 7).into()
+        );
+        assert_eq!(bytes, &b"<msg>hi"[..]);
+    }
+}
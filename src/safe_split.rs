@@ -0,0 +1,98 @@
+//! Extension trait for splitting buffers without panicking
+
+use crate::error;
+use bytes::{Bytes, BytesMut};
+
+/// Extension trait for splitting [`Bytes`] and [`BytesMut`] without panicking
+///
+/// [`BytesMut::split_to`] and [`BytesMut::split_off`] panic if the index is
+/// out of bounds, which is easy to trigger when cutting frames out of a
+/// receive buffer using a length read from the wire. These methods check the
+/// index first, and leave the buffer untouched if it is out of bounds.
+///
+/// ```
+/// use safer_bytes::{BytesMut, SafeSplit};
+///
+/// let mut buffer = BytesMut::from(&b"\x05hello\x05wor"[..]);
+///
+/// let frame = buffer.try_split_to(6)?;
+/// assert_eq!(&frame[..], b"\x05hello");
+///
+/// assert!(buffer.try_split_to(6).is_err());
+/// assert_eq!(&buffer[..], b"\x05wor");
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+pub trait SafeSplit: Sized {
+    /// Split the buffer in two at the given index, returning `[0, at)` and
+    /// leaving `[at, len)` in the buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `at` is greater than the length of
+    /// the buffer
+    fn try_split_to(&mut self, at: usize) -> Result<Self, error::Truncated>;
+
+    /// Split the buffer in two at the given index, returning `[at, len)` and
+    /// leaving `[0, at)` in the buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `at` is greater than the length of
+    /// the buffer
+    fn try_split_off(&mut self, at: usize) -> Result<Self, error::Truncated>;
+}
+
+macro_rules! safe_split {
+    ($($t:ty),*) => {
+        $(
+            impl SafeSplit for $t {
+                fn try_split_to(&mut self, at: usize) -> Result<Self, error::Truncated> {
+                    error::Truncated::check(at, self.len())?;
+                    Ok(self.split_to(at))
+                }
+
+                fn try_split_off(&mut self, at: usize) -> Result<Self, error::Truncated> {
+                    error::Truncated::check(at, self.len())?;
+                    Ok(self.split_off(at))
+                }
+            }
+        )*
+    };
+}
+
+safe_split!(Bytes, BytesMut);
+
+#[cfg(test)]
+mod tests {
+    use super::SafeSplit;
+    use crate::error;
+    use bytes::{Bytes, BytesMut};
+
+    #[test]
+    fn bytes_mut() {
+        let mut buffer = BytesMut::from(&[1, 2, 3, 4, 5][..]);
+
+        assert_eq!(
+            buffer.try_split_off(6).unwrap_err(),
+            error::Truncated::new(6, 5)
+        );
+        assert_eq!(&buffer.try_split_off(4).unwrap()[..], [5]);
+        assert_eq!(&buffer.try_split_to(1).unwrap()[..], [1]);
+        assert_eq!(
+            buffer.try_split_to(4).unwrap_err(),
+            error::Truncated::new(4, 3)
+        );
+        assert!(buffer.try_split_off(3).unwrap().is_empty());
+        assert_eq!(&buffer[..], [2, 3, 4]);
+    }
+
+    #[test]
+    fn bytes() {
+        let mut buffer = Bytes::from_static(b"abc");
+
+        assert!(buffer.try_split_to(4).is_err());
+        assert_eq!(buffer.try_split_to(3).unwrap(), &b"abc"[..]);
+        assert!(buffer.try_split_off(0).unwrap().is_empty());
+        assert!(buffer.try_split_off(1).is_err());
+    }
+}
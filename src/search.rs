@@ -0,0 +1,202 @@
+//! Searching for patterns across the chunks of a [`bytes::Buf`]
+
+#[cfg(feature = "std")]
+use crate::safe_buf::MAX_PEEK_CHUNKS;
+use crate::SafeBuf;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use bytes::{Buf, Bytes};
+use memchr::memmem;
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// What to do with a delimiter or sync marker once it has been found
+///
+/// This is used by [`SafeBuf::try_resync_to`](crate::SafeBuf::try_resync_to)
+/// and [`SafeBuf::try_take_until_pattern`](crate::SafeBuf::try_take_until_pattern).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// Consume the delimiter, so that the buffer is positioned after it
+    Consume,
+
+    /// Leave the delimiter in the buffer, so that the buffer is positioned at
+    /// its start
+    Retain,
+}
+
+/// An iterator over the records in a buffer which are terminated by a
+/// multi-byte pattern
+///
+/// This is created by
+/// [`SafeBuf::split_on_pattern`](crate::SafeBuf::split_on_pattern).
+#[derive(Debug)]
+pub struct SplitOnPattern<'a, B> {
+    buffer: &'a mut B,
+    pattern: &'a [u8],
+}
+
+impl<'a, B> SplitOnPattern<'a, B>
+where
+    B: Buf,
+{
+    pub(crate) fn new(buffer: &'a mut B, pattern: &'a [u8]) -> Self {
+        Self { buffer, pattern }
+    }
+
+    /// The number of bytes left in the buffer, which do not (yet) form a
+    /// complete record
+    ///
+    /// Once the iterator is exhausted, a non-zero value indicates a trailing
+    /// partial record. Streaming callers can keep these bytes and resume once
+    /// more have arrived, and other callers can treat them as truncated.
+    #[must_use]
+    pub fn partial_len(&self) -> usize {
+        self.buffer.remaining()
+    }
+}
+
+impl<B> Iterator for SplitOnPattern<'_, B>
+where
+    B: Buf,
+{
+    type Item = Bytes;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pattern.is_empty() {
+            return None;
+        }
+        self.buffer
+            .try_take_until_pattern(self.pattern, Delimiter::Consume)
+    }
+}
+
+/// Find the offset of the first occurrence of `needle` in the buffer, without
+/// advancing it
+///
+/// The search covers the first
+/// [`MAX_PEEK_CHUNKS`](crate::safe_buf::MAX_PEEK_CHUNKS) chunks of the buffer
+/// (which is every chunk, for all but heavily fragmented buffers), including
+/// occurrences which straddle chunk boundaries. Without the `std` feature, only
+/// the first chunk is searched.
+pub(crate) fn find<B>(buffer: &B, needle: &[u8]) -> Option<usize>
+where
+    B: Buf + ?Sized,
+{
+    if needle.is_empty() {
+        return Some(0);
+    }
+
+    let chunk = buffer.chunk();
+    if chunk.len() == buffer.remaining() {
+        return find_in_slice(chunk, needle);
+    }
+
+    find_vectored(buffer, needle)
+}
+
+/// Find `needle` in a buffer which is split across several chunks
+#[cfg(feature = "std")]
+fn find_vectored<B>(buffer: &B, needle: &[u8]) -> Option<usize>
+where
+    B: Buf + ?Sized,
+{
+    let mut slices = [IoSlice::new(&[]); MAX_PEEK_CHUNKS];
+    let n = buffer.chunks_vectored(&mut slices);
+
+    // the last `needle.len() - 1` bytes seen, which may begin an occurrence
+    // that straddles the next chunk boundary
+    let mut tail = Vec::with_capacity(2 * needle.len());
+    let mut offset = 0;
+
+    for slice in &slices[..n] {
+        if !tail.is_empty() {
+            let tail_len = tail.len();
+            tail.extend_from_slice(&slice[..slice.len().min(needle.len() - 1)]);
+            if let Some(position) = memmem::find(&tail, needle) {
+                return Some(offset - tail_len + position);
+            }
+            tail.truncate(tail_len);
+        }
+
+        if let Some(position) = find_in_slice(slice, needle) {
+            return Some(offset + position);
+        }
+
+        tail.extend_from_slice(&slice[slice.len().saturating_sub(needle.len() - 1)..]);
+        let excess = tail.len().saturating_sub(needle.len() - 1);
+        tail.drain(..excess);
+        offset += slice.len();
+    }
+
+    None
+}
+
+/// Without `std` there is no [`Buf::chunks_vectored`], so only the first chunk
+/// of the buffer is searched
+#[cfg(not(feature = "std"))]
+fn find_vectored<B>(buffer: &B, needle: &[u8]) -> Option<usize>
+where
+    B: Buf + ?Sized,
+{
+    find_in_slice(buffer.chunk(), needle)
+}
+
+/// The number of bytes at the front of the buffer which [`find`] searches
+#[cfg(feature = "std")]
+pub(crate) fn searched_len<B>(buffer: &B) -> usize
+where
+    B: Buf + ?Sized,
+{
+    let mut slices = [IoSlice::new(&[]); MAX_PEEK_CHUNKS];
+    let n = buffer.chunks_vectored(&mut slices);
+    slices[..n].iter().map(|slice| slice.len()).sum()
+}
+
+/// The number of bytes at the front of the buffer which [`find`] searches
+#[cfg(not(feature = "std"))]
+pub(crate) fn searched_len<B>(buffer: &B) -> usize
+where
+    B: Buf + ?Sized,
+{
+    buffer.chunk().len()
+}
+
+fn find_in_slice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if let [byte] = needle {
+        memchr::memchr(*byte, haystack)
+    } else {
+        memmem::find(haystack, needle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find;
+
+    #[test]
+    fn contiguous() {
+        let buffer: &[u8] = b"GET / HTTP/1.1\r\n\r\n";
+        assert_eq!(find(&buffer, b"\r\n"), Some(14));
+        assert_eq!(find(&buffer, b"\r\n\r\n"), Some(14));
+        assert_eq!(find(&buffer, b"x"), None);
+        assert_eq!(find(&buffer, b""), Some(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn across_chunks() {
+        use bytes::{Buf, Bytes};
+
+        let buffer = (&b"ab\r"[..]).chain(&b"\ncd"[..]);
+        assert_eq!(find(&buffer, b"\r\n"), Some(2));
+        assert_eq!(find(&buffer, b"d"), Some(5));
+
+        // a pattern spanning several tiny chunks
+        let buffer = Bytes::from_static(b"xx\0")
+            .chain(Bytes::from_static(b"\0"))
+            .chain(Bytes::from_static(b""))
+            .chain(Bytes::from_static(b"\x01yy"));
+        assert_eq!(find(&buffer, b"\0\0\x01"), Some(2));
+        assert_eq!(find(&buffer, b"\0\0\x02"), None);
+    }
+}
@@ -0,0 +1,131 @@
+//! Random access to cursor-backed buffers
+
+use crate::{error, FromBuf, SafeBuf};
+use core::convert::TryFrom;
+use std::io::Cursor;
+
+/// Extension trait for buffers which can be repositioned, such as
+/// [`io::Cursor`](std::io::Cursor)
+///
+/// Random-access formats (archives, executables, fonts) are navigated by
+/// following offsets from a header or index. This trait adds checked seeking,
+/// and reads at absolute offsets, so these formats can be parsed with the
+/// same API as a stream.
+///
+/// ```
+/// use safer_bytes::{SafeBuf, SeekBuf};
+/// use std::io::Cursor;
+///
+/// // a header holding the offset of a table
+/// let mut file = Cursor::new(vec![0x00, 0x04, 0xFF, 0xFF, 0x12, 0x34]);
+///
+/// let table = usize::from(file.try_get_u16()?);
+/// file.try_seek(table)?;
+/// assert_eq!(file.try_get_u16()?, 0x1234);
+///
+/// assert!(file.try_seek(7).is_err());
+/// assert_eq!(SeekBuf::position(&file), 6);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+pub trait SeekBuf: SafeBuf {
+    /// The whole underlying data, regardless of the current position
+    fn data(&self) -> &[u8];
+
+    /// The current position, as an offset from the start of the data
+    fn position(&self) -> usize;
+
+    /// Move to an absolute position, with a check to ensure it lies within
+    /// the data
+    ///
+    /// Seeking to the end of the data is permitted. The position is
+    /// unchanged on failure.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `position` is past the end of the
+    /// data
+    fn try_seek(&mut self, position: usize) -> Result<(), error::Truncated>;
+
+    /// Borrow `len` bytes at an absolute position, without moving
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the bytes extend past the end of
+    /// the data
+    fn try_slice_at(&self, position: usize, len: usize) -> Result<&[u8], error::Truncated> {
+        let data = self.data();
+        let end = position.saturating_add(len);
+        error::Truncated::check(end, data.len())?;
+        Ok(&data[position..end])
+    }
+
+    /// Read a custom object at an absolute position, then return to the
+    /// current position
+    ///
+    /// The position is restored whether or not the object can be parsed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `position` is past the end of the
+    /// data, or if the type cannot be parsed from the bytes
+    fn try_extract_at<T>(&mut self, position: usize) -> crate::Result<T>
+    where
+        Self: Sized,
+        T: FromBuf,
+    {
+        let original = SeekBuf::position(self);
+        self.try_seek(position)?;
+        let value = self.extract();
+        self.try_seek(original)?;
+        value
+    }
+}
+
+impl<T> SeekBuf for Cursor<T>
+where
+    T: AsRef<[u8]>,
+{
+    fn data(&self) -> &[u8] {
+        self.get_ref().as_ref()
+    }
+
+    fn position(&self) -> usize {
+        usize::try_from(Cursor::position(self)).unwrap_or(usize::MAX)
+    }
+
+    fn try_seek(&mut self, position: usize) -> Result<(), error::Truncated> {
+        error::Truncated::check(position, self.data().len())?;
+        self.set_position(position as u64);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeekBuf;
+    use crate::{error, MacAddr};
+    use std::io::Cursor;
+
+    #[test]
+    fn random_access() {
+        let mut file = Cursor::new(&[9, 1, 2, 3, 4, 5, 6, 7][..]);
+
+        assert_eq!(file.try_slice_at(6, 2).unwrap(), [6, 7]);
+        assert_eq!(
+            file.try_slice_at(6, 3).unwrap_err(),
+            error::Truncated::new(9, 8)
+        );
+
+        assert_eq!(
+            file.try_extract_at::<MacAddr>(1).unwrap(),
+            MacAddr([1, 2, 3, 4, 5, 6])
+        );
+        assert!(file.try_extract_at::<MacAddr>(3).is_err());
+        assert_eq!(SeekBuf::position(&file), 0);
+
+        file.try_seek(8).unwrap();
+        assert_eq!(bytes::Buf::remaining(&file), 0);
+        assert_eq!(file.try_seek(9).unwrap_err(), error::Truncated::new(9, 8));
+        assert_eq!(SeekBuf::position(&file), 8);
+    }
+}
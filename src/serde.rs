@@ -0,0 +1,186 @@
+//! A compact binary format for [`serde`]
+//!
+//! Values are written without any type information, in the order in which
+//! they are declared:
+//!
+//! - `bool` is a single byte, `0` or `1`
+//! - integers are fixed-width in the configured byte order, or varints (see
+//!   [`IntEncoding`])
+//! - floats are fixed-width, in the configured byte order
+//! - `char` is its scalar value, as a `u32` (or a string, with
+//!   [`IntEncoding::Varint`])
+//! - strings, byte strings, sequences and maps are a length followed by their
+//!   contents
+//! - options are a byte, `0` for `None` or `1` for `Some`, followed by the
+//!   value if present
+//! - structs and tuples are their fields, in order
+//! - enums are the variant index (as a `u32`) followed by the variant's
+//!   contents
+//!
+//! [`Config::postcard`] selects the same encoding as the
+//! [postcard](https://docs.rs/postcard) crate, so messages produced by devices
+//! using postcard can be decoded through this crate.
+//!
+//! Since the format is not self-describing, types which rely on
+//! `deserialize_any` (such as untagged enums) are not supported.
+//!
+//! This module requires the `serde` and `std` features.
+//!
+//! ```
+//! use safer_bytes::serde::{from_buf, to_buf, Config};
+//!
+//! #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+//! struct Header {
+//!     version: u8,
+//!     name: String,
+//!     flags: Option<u16>,
+//! }
+//!
+//! let mut buffer: &[u8] = &[1, 0, 0, 0, 0, 0, 0, 0, 2, b'h', b'i', 1, 0, 7];
+//! let header: Header = from_buf(&mut buffer, Config::new(1024))?;
+//!
+//! assert_eq!(
+//!     header,
+//!     Header {
+//!         version: 1,
+//!         name: "hi".into(),
+//!         flags: Some(7),
+//!     }
+//! );
+//!
+//! let mut encoded = Vec::new();
+//! to_buf(&header, &mut encoded, Config::new(1024))?;
+//! assert_eq!(encoded, [1, 0, 0, 0, 0, 0, 0, 0, 2, b'h', b'i', 1, 0, 7]);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{Endian, Error};
+use alloc::string::ToString;
+use core::fmt;
+
+mod de;
+mod ser;
+
+pub use de::{from_buf, Deserializer};
+pub use ser::{to_buf, Serializer};
+
+/// How the lengths of strings, byte strings, sequences and maps are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthEncoding {
+    /// A `u64`, in the configured byte order
+    Fixed,
+
+    /// A [LEB128](crate::wire::leb128) variable-length integer
+    Varint,
+}
+
+/// How integers wider than a byte (and enum variant indices) are encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntEncoding {
+    /// Fixed-width, in the configured byte order
+    Fixed,
+
+    /// A LEB128 variable-length integer, with signed integers zigzag-encoded
+    /// (so that small negative numbers are short)
+    ///
+    /// For compatibility with postcard, `char` is also encoded as a
+    /// length-prefixed UTF-8 string rather than a `u32`.
+    Varint,
+}
+
+/// The configuration of the wire format
+///
+/// Data must be read with the same configuration it was written with. The
+/// maximum length is mandatory, so that a hostile length prefix can never
+/// cause unbounded allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    max_len: usize,
+    endian: Endian,
+    lengths: LengthEncoding,
+    integers: IntEncoding,
+}
+
+impl Config {
+    /// Construct a new configuration, accepting strings, byte strings,
+    /// sequences and maps of up to `max_len` elements
+    ///
+    /// By default, values are big-endian, and lengths and integers are
+    /// fixed-width.
+    #[must_use]
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            endian: Endian::Big,
+            lengths: LengthEncoding::Fixed,
+            integers: IntEncoding::Fixed,
+        }
+    }
+
+    /// A configuration matching the encoding used by the
+    /// [postcard](https://docs.rs/postcard) crate
+    ///
+    /// Lengths and integers are varints, and floats are little-endian.
+    ///
+    /// ```
+    /// use safer_bytes::serde::{from_buf, Config};
+    ///
+    /// // (300_u16, -1_i32, 'a')
+    /// let mut buffer: &[u8] = &[0xAC, 0x02, 0x01, 0x01, b'a'];
+    /// let value: (u16, i32, char) = from_buf(&mut buffer, Config::postcard(1024))?;
+    /// assert_eq!(value, (300, -1, 'a'));
+    /// # Ok::<(), safer_bytes::Error>(())
+    /// ```
+    #[must_use]
+    pub fn postcard(max_len: usize) -> Self {
+        Self::new(max_len)
+            .endian(Endian::Little)
+            .lengths(LengthEncoding::Varint)
+            .integers(IntEncoding::Varint)
+    }
+
+    /// Set the byte order of integers, floats, and fixed-width lengths
+    #[must_use]
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Set the encoding of lengths
+    #[must_use]
+    pub fn lengths(mut self, lengths: LengthEncoding) -> Self {
+        self.lengths = lengths;
+        self
+    }
+
+    /// Set the encoding of integers
+    #[must_use]
+    pub fn integers(mut self, integers: IntEncoding) -> Self {
+        self.integers = integers;
+        self
+    }
+
+    /// The maximum length accepted by this configuration
+    #[must_use]
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl ::serde::de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::custom(msg.to_string())
+    }
+}
+
+impl ::serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self::custom(msg.to_string())
+    }
+}
@@ -0,0 +1,637 @@
+//! Deserialization
+
+use super::{Config, IntEncoding, LengthEncoding};
+use crate::{error, wire::leb128, Endian, Error, SafeBuf};
+use ::serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use alloc::string::String;
+use bytes::{Buf, Bytes};
+use core::convert::TryFrom;
+use paste::paste;
+
+/// Deserialize a value from the front of a buffer
+///
+/// The buffer is advanced past the value; any bytes which follow it are left
+/// in place.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, if a length
+/// exceeds the configured maximum, or if the value cannot be deserialized
+pub fn from_buf<T, B>(buffer: B, config: Config) -> crate::Result<T>
+where
+    T: DeserializeOwned,
+    B: Buf,
+{
+    T::deserialize(&mut Deserializer::new(buffer, config))
+}
+
+/// A [`serde::Deserializer`](::serde::Deserializer) reading the format
+/// described in the [module documentation](super) from a buffer
+///
+/// Every read is bounds-checked, so malformed input fails with an [`Error`]
+/// rather than panicking.
+#[derive(Debug, Clone)]
+pub struct Deserializer<B> {
+    buffer: B,
+    config: Config,
+}
+
+impl<B> Deserializer<B> {
+    /// Construct a new [`Deserializer`] over a buffer
+    pub fn new(buffer: B, config: Config) -> Self {
+        Self { buffer, config }
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Consume the deserializer, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.buffer
+    }
+}
+
+macro_rules! get_fixed {
+    ($($t:ty),*) => {
+        paste! {
+            $(
+                fn [<get_fixed_ $t>](&mut self) -> crate::Result<$t> {
+                    Ok(match self.config.endian {
+                        Endian::Big => SafeBuf::[<try_get_ $t>](&mut self.buffer)?,
+                        Endian::Little => SafeBuf::[<try_get_ $t _le>](&mut self.buffer)?,
+                    })
+                }
+            )*
+        }
+    };
+}
+
+impl<B> Deserializer<B>
+where
+    B: Buf,
+{
+    get_fixed!(u16, i16, u32, i32, u64, i64, u128, i128);
+}
+
+macro_rules! get_integers {
+    ($($u:ty, $i:ty: $bits:literal),*) => {
+        paste! {
+            $(
+                // the varint is checked to fit in `$bits` bits
+                #[allow(clippy::cast_possible_truncation)]
+                fn [<get_ $u>](&mut self) -> crate::Result<$u> {
+                    match self.config.integers {
+                        IntEncoding::Fixed => self.[<get_fixed_ $u>](),
+                        IntEncoding::Varint => Ok(self.get_varint($bits)? as $u),
+                    }
+                }
+
+                #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                fn [<get_ $i>](&mut self) -> crate::Result<$i> {
+                    match self.config.integers {
+                        IntEncoding::Fixed => self.[<get_fixed_ $i>](),
+                        IntEncoding::Varint => {
+                            let zigzag = self.get_varint($bits)? as $u;
+                            Ok((zigzag >> 1) as $i ^ -((zigzag & 1) as $i))
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl<B> Deserializer<B>
+where
+    B: Buf,
+{
+    get_integers!(u16, i16: 16, u32, i32: 32, u64, i64: 64, u128, i128: 128);
+}
+
+impl<B> Deserializer<B>
+where
+    B: Buf,
+{
+    fn get_len(&mut self) -> crate::Result<usize> {
+        let len = match self.config.lengths {
+            LengthEncoding::Fixed => self.get_fixed_u64()?,
+            LengthEncoding::Varint => leb128::try_get_u64(&mut self.buffer)?,
+        };
+        let len = usize::try_from(len).unwrap_or(usize::MAX);
+        error::LimitExceeded::check("length", len, self.config.max_len)?;
+        Ok(len)
+    }
+
+    /// Read an unsigned LEB128 integer which fits in `bits` bits
+    fn get_varint(&mut self, bits: u32) -> crate::Result<u128> {
+        let max_len = (bits + 6) / 7;
+        let mut value = 0;
+        for i in 0..max_len {
+            let byte = SafeBuf::try_get_u8(&mut self.buffer)?;
+            let payload = byte & 0x7F;
+            if i == max_len - 1 && (byte & 0x80 != 0 || u32::from(payload) >> (bits - 7 * i) != 0) {
+                return Err(Error::invalid("varint", byte));
+            }
+
+            value |= u128::from(payload) << (7 * i);
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    fn get_bytes(&mut self) -> crate::Result<Bytes> {
+        let len = self.get_len()?;
+        Ok(SafeBuf::try_copy_to_bytes(&mut self.buffer, len)?)
+    }
+
+    fn get_string(&mut self) -> crate::Result<String> {
+        let bytes = self.get_bytes()?;
+        match core::str::from_utf8(&bytes) {
+            Ok(string) => Ok(string.into()),
+            Err(_) => Err(Error::invalid("string", bytes)),
+        }
+    }
+
+    fn get_tag(&mut self, what: &'static str) -> crate::Result<bool> {
+        match SafeBuf::try_get_u8(&mut self.buffer)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(Error::invalid(what, tag)),
+        }
+    }
+}
+
+macro_rules! deserialize_primitives {
+    ($($t:ty),*) => {
+        paste! {
+            $(
+                fn [<deserialize_ $t>]<V>(self, visitor: V) -> crate::Result<V::Value>
+                where
+                    V: Visitor<'de>,
+                {
+                    visitor.[<visit_ $t>](self.[<get_ $t>]()?)
+                }
+            )*
+        }
+    };
+}
+
+impl<'de, B> ::serde::Deserializer<'de> for &mut Deserializer<B>
+where
+    B: Buf,
+{
+    type Error = Error;
+
+    deserialize_primitives!(u16, i16, u32, i32, u64, i64, u128, i128);
+
+    fn deserialize_any<V>(self, _visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Deserialization(
+            "the format is not self-describing, so the type must be known",
+        ))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bool(self.get_tag("bool")?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u8(SafeBuf::try_get_u8(&mut self.buffer)?)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i8(SafeBuf::try_get_i8(&mut self.buffer)?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f32(f32::from_bits(self.get_fixed_u32()?))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_f64(f64::from_bits(self.get_fixed_u64()?))
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let c = match self.config.integers {
+            IntEncoding::Fixed => {
+                let value = self.get_fixed_u32()?;
+                core::char::from_u32(value).ok_or_else(|| Error::invalid("char", value))?
+            }
+            IntEncoding::Varint => {
+                let string = self.get_string()?;
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => c,
+                    _ => return Err(Error::invalid("char", string.into_bytes())),
+                }
+            }
+        };
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.get_string()?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_byte_buf(self.get_bytes()?.to_vec())
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.get_tag("option tag")? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let len = self.get_len()?;
+        visitor.visit_seq(Access {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(Access {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let len = self.get_len()?;
+        visitor.visit_map(Access {
+            deserializer: self,
+            len,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Deserialization(
+            "the format does not encode identifiers",
+        ))
+    }
+
+    fn deserialize_ignored_any<V>(self, _visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Deserialization(
+            "the format is not self-describing, so values cannot be skipped",
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Access to the elements of a sequence, tuple, or map
+struct Access<'a, B> {
+    deserializer: &'a mut Deserializer<B>,
+    len: usize,
+}
+
+impl<'de, B> SeqAccess<'de> for Access<'_, B>
+where
+    B: Buf,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> crate::Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, B> MapAccess<'de> for Access<'_, B>
+where
+    B: Buf,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> crate::Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> crate::Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, B> EnumAccess<'de> for &mut Deserializer<B>
+where
+    B: Buf,
+{
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> crate::Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let index = self.get_u32()?;
+        let variant =
+            seed.deserialize::<de::value::U32Deserializer<Error>>(index.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, B> VariantAccess<'de> for &mut Deserializer<B>
+where
+    B: Buf,
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> crate::Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        ::serde::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> crate::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        ::serde::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_buf;
+    use crate::{
+        serde::{Config, LengthEncoding},
+        Endian, Error,
+    };
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Message {
+        Ping,
+        Data(Vec<u8>),
+        Move { x: i16, y: i16 },
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Envelope {
+        id: u32,
+        flag: bool,
+        ratio: f32,
+        tag: char,
+        messages: Vec<Message>,
+        labels: BTreeMap<String, (u8, Option<u64>)>,
+    }
+
+    #[test]
+    fn round_trip_fixed() {
+        let mut buffer: &[u8] = &[
+            0, 0, 0, 7, // id
+            1, // flag
+            0x3F, 0x80, 0, 0, // ratio
+            0, 0, 0, b'x', // tag
+            0, 0, 0, 0, 0, 0, 0, 3, // messages
+            0, 0, 0, 0, // Ping
+            0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 2, 0xAB, 0xCD, // Data
+            0, 0, 0, 2, 0xFF, 0xFF, 0, 1, // Move
+            0, 0, 0, 0, 0, 0, 0, 1, // labels
+            0, 0, 0, 0, 0, 0, 0, 1, b'a', 9, 0,    // "a" => (9, None)
+            0xEE, // trailing
+        ];
+
+        let envelope: Envelope = from_buf(&mut buffer, Config::new(16)).unwrap();
+        assert_eq!(
+            envelope,
+            Envelope {
+                id: 7,
+                flag: true,
+                ratio: 1.0,
+                tag: 'x',
+                messages: vec![
+                    Message::Ping,
+                    Message::Data(vec![0xAB, 0xCD]),
+                    Message::Move { x: -1, y: 1 }
+                ],
+                labels: vec![("a".to_string(), (9, None))].into_iter().collect(),
+            }
+        );
+        assert_eq!(buffer, &[0xEE]);
+    }
+
+    #[test]
+    fn varint_little_endian() {
+        let config = Config::new(16)
+            .endian(Endian::Little)
+            .lengths(LengthEncoding::Varint);
+        let mut buffer: &[u8] = &[2, 1, 0, 2, 0];
+
+        let value: Vec<u16> = from_buf(&mut buffer, config).unwrap();
+        assert_eq!(value, vec![1, 2]);
+    }
+
+    #[test]
+    fn postcard() {
+        let config = Config::postcard(16);
+
+        let mut buffer: &[u8] = &[0xAC, 0x02, 0x03, 0x02, 0xC3, 0xA9, 0x02, 0x01, 0x02];
+        let value: (u16, i8, char, Message) = from_buf(&mut buffer, config).unwrap();
+        assert_eq!(value, (300, 3, 'é', Message::Move { x: -1, y: 1 }));
+
+        // too long for a u16
+        let error = from_buf::<u16, _>(&[0xFF, 0xFF, 0x04][..], config).unwrap_err();
+        assert_eq!(error, Error::invalid("varint", 0x04_u8));
+        assert_eq!(
+            from_buf::<u16, _>(&[0xFF, 0xFF, 0x03][..], config).unwrap(),
+            u16::MAX
+        );
+
+        let error = from_buf::<char, _>(&[0x02, b'a', b'b'][..], config).unwrap_err();
+        assert_eq!(error, Error::invalid("char", &b"ab"[..]));
+    }
+
+    #[test]
+    fn errors() {
+        let config = Config::new(4);
+
+        let error = from_buf::<Vec<u8>, _>(&[0, 0, 0, 0, 0, 0, 0, 5][..], config).unwrap_err();
+        assert!(matches!(error, Error::LimitExceeded(_)));
+
+        let error = from_buf::<bool, _>(&[2][..], config).unwrap_err();
+        assert_eq!(error, Error::invalid("bool", 2_u8));
+
+        let error = from_buf::<String, _>(&[0, 0, 0, 0, 0, 0, 0, 1, 0xFF][..], config).unwrap_err();
+        assert_eq!(error, Error::invalid("string", [0xFF]));
+
+        let error = from_buf::<u32, _>(&[0, 0][..], config).unwrap_err();
+        assert!(matches!(error, Error::Truncated(_)));
+
+        let error = from_buf::<Message, _>(&[0, 0, 0, 3][..], config).unwrap_err();
+        assert!(matches!(error, Error::Custom(_)));
+    }
+}
@@ -0,0 +1,529 @@
+//! Serialization
+
+use super::{Config, IntEncoding, LengthEncoding};
+use crate::{error, Endian, Error};
+use ::serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use bytes::BufMut;
+use paste::paste;
+
+/// Serialize a value into a buffer
+///
+/// # Errors
+///
+/// This method will return an error if the buffer does not have enough space
+/// remaining, if a length exceeds the configured maximum, or if the value
+/// cannot be serialized. In this case, part of the value may already have
+/// been written.
+pub fn to_buf<T, B>(value: &T, buffer: B, config: Config) -> crate::Result<()>
+where
+    T: Serialize + ?Sized,
+    B: BufMut,
+{
+    value.serialize(&mut Serializer::new(buffer, config))
+}
+
+/// A [`serde::Serializer`](::serde::Serializer) writing the format described in
+/// the [module documentation](super) into a buffer
+///
+/// Lengths are checked against the same maximum as the
+/// [`Deserializer`](super::Deserializer), so anything written with a given
+/// [`Config`] can be read back with it.
+#[derive(Debug, Clone)]
+pub struct Serializer<B> {
+    buffer: B,
+    config: Config,
+}
+
+impl<B> Serializer<B> {
+    /// Construct a new [`Serializer`] writing into a buffer
+    pub fn new(buffer: B, config: Config) -> Self {
+        Self { buffer, config }
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.buffer
+    }
+
+    /// Consume the serializer, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.buffer
+    }
+}
+
+macro_rules! put_fixed {
+    ($($t:ty),*) => {
+        paste! {
+            $(
+                fn [<put_fixed_ $t>](&mut self, value: $t) -> crate::Result<()> {
+                    match self.config.endian {
+                        Endian::Big => self.put(&value.to_be_bytes()),
+                        Endian::Little => self.put(&value.to_le_bytes()),
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl<B> Serializer<B>
+where
+    B: BufMut,
+{
+    put_fixed!(u16, i16, u32, i32, u64, i64, u128, i128);
+}
+
+macro_rules! put_integers {
+    ($($u:ty, $i:ty: $bits:literal),*) => {
+        paste! {
+            $(
+                fn [<put_ $u>](&mut self, value: $u) -> crate::Result<()> {
+                    match self.config.integers {
+                        IntEncoding::Fixed => self.[<put_fixed_ $u>](value),
+                        IntEncoding::Varint => self.put_varint(value.into()),
+                    }
+                }
+
+                #[allow(clippy::cast_sign_loss)]
+                fn [<put_ $i>](&mut self, value: $i) -> crate::Result<()> {
+                    match self.config.integers {
+                        IntEncoding::Fixed => self.[<put_fixed_ $i>](value),
+                        IntEncoding::Varint => {
+                            let zigzag = ((value << 1) ^ (value >> ($bits - 1))) as $u;
+                            self.put_varint(zigzag.into())
+                        }
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl<B> Serializer<B>
+where
+    B: BufMut,
+{
+    put_integers!(u16, i16: 16, u32, i32: 32, u64, i64: 64, u128, i128: 128);
+}
+
+impl<B> Serializer<B>
+where
+    B: BufMut,
+{
+    fn put(&mut self, bytes: &[u8]) -> crate::Result<()> {
+        error::Truncated::check(bytes.len(), self.buffer.remaining_mut())?;
+        self.buffer.put_slice(bytes);
+        Ok(())
+    }
+
+    fn put_len(&mut self, len: Option<usize>) -> crate::Result<()> {
+        let len = len.ok_or_else(|| {
+            Error::custom("the format requires lengths to be known before serializing")
+        })?;
+        error::LimitExceeded::check("length", len, self.config.max_len)?;
+
+        match self.config.lengths {
+            LengthEncoding::Fixed => self.put_fixed_u64(len as u64),
+            LengthEncoding::Varint => self.put_varint(len as u128),
+        }
+    }
+
+    /// Write an unsigned LEB128 integer
+    fn put_varint(&mut self, mut value: u128) -> crate::Result<()> {
+        let mut bytes = [0; 19];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                bytes[len] = byte;
+                return self.put(&bytes[..=len]);
+            }
+            bytes[len] = byte | 0x80;
+            len += 1;
+        }
+    }
+}
+
+macro_rules! serialize_primitives {
+    ($($t:ty),*) => {
+        paste! {
+            $(
+                fn [<serialize_ $t>](self, value: $t) -> crate::Result<()> {
+                    self.[<put_ $t>](value)
+                }
+            )*
+        }
+    };
+}
+
+impl<B> ::serde::Serializer for &mut Serializer<B>
+where
+    B: BufMut,
+{
+    type Error = Error;
+    type Ok = ();
+    type SerializeMap = Self;
+    type SerializeSeq = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+
+    serialize_primitives!(u16, i16, u32, i32, u64, i64, u128, i128);
+
+    fn serialize_bool(self, value: bool) -> crate::Result<()> {
+        self.put(&[u8::from(value)])
+    }
+
+    fn serialize_u8(self, value: u8) -> crate::Result<()> {
+        self.put(&[value])
+    }
+
+    fn serialize_i8(self, value: i8) -> crate::Result<()> {
+        self.put(&value.to_be_bytes())
+    }
+
+    fn serialize_f32(self, value: f32) -> crate::Result<()> {
+        self.put_fixed_u32(value.to_bits())
+    }
+
+    fn serialize_f64(self, value: f64) -> crate::Result<()> {
+        self.put_fixed_u64(value.to_bits())
+    }
+
+    fn serialize_char(self, value: char) -> crate::Result<()> {
+        match self.config.integers {
+            IntEncoding::Fixed => self.put_fixed_u32(value.into()),
+            IntEncoding::Varint => self.serialize_str(value.encode_utf8(&mut [0; 4])),
+        }
+    }
+
+    fn serialize_str(self, value: &str) -> crate::Result<()> {
+        self.serialize_bytes(value.as_bytes())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> crate::Result<()> {
+        self.put_len(Some(value.len()))?;
+        self.put(value)
+    }
+
+    fn serialize_none(self) -> crate::Result<()> {
+        self.put(&[0])
+    }
+
+    fn serialize_some<T>(self, value: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.put(&[1])?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> crate::Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> crate::Result<()> {
+        self.put_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.put_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> crate::Result<Self> {
+        self.put_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> crate::Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> crate::Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self> {
+        self.put_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> crate::Result<Self> {
+        self.put_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> crate::Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> crate::Result<Self> {
+        self.put_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Implement a compound serializer whose elements are written one after
+/// another, with nothing to finish
+macro_rules! serialize_elements {
+    ($($trait:ident::$method:ident),*) => {
+        $(
+            impl<B> $trait for &mut Serializer<B>
+            where
+                B: BufMut,
+            {
+                type Error = Error;
+                type Ok = ();
+
+                fn $method<T>(&mut self, value: &T) -> crate::Result<()>
+                where
+                    T: Serialize + ?Sized,
+                {
+                    value.serialize(&mut **self)
+                }
+
+                fn end(self) -> crate::Result<()> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+serialize_elements!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl<B> SerializeMap for &mut Serializer<B>
+where
+    B: BufMut,
+{
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_key<T>(&mut self, key: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+impl<B> SerializeStruct for &mut Serializer<B>
+where
+    B: BufMut,
+{
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+impl<B> SerializeStructVariant for &mut Serializer<B>
+where
+    B: BufMut,
+{
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> crate::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_buf;
+    use crate::{
+        serde::{from_buf, Config, IntEncoding, LengthEncoding},
+        Endian, Error,
+    };
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Message {
+        Ping,
+        Data(Vec<u8>),
+        Move { x: i16, y: i16 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Envelope {
+        id: u32,
+        name: String,
+        ratio: f64,
+        messages: Vec<Message>,
+        labels: BTreeMap<String, (char, Option<u64>)>,
+    }
+
+    fn envelope() -> Envelope {
+        Envelope {
+            id: 7,
+            name: "envelope".into(),
+            ratio: -0.5,
+            messages: vec![
+                Message::Ping,
+                Message::Data(vec![0xAB, 0xCD]),
+                Message::Move { x: -1, y: 1 },
+            ],
+            labels: vec![("a".to_string(), ('z', Some(300)))]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        let mut buffer = Vec::new();
+        to_buf(&Message::Move { x: -1, y: 1 }, &mut buffer, Config::new(16)).unwrap();
+        assert_eq!(buffer, [0, 0, 0, 2, 0xFF, 0xFF, 0, 1]);
+
+        let config = Config::new(16)
+            .endian(Endian::Little)
+            .lengths(LengthEncoding::Varint);
+        let mut buffer = Vec::new();
+        to_buf(&vec![1_u16, 2], &mut buffer, config).unwrap();
+        assert_eq!(buffer, [2, 1, 0, 2, 0]);
+    }
+
+    #[test]
+    fn round_trip() {
+        for &endian in &[Endian::Big, Endian::Little] {
+            for &lengths in &[LengthEncoding::Fixed, LengthEncoding::Varint] {
+                for &integers in &[IntEncoding::Fixed, IntEncoding::Varint] {
+                    let config = Config::new(16)
+                        .endian(endian)
+                        .lengths(lengths)
+                        .integers(integers);
+
+                    let mut buffer = Vec::new();
+                    to_buf(&envelope(), &mut buffer, config).unwrap();
+
+                    let mut encoded = &buffer[..];
+                    assert_eq!(
+                        from_buf::<Envelope, _>(&mut encoded, config).unwrap(),
+                        envelope()
+                    );
+                    assert!(encoded.is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn postcard() {
+        // encodings taken from the postcard wire format specification
+        fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+            let mut buffer = Vec::new();
+            to_buf(value, &mut buffer, Config::postcard(16)).unwrap();
+            buffer
+        }
+
+        assert_eq!(encode(&300_u16), [0xAC, 0x02]);
+        assert_eq!(encode(&-1_i32), [0x01]);
+        assert_eq!(
+            encode(&i64::MIN),
+            [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+        );
+        assert_eq!(encode(&1.0_f32), [0x00, 0x00, 0x80, 0x3F]);
+        assert_eq!(encode(&'é'), [0x02, 0xC3, 0xA9]);
+        assert_eq!(encode(&Message::Move { x: -1, y: 1 }), [0x02, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut storage = [0_u8; 3];
+        let error = to_buf(&1_u32, &mut storage[..], Config::new(16)).unwrap_err();
+        assert!(matches!(error, Error::Truncated(_)));
+
+        let mut buffer = Vec::new();
+        let error = to_buf("too long", &mut buffer, Config::new(4)).unwrap_err();
+        assert!(matches!(error, Error::LimitExceeded(_)));
+
+        // a sequence whose length is not known up front
+        let mut serializer = super::Serializer::new(Vec::new(), Config::new(16));
+        let error = (&mut serializer)
+            .collect_seq((0_u8..4).filter(|i| i % 2 == 0))
+            .unwrap_err();
+        assert!(matches!(error, Error::Custom(_)));
+    }
+}
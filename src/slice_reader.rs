@@ -0,0 +1,236 @@
+//! A checked reader specialised for contiguous byte slices
+
+use crate::error;
+use bytes::Buf;
+use paste::paste;
+
+macro_rules! get_primitives {
+    ($($t:ty: $width:literal,)*) => {
+        $(paste! {
+            #[doc = "Read a big-endian `" $t "` from the front of the slice, with a check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the slice is insufficent"]
+            #[inline]
+            pub fn [<try_get_ $t>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                self.try_take_const::<$width>().map($t::from_be_bytes)
+            }
+
+            #[doc = "Read a little-endian `" $t "` from the front of the slice, with a check to ensure there are enough bytes remaining, without panicking."]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the slice is insufficent"]
+            #[inline]
+            pub fn [<try_get_ $t _le>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                self.try_take_const::<$width>().map($t::from_le_bytes)
+            }
+        })*
+    };
+}
+
+/// A reader over a contiguous byte slice
+///
+/// A `&[u8]` already implements [`Buf`] (and so [`SafeBuf`](crate::SafeBuf)),
+/// but the generic methods have to allow for the buffer being split across
+/// several chunks. A [`SliceReader`] knows that its input is contiguous, so its
+/// checked getters compile down to a length comparison and a direct load.
+/// Bytes taken from the reader are borrowed from the input rather than copied.
+///
+/// A [`SliceReader`] also implements [`Buf`], so the rest of the
+/// [`SafeBuf`](crate::SafeBuf) API remains available.
+///
+/// ```
+/// use safer_bytes::SliceReader;
+///
+/// let mut reader = SliceReader::new(&[0x00, 0x2A, 3, b'a', b'b', b'c']);
+///
+/// assert_eq!(reader.try_get_u16()?, 42);
+/// let len = reader.try_get_u8()?;
+/// assert_eq!(reader.try_take(len.into())?, b"abc");
+/// assert_eq!(reader.position(), 6);
+/// # Ok::<(), safer_bytes::error::Truncated>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SliceReader<'a> {
+    data: &'a [u8],
+    rest: &'a [u8],
+}
+
+impl<'a> SliceReader<'a> {
+    /// Construct a new [`SliceReader`] over a slice
+    #[must_use]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, rest: data }
+    }
+
+    /// The number of bytes read so far
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.data.len() - self.rest.len()
+    }
+
+    /// The number of bytes left to read
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rest.len()
+    }
+
+    /// Whether there are any bytes left to read
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    /// The bytes left to read
+    #[must_use]
+    pub fn as_slice(&self) -> &'a [u8] {
+        self.rest
+    }
+
+    /// Take a given number of bytes from the front of the slice, with a check
+    /// to ensure there are enough remaining
+    ///
+    /// The bytes are borrowed from the input, not copied.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// slice is insufficent
+    #[inline]
+    pub fn try_take(&mut self, len: usize) -> core::result::Result<&'a [u8], error::Truncated> {
+        error::Truncated::check(len, self.rest.len())?;
+        let (head, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(head)
+    }
+
+    /// Borrow a given number of bytes from the front of the slice, with a check
+    /// to ensure there are enough remaining, without advancing the reader
+    ///
+    /// Unlike [`SafeBuf::try_peek`](crate::SafeBuf::try_peek), this never
+    /// copies or allocates.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// slice is insufficent
+    #[inline]
+    pub fn try_peek(&self, len: usize) -> core::result::Result<&'a [u8], error::Truncated> {
+        error::Truncated::check(len, self.rest.len())?;
+        Ok(&self.rest[..len])
+    }
+
+    /// Take a fixed number of bytes from the front of the slice into an array,
+    /// with a check to ensure there are enough remaining
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// slice is insufficent
+    #[inline]
+    pub fn try_take_const<const N: usize>(
+        &mut self,
+    ) -> core::result::Result<[u8; N], error::Truncated> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.try_take(N)?);
+        Ok(array)
+    }
+
+    /// Read a `u8` from the front of the slice, with a check to ensure there is
+    /// a byte remaining, without panicking.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the slice is empty
+    #[inline]
+    pub fn try_get_u8(&mut self) -> core::result::Result<u8, error::Truncated> {
+        self.try_take_const::<1>().map(|[byte]| byte)
+    }
+
+    /// Read an `i8` from the front of the slice, with a check to ensure there
+    /// is a byte remaining, without panicking.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the slice is empty
+    #[inline]
+    pub fn try_get_i8(&mut self) -> core::result::Result<i8, error::Truncated> {
+        self.try_take_const::<1>().map(i8::from_be_bytes)
+    }
+}
+
+impl SliceReader<'_> {
+    get_primitives! {
+        u16: 2,
+        i16: 2,
+        u32: 4,
+        i32: 4,
+        u64: 8,
+        i64: 8,
+        u128: 16,
+        i128: 16,
+    }
+}
+
+impl<'a> From<&'a [u8]> for SliceReader<'a> {
+    fn from(data: &'a [u8]) -> Self {
+        Self::new(data)
+    }
+}
+
+impl Buf for SliceReader<'_> {
+    fn remaining(&self) -> usize {
+        self.rest.len()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.rest
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.rest = &self.rest[cnt..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SliceReader;
+    use crate::{error, SafeBuf};
+
+    #[test]
+    fn getters() {
+        let data = [0_u8, 1, 1, 0, 0xFF, 0, 0, 0, 2];
+        let mut reader = SliceReader::new(&data);
+
+        assert_eq!(reader.try_get_u16().unwrap(), 1);
+        assert_eq!(reader.try_get_u16_le().unwrap(), 1);
+        assert_eq!(reader.try_get_i8().unwrap(), -1);
+        assert_eq!(
+            reader.try_get_u64().unwrap_err(),
+            error::Truncated::new(8, 4)
+        );
+        assert_eq!(reader.try_get_u32().unwrap(), 2);
+        assert!(reader.is_empty());
+        assert_eq!(reader.position(), data.len());
+    }
+
+    #[test]
+    fn try_take_borrows() {
+        let data = [1_u8, 2, 3];
+        let mut reader = SliceReader::from(&data[..]);
+
+        let taken = reader.try_take(2).unwrap();
+        assert!(std::ptr::eq(taken, &data[..2]));
+        assert!(reader.try_take(2).is_err());
+        assert_eq!(reader.as_slice(), &[3]);
+
+        assert!(std::ptr::eq(reader.try_peek(1).unwrap(), &data[2..]));
+        assert!(reader.try_peek(2).is_err());
+        assert_eq!(reader.position(), 2);
+
+        // the generic API is still available
+        assert_eq!(SafeBuf::try_peek_const::<1>(&reader).unwrap(), [3]);
+    }
+}
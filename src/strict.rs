@@ -0,0 +1,420 @@
+//! A wrapper around a [`bytes::Buf`] which only exposes the fallible API
+
+use crate::{error, FromBuf, SafeBuf};
+use bytes::{Buf, Bytes};
+use paste::paste;
+
+macro_rules! delegate_getters {
+    ($($t:ty),*) => {
+        $(paste! {
+            #[doc = "See [`SafeBuf::try_get_" $t "`]"]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            #[inline]
+            pub fn [<try_get_ $t>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                SafeBuf::[<try_get_ $t>](&mut self.inner)
+            }
+
+            #[doc = "See [`SafeBuf::try_get_" $t "_le`]"]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the number of bytes remaining in the buffer is insufficent"]
+            #[inline]
+            pub fn [<try_get_ $t _le>](&mut self) -> core::result::Result<$t, error::Truncated> {
+                SafeBuf::[<try_get_ $t _le>](&mut self.inner)
+            }
+        })*
+    };
+}
+
+/// A wrapper around a [`Buf`] which only exposes the fallible API
+///
+/// Since [`SafeBuf`] is implemented for every [`Buf`], the panicking [`Buf`]
+/// methods are always within reach of a typo. A [`Strict`] does not implement
+/// [`Buf`], and only provides the checked methods, so a codebase which parses
+/// untrusted input through a [`Strict`] cannot panic on a short buffer.
+///
+/// Note that [`Strict::extract`] hands the underlying buffer to the
+/// [`FromBuf`] implementation, which is responsible for its own reads.
+///
+/// ```
+/// use safer_bytes::Strict;
+///
+/// let mut buffer = Strict::new(&[0x00, 0x2A, 0xFF][..]);
+///
+/// assert_eq!(buffer.try_get_u16()?, 42);
+/// assert!(buffer.try_get_u16().is_err());
+/// assert_eq!(buffer.remaining(), 1);
+/// # Ok::<(), safer_bytes::error::Truncated>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Strict<B> {
+    inner: B,
+}
+
+impl<B> Strict<B> {
+    /// Wrap a buffer
+    pub fn new(inner: B) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> Strict<B>
+where
+    B: Buf,
+{
+    /// The number of bytes remaining in the buffer
+    pub fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    /// Whether there are any bytes remaining in the buffer
+    pub fn has_remaining(&self) -> bool {
+        self.inner.has_remaining()
+    }
+
+    /// The bytes at the front of the buffer which are contiguous in memory
+    ///
+    /// See [`Buf::chunk`]
+    pub fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    /// See [`SafeBuf::try_advance`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    pub fn try_advance(&mut self, cnt: usize) -> core::result::Result<(), error::Truncated> {
+        self.inner.try_advance(cnt)
+    }
+
+    /// See [`SafeBuf::try_copy_to_bytes`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    pub fn try_copy_to_bytes(
+        &mut self,
+        len: usize,
+    ) -> core::result::Result<Bytes, error::Truncated> {
+        self.inner.try_copy_to_bytes(len)
+    }
+
+    /// See [`SafeBuf::try_copy_to_bytes_limited`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `len` exceeds `max_alloc`, or if the
+    /// number of bytes remaining in the buffer is insufficent
+    pub fn try_copy_to_bytes_limited(
+        &mut self,
+        len: usize,
+        max_alloc: usize,
+    ) -> crate::Result<Bytes> {
+        self.inner.try_copy_to_bytes_limited(len, max_alloc)
+    }
+
+    /// See [`SafeBuf::try_copy_to_slice`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    pub fn try_copy_to_slice(
+        &mut self,
+        dst: &mut [u8],
+    ) -> core::result::Result<(), error::Truncated> {
+        SafeBuf::try_copy_to_slice(&mut self.inner, dst)
+    }
+
+    /// See [`SafeBuf::try_take_into`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if `len` is larger than `dst`, or if
+    /// the number of bytes remaining in the buffer is insufficent
+    pub fn try_take_into<'a>(&mut self, dst: &'a mut [u8], len: usize) -> crate::Result<&'a [u8]> {
+        self.inner.try_take_into(dst, len)
+    }
+
+    /// See [`SafeBuf::try_take_const`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    pub fn try_take_const<const N: usize>(
+        &mut self,
+    ) -> core::result::Result<[u8; N], error::Truncated> {
+        self.inner.try_take_const()
+    }
+
+    /// See [`SafeBuf::try_take_rest`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if more than `max` bytes remain in
+    /// the buffer
+    pub fn try_take_rest(
+        &mut self,
+        max: usize,
+    ) -> core::result::Result<Bytes, error::LimitExceeded> {
+        self.inner.try_take_rest(max)
+    }
+
+    /// See [`SafeBuf::try_peek`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    pub fn try_peek(&self, len: usize) -> core::result::Result<Bytes, error::Truncated> {
+        self.inner.try_peek(len)
+    }
+
+    /// See [`SafeBuf::try_peek_const`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the bytes cannot be inspected without
+    /// advancing the buffer
+    pub fn try_peek_const<const N: usize>(
+        &self,
+    ) -> core::result::Result<[u8; N], error::Truncated> {
+        self.inner.try_peek_const()
+    }
+
+    /// See [`SafeBuf::extract`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the type cannot be parsed from the bytes.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.inner.extract()
+    }
+
+    /// See [`SafeBuf::extract_strict`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the type cannot be parsed from the bytes, or
+    /// if there are bytes left over.
+    pub fn extract_strict<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.inner.extract_strict()
+    }
+
+    /// See [`SafeBuf::try_extract`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the type cannot be parsed from the bytes.
+    pub fn try_extract<T>(&mut self) -> crate::Result<T>
+    where
+        B: Clone,
+        T: FromBuf,
+    {
+        self.inner.try_extract()
+    }
+
+    /// See [`SafeBuf::should_be_exhausted`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if there are bytes remaining in the
+    /// buffer
+    pub fn should_be_exhausted(&self) -> core::result::Result<(), error::ExtraneousBytes> {
+        self.inner.should_be_exhausted()
+    }
+
+    /// See [`SafeBuf::try_expect_bytes`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the bytes cannot be inspected without
+    /// advancing the buffer, or if the bytes do not match.
+    pub fn try_expect_bytes(&mut self, expected: &[u8]) -> crate::Result<()> {
+        self.inner.try_expect_bytes(expected)
+    }
+
+    /// See [`SafeBuf::try_strip_prefix`]
+    ///
+    /// # Errors
+    ///
+    /// If `required` is `true`, this method will return an error if the number
+    /// of bytes remaining in the buffer is insufficent, if the bytes cannot be
+    /// inspected without advancing the buffer, or if the bytes do not match.
+    pub fn try_strip_prefix(&mut self, prefix: &[u8], required: bool) -> crate::Result<bool> {
+        self.inner.try_strip_prefix(prefix, required)
+    }
+
+    /// See [`SafeBuf::try_expect_bytes_ct`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, if the bytes cannot be inspected without
+    /// advancing the buffer, or if the bytes do not match.
+    pub fn try_expect_bytes_ct(&mut self, expected: &[u8]) -> crate::Result<()> {
+        self.inner.try_expect_bytes_ct(expected)
+    }
+
+    /// See [`SafeBuf::try_get_primitive`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    #[cfg(feature = "num-traits")]
+    pub fn try_get_primitive<T>(&mut self) -> core::result::Result<T, error::Truncated>
+    where
+        T: num_traits::FromBytes,
+        T::Bytes: Default,
+    {
+        self.inner.try_get_primitive()
+    }
+
+    /// See [`SafeBuf::try_get_primitive_le`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    #[cfg(feature = "num-traits")]
+    pub fn try_get_primitive_le<T>(&mut self) -> core::result::Result<T, error::Truncated>
+    where
+        T: num_traits::FromBytes,
+        T::Bytes: Default,
+    {
+        self.inner.try_get_primitive_le()
+    }
+
+    /// See [`SafeBuf::extract_flags`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the field contains unknown bits and
+    /// `unknown` is [`UnknownBits::Reject`](crate::flags::UnknownBits::Reject)
+    #[cfg(feature = "bitflags")]
+    pub fn extract_flags<F>(&mut self, unknown: crate::flags::UnknownBits) -> crate::Result<F>
+    where
+        F: crate::flags::Flags,
+    {
+        self.inner.extract_flags(unknown)
+    }
+
+    /// See [`SafeBuf::extract_flags_le`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent, or if the field contains unknown bits and
+    /// `unknown` is [`UnknownBits::Reject`](crate::flags::UnknownBits::Reject)
+    #[cfg(feature = "bitflags")]
+    pub fn extract_flags_le<F>(&mut self, unknown: crate::flags::UnknownBits) -> crate::Result<F>
+    where
+        F: crate::flags::Flags,
+    {
+        self.inner.extract_flags_le(unknown)
+    }
+
+    /// See [`SafeBuf::try_get_u8`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer is empty
+    #[inline]
+    pub fn try_get_u8(&mut self) -> core::result::Result<u8, error::Truncated> {
+        SafeBuf::try_get_u8(&mut self.inner)
+    }
+
+    /// See [`SafeBuf::try_get_i8`]
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the buffer is empty
+    #[inline]
+    pub fn try_get_i8(&mut self) -> core::result::Result<i8, error::Truncated> {
+        SafeBuf::try_get_i8(&mut self.inner)
+    }
+}
+
+impl<B> Strict<B>
+where
+    B: Buf,
+{
+    delegate_getters!(u16, i16, u32, i32, u64, i64, u128, i128);
+}
+
+impl<B> From<B> for Strict<B>
+where
+    B: Buf,
+{
+    fn from(inner: B) -> Self {
+        Self::new(inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Strict;
+    use crate::FromBuf;
+
+    #[derive(Debug, PartialEq)]
+    struct Pair(u8, u8);
+
+    impl FromBuf for Pair {
+        fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+        where
+            B: bytes::Buf,
+        {
+            Ok(Self(
+                crate::SafeBuf::try_get_u8(&mut buffer)?,
+                crate::SafeBuf::try_get_u8(&mut buffer)?,
+            ))
+        }
+    }
+
+    #[test]
+    fn fallible_api() {
+        let mut buffer = Strict::new(&[0_u8, 1, 2, 3, 4, 5, 6][..]);
+
+        assert_eq!(buffer.try_get_u16_le().unwrap(), 0x0100);
+        assert_eq!(buffer.extract::<Pair>().unwrap(), Pair(2, 3));
+        assert!(buffer.try_extract::<Pair>().is_ok());
+        assert!(buffer.try_extract::<Pair>().is_err());
+        assert_eq!(buffer.remaining(), 1);
+        assert!(buffer.try_advance(2).is_err());
+        assert!(buffer.should_be_exhausted().is_err());
+        assert_eq!(buffer.try_take_const::<1>().unwrap(), [6]);
+        assert!(!buffer.has_remaining());
+    }
+}
@@ -0,0 +1,174 @@
+//! A bounded view over the next bytes of a [`bytes::Buf`]
+
+use crate::error;
+use bytes::{Buf, Bytes};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// A bounded view over the next bytes of a buffer
+///
+/// This is created by [`SafeBuf::try_take_buf`](crate::SafeBuf::try_take_buf).
+/// Reads from the view are served directly from the parent buffer without
+/// copying, and can never read past the end of the region. When the view is
+/// dropped (or [`finish`](SubBuf::finish)ed), the parent is advanced past any
+/// unread bytes in the region, so the parent always ends up positioned
+/// directly after it.
+#[derive(Debug)]
+pub struct SubBuf<'a, B>
+where
+    B: Buf + ?Sized,
+{
+    parent: &'a mut B,
+    limit: usize,
+}
+
+impl<'a, B> SubBuf<'a, B>
+where
+    B: Buf + ?Sized,
+{
+    pub(crate) fn new(parent: &'a mut B, limit: usize) -> Self {
+        debug_assert!(parent.remaining() >= limit);
+        Self { parent, limit }
+    }
+
+    /// Consume the view, checking that the region was fully read
+    ///
+    /// The parent is advanced past the end of the region regardless of the
+    /// outcome.
+    ///
+    /// # Errors
+    ///
+    /// This method will return [`error::ExtraneousBytes`] if there are bytes
+    /// left in the region.
+    pub fn finish(self) -> core::result::Result<(), error::ExtraneousBytes> {
+        if self.limit == 0 {
+            Ok(())
+        } else {
+            Err(error::ExtraneousBytes::from_buf(&self))
+        }
+    }
+}
+
+impl<B> Buf for SubBuf<'_, B>
+where
+    B: Buf + ?Sized,
+{
+    fn remaining(&self) -> usize {
+        self.limit
+    }
+
+    fn chunk(&self) -> &[u8] {
+        let chunk = self.parent.chunk();
+        &chunk[..chunk.len().min(self.limit)]
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        chunks_vectored_limited(&*self.parent, self.limit, dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.limit,
+            "cannot advance past the end of the region"
+        );
+        self.parent.advance(cnt);
+        self.limit -= cnt;
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        assert!(
+            len <= self.limit,
+            "cannot advance past the end of the region"
+        );
+        self.limit -= len;
+        self.parent.copy_to_bytes(len)
+    }
+}
+
+impl<B> Drop for SubBuf<'_, B>
+where
+    B: Buf + ?Sized,
+{
+    fn drop(&mut self) {
+        self.parent.advance(self.limit);
+    }
+}
+
+/// Fill `dst` with the chunks of `buffer`, clamped to its first `limit` bytes
+#[cfg(feature = "std")]
+pub(crate) fn chunks_vectored_limited<'a, B>(
+    buffer: &'a B,
+    limit: usize,
+    dst: &mut [IoSlice<'a>],
+) -> usize
+where
+    B: Buf + ?Sized,
+{
+    if limit == 0 {
+        return 0;
+    }
+
+    let n = buffer.chunks_vectored(dst);
+    let mut left = limit;
+    for (i, slice) in dst[..n].iter_mut().enumerate() {
+        if slice.len() >= left {
+            // SAFETY: the slice points into `buffer`, which is borrowed for
+            // `'a`, and `left` is within its bounds. `IoSlice::advance` would
+            // avoid this, but is newer than the MSRV.
+            let clamped: &'a [u8] = unsafe { core::slice::from_raw_parts(slice.as_ptr(), left) };
+            *slice = IoSlice::new(clamped);
+            return i + 1;
+        }
+        left -= slice.len();
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SafeBuf;
+
+    #[test]
+    fn bounded() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        {
+            let mut sub = buffer.try_take_buf(4).unwrap();
+            assert_eq!(sub.try_get_u16().unwrap(), 0x0001);
+            assert!(sub.try_get_u32().is_err());
+            assert_eq!(sub.try_get_u16().unwrap(), 0x0203);
+            assert!(sub.finish().is_ok());
+        }
+
+        assert_eq!(buffer, &[4, 5]);
+        assert!(buffer.try_take_buf(3).is_err());
+    }
+
+    #[test]
+    fn skips_unread_bytes() {
+        let mut buffer = &[0_u8, 1, 2, 3, 4, 5][..];
+
+        let mut sub = buffer.try_take_buf(4).unwrap();
+        assert_eq!(sub.try_get_u8().unwrap(), 0);
+        assert!(sub.finish().is_err());
+
+        assert_eq!(buffer, &[4, 5]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn chunks_vectored() {
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1, 2][..], &[3_u8, 4, 5, 6][..]);
+
+        let sub = buffer.try_take_buf(5).unwrap();
+        let mut slices = [std::io::IoSlice::new(&[]); 4];
+        assert_eq!(bytes::Buf::chunks_vectored(&sub, &mut slices), 2);
+        assert_eq!(&*slices[0], &[0, 1, 2]);
+        assert_eq!(&*slices[1], &[3, 4]);
+
+        // peeks see past the first chunk of the parent
+        assert_eq!(sub.try_peek(5).unwrap(), &[0, 1, 2, 3, 4][..]);
+        assert!(sub.try_peek(6).is_err());
+    }
+}
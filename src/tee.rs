@@ -0,0 +1,238 @@
+//! A [`bytes::Buf`] wrapper which copies consumed bytes into a sink
+
+use crate::FromBuf;
+use alloc::vec::Vec;
+use bytes::Buf;
+#[cfg(feature = "std")]
+use std::io::{self, IoSlice};
+
+/// Objects which implement [`TeeSink`] receive the bytes consumed through a
+/// [`Tee`]
+///
+/// This is implemented for `Vec<u8>`, and for any `FnMut(&[u8])` closure (for
+/// example, one which sends the bytes down a channel). Use [`IoSink`] to write
+/// the bytes to a file.
+pub trait TeeSink {
+    /// Receive the next bytes consumed from the buffer
+    fn consumed(&mut self, bytes: &[u8]);
+}
+
+impl TeeSink for Vec<u8> {
+    fn consumed(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+impl<F> TeeSink for F
+where
+    F: FnMut(&[u8]),
+{
+    fn consumed(&mut self, bytes: &[u8]) {
+        self(bytes);
+    }
+}
+
+/// A [`TeeSink`] which writes to an [`io::Write`]
+///
+/// [`Buf::advance`] cannot fail, so the first write error is stored (and
+/// subsequent bytes are dropped) until the sink is recovered with
+/// [`IoSink::into_inner`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSink<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoSink<W> {
+    /// Wrap a writer
+    pub fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// Recover the writer, or the first error encountered while writing to it
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if any write failed
+    pub fn into_inner(self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.inner),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> TeeSink for IoSink<W>
+where
+    W: io::Write,
+{
+    fn consumed(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            self.error = self.inner.write_all(bytes).err();
+        }
+    }
+}
+
+/// A wrapper around a [`Buf`] which copies every consumed byte into a sink
+///
+/// This captures exactly the bytes a parser read, so that a parse which fails
+/// in production can be recorded and replayed deterministically in a test.
+/// Unlike [`Traced`](crate::Traced), nothing is recorded about the reads
+/// themselves, so the overhead is a single copy of each byte.
+///
+/// ```
+/// use safer_bytes::{SafeBuf, Tee};
+///
+/// let mut buffer = Tee::new(&[0x00, 0x02, 0xAA, 0xBB, 0xCC][..], Vec::new());
+///
+/// let len = buffer.try_get_u16()?;
+/// buffer.try_advance(len.into())?;
+///
+/// let (rest, captured) = buffer.into_parts();
+/// assert_eq!(captured, [0x00, 0x02, 0xAA, 0xBB]);
+/// assert_eq!(rest, [0xCC]);
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tee<B, S> {
+    inner: B,
+    sink: S,
+}
+
+impl<B, S> Tee<B, S> {
+    /// Wrap a buffer, copying consumed bytes into `sink`
+    pub fn new(inner: B, sink: S) -> Self {
+        Self { inner, sink }
+    }
+
+    /// Get a reference to the sink
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Get a mutable reference to the sink
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer and the sink
+    pub fn into_parts(self) -> (B, S) {
+        (self.inner, self.sink)
+    }
+}
+
+impl<B, S> Tee<B, S>
+where
+    B: Buf,
+    S: TeeSink,
+{
+    /// Read a custom object from the buffer, copying the bytes it consumes
+    /// into the sink
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the type cannot be parsed from the
+    /// bytes.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        T::from_buf(self)
+    }
+}
+
+impl<B, S> Buf for Tee<B, S>
+where
+    B: Buf,
+    S: TeeSink,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining(), "cannot advance past end of buffer");
+
+        let mut left = cnt;
+        while left > 0 {
+            let chunk = self.inner.chunk();
+            let len = chunk.len().min(left);
+            self.sink.consumed(&chunk[..len]);
+            self.inner.advance(len);
+            left -= len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tee;
+    use crate::{testing::FragmentedBuf, MacAddr, SafeBuf};
+
+    #[test]
+    fn captures_across_chunks() {
+        let data = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut pieces = Vec::new();
+        let mut buffer = Tee::new(
+            FragmentedBuf::new(&data, vec![2, 5, 2]),
+            |bytes: &[u8]| {
+                pieces.push(bytes.to_vec());
+            },
+        );
+
+        assert_eq!(
+            buffer.extract::<MacAddr>().unwrap(),
+            MacAddr([1, 2, 3, 4, 5, 6])
+        );
+        assert!(SafeBuf::try_get_u32(&mut buffer).is_err());
+        SafeBuf::try_advance(&mut buffer, 1).unwrap();
+
+        drop(buffer);
+        assert_eq!(pieces, [vec![1, 2], vec![3, 4, 5, 6], vec![7]]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn io_sink() {
+        use super::IoSink;
+
+        let mut buffer = Tee::new(&b"replay me"[..], IoSink::new(Vec::new()));
+        SafeBuf::try_advance(&mut buffer, 6).unwrap();
+
+        let (_, sink) = buffer.into_parts();
+        assert_eq!(sink.into_inner().unwrap(), b"replay");
+
+        let mut storage = [0; 2];
+        let mut buffer = Tee::new(&b"abc"[..], IoSink::new(&mut storage[..]));
+        SafeBuf::try_advance(&mut buffer, 3).unwrap();
+        assert!(buffer.into_parts().1.into_inner().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn peek_across_chunks() {
+        let buffer = Tee::new(
+            bytes::Buf::chain(&[0_u8, 1][..], &[2_u8, 3][..]),
+            Vec::new(),
+        );
+        assert_eq!(buffer.try_peek(4).unwrap(), &[0, 1, 2, 3][..]);
+        assert!(buffer.sink().is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+//! Test doubles for exercising parsers against awkward buffers
+//!
+//! Most buffers seen in unit tests are contiguous, but buffers seen in
+//! production (such as [`Chain`](bytes::buf::Chain) or a
+//! [`ChunkedBuf`](crate::ChunkedBuf)) may split a value across several
+//! chunks. [`FragmentedBuf`] makes those chunk boundaries explicit, so that a
+//! parser can be tested against them.
+//!
+//! Similarly, a message may be cut off at any point. [`TruncatingBuf`]
+//! presents only a prefix of a buffer, so that every
+//! [`Truncated`](crate::error::Truncated) path of a parser can be exercised.
+//!
+//! [`assert_round_trip`] and [`assert_rejects`] build on these to check
+//! [`FromBuf`] and [`ToBuf`] implementations in a line each.
+//!
+//! The assertions panic, so this module is only available with the `testing`
+//! feature, which is intended to be enabled for dev-dependencies.
+//!
+//! ```
+//! use safer_bytes::{testing::FragmentedBuf, SafeBuf};
+//!
+//! let mut buffer = FragmentedBuf::bytewise(&[0x12, 0x34, 0x56, 0x78]);
+//!
+//! assert_eq!(buffer.try_get_u32()?, 0x1234_5678);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{Error, FromBuf, ToBuf};
+use alloc::vec::Vec;
+use bytes::Buf;
+use core::fmt::Debug;
+
+/// Assert that `value` survives being written and read back, returning its
+/// encoding
+///
+/// The value is read back both from a contiguous buffer and from a
+/// [`FragmentedBuf`] of single bytes, and must consume exactly the bytes which
+/// were written in both cases. The returned encoding can be compared against a
+/// golden value.
+///
+/// ```
+/// use safer_bytes::{testing::assert_round_trip, MacAddr};
+///
+/// let address = MacAddr([0x00, 0x1B, 0x44, 0x11, 0x3A, 0xB7]);
+/// assert_eq!(assert_round_trip(&address), address.octets());
+/// ```
+///
+/// # Panics
+///
+/// This function will panic if the value cannot be written, if it cannot be
+/// read back, if the value read back differs, or if it does not consume the
+/// whole encoding
+#[track_caller]
+pub fn assert_round_trip<T>(value: &T) -> Vec<u8>
+where
+    T: FromBuf + ToBuf + PartialEq + Debug,
+{
+    let mut encoded = Vec::new();
+    if let Err(e) = value.to_buf(&mut encoded) {
+        panic!("failed to write {:?}: {}", value, e);
+    }
+
+    let mut contiguous = &encoded[..];
+    let mut fragmented = FragmentedBuf::bytewise(&encoded);
+    for (result, remaining) in &[
+        (T::from_buf(&mut contiguous), contiguous.remaining()),
+        (T::from_buf(&mut fragmented), fragmented.remaining()),
+    ] {
+        match result {
+            Ok(decoded) => assert_eq!(decoded, value, "value changed by a round trip"),
+            Err(e) => panic!(
+                "failed to read back {:?} from {:02x?}: {}",
+                value, encoded, e
+            ),
+        }
+        assert_eq!(
+            *remaining,
+            0,
+            "{} of {} bytes were not read back",
+            remaining,
+            encoded.len()
+        );
+    }
+
+    encoded
+}
+
+/// Assert that parsing a `T` from `bytes` fails with the `expected` error
+///
+/// The bytes are parsed both from a contiguous buffer and from a
+/// [`FragmentedBuf`] of single bytes, which must fail in the same way.
+///
+/// ```
+/// use safer_bytes::{error::Truncated, testing::assert_rejects, MacAddr};
+///
+/// assert_rejects::<MacAddr>(&[0x00, 0x1B, 0x44], Truncated::new(6, 3));
+/// ```
+///
+/// # Panics
+///
+/// This function will panic if a `T` is parsed successfully, or if parsing
+/// fails with a different error
+#[track_caller]
+pub fn assert_rejects<T>(bytes: &[u8], expected: impl Into<Error>)
+where
+    T: FromBuf + Debug,
+{
+    let expected = expected.into();
+    for result in &[
+        T::from_buf(bytes),
+        T::from_buf(FragmentedBuf::bytewise(bytes)),
+    ] {
+        match result {
+            Ok(value) => panic!("parsed {:?} from {:02x?}, expected an error", value, bytes),
+            Err(e) => assert_eq!(e, &expected, "wrong error parsing {:02x?}", bytes),
+        }
+    }
+}
+
+/// A [`Buf`] over a byte string, divided into chunks of chosen sizes
+///
+/// Each call to [`Buf::chunk`] returns at most one chunk, so reads which span
+/// a boundary must be assembled from several chunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentedBuf {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+    position: usize,
+    chunk: usize,
+}
+
+impl FragmentedBuf {
+    /// Divide `data` into chunks of the given sizes
+    ///
+    /// Sizes of zero are ignored. Sizes which run past the end of the data are
+    /// truncated, and any data left over after the sizes are exhausted forms a
+    /// final chunk.
+    pub fn new(data: &[u8], sizes: impl IntoIterator<Item = usize>) -> Self {
+        let mut ends = Vec::new();
+        let mut end = 0;
+        for size in sizes {
+            if end == data.len() {
+                break;
+            }
+            if size > 0 {
+                end += size.min(data.len() - end);
+                ends.push(end);
+            }
+        }
+        if end < data.len() {
+            ends.push(data.len());
+        }
+
+        Self {
+            data: data.to_vec(),
+            ends,
+            position: 0,
+            chunk: 0,
+        }
+    }
+
+    /// Divide `data` into chunks of `size` bytes (the last of which may be
+    /// shorter)
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if `size` is zero
+    #[must_use]
+    pub fn uniform(data: &[u8], size: usize) -> Self {
+        assert!(size > 0, "chunk size must be non-zero");
+        Self::new(data, core::iter::repeat(size))
+    }
+
+    /// Divide `data` into chunks of a single byte each
+    ///
+    /// This is the most fragmented form of a buffer, so every multi-byte read
+    /// spans a chunk boundary.
+    #[must_use]
+    pub fn bytewise(data: &[u8]) -> Self {
+        Self::uniform(data, 1)
+    }
+
+    /// The number of chunks remaining, including the current one
+    #[must_use]
+    pub fn chunks_remaining(&self) -> usize {
+        self.ends.len() - self.chunk
+    }
+}
+
+impl Buf for FragmentedBuf {
+    fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    fn chunk(&self) -> &[u8] {
+        match self.ends.get(self.chunk) {
+            Some(&end) => &self.data[self.position..end],
+            None => &[],
+        }
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining(),
+            "cannot advance past the end of the buffer"
+        );
+        self.position += cnt;
+        while self
+            .ends
+            .get(self.chunk)
+            .map_or(false, |&end| end <= self.position)
+        {
+            self.chunk += 1;
+        }
+    }
+}
+
+/// A [`Buf`] which presents only the first bytes of another buffer, as though
+/// the rest had been cut off
+///
+/// ```
+/// use safer_bytes::{testing::TruncatingBuf, Bytes, SafeBuf};
+///
+/// fn parse<B: SafeBuf>(buffer: &mut B) -> safer_bytes::Result<Bytes> {
+///     let len = SafeBuf::try_get_u16(buffer)?;
+///     Ok(SafeBuf::try_copy_to_bytes(buffer, len.into())?)
+/// }
+///
+/// let message = [0, 4, b'a', b'b', b'c', b'd'];
+/// assert_eq!(parse(&mut &message[..])?, &b"abcd"[..]);
+///
+/// // parsing any strict prefix of the message fails cleanly
+/// for mut buffer in TruncatingBuf::prefixes(&message) {
+///     assert!(parse(&mut buffer).is_err());
+/// }
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncatingBuf<B> {
+    inner: B,
+    remaining: usize,
+}
+
+impl<B> TruncatingBuf<B>
+where
+    B: Buf,
+{
+    /// Present only the first `len` bytes of `inner`
+    ///
+    /// If `inner` holds fewer than `len` bytes, all of them are presented.
+    pub fn new(inner: B, len: usize) -> Self {
+        let remaining = len.min(inner.remaining());
+        Self { inner, remaining }
+    }
+
+    /// Consume the wrapper, returning the underlying buffer
+    ///
+    /// The bytes which were hidden remain in the underlying buffer.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<'a> TruncatingBuf<&'a [u8]> {
+    /// Every strict prefix of `data`, from the empty prefix up to all but the
+    /// last byte
+    pub fn prefixes(data: &'a [u8]) -> impl Iterator<Item = Self> + 'a {
+        (0..data.len()).map(move |len| Self::new(data, len))
+    }
+}
+
+impl<B> Buf for TruncatingBuf<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    fn chunk(&self) -> &[u8] {
+        let chunk = self.inner.chunk();
+        &chunk[..chunk.len().min(self.remaining)]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining,
+            "cannot advance past the end of the buffer"
+        );
+        self.inner.advance(cnt);
+        self.remaining -= cnt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_rejects, assert_round_trip, FragmentedBuf, TruncatingBuf};
+    use crate::{error, Error, FromBuf, MacAddr, SafeBuf};
+    use bytes::Buf;
+
+    #[test]
+    fn chunk_boundaries() {
+        let mut buffer = FragmentedBuf::new(&[1, 2, 3, 4, 5, 6], vec![2, 0, 3]);
+        assert_eq!(buffer.chunks_remaining(), 3);
+        assert_eq!(buffer.chunk(), [1, 2]);
+
+        buffer.advance(1);
+        assert_eq!(buffer.chunk(), [2]);
+        buffer.advance(2);
+        assert_eq!(buffer.chunk(), [4, 5]);
+        assert_eq!(buffer.chunks_remaining(), 2);
+        buffer.advance(2);
+        assert_eq!(buffer.chunk(), [6]);
+        buffer.advance(1);
+        assert!(buffer.chunk().is_empty());
+        assert_eq!(buffer.chunks_remaining(), 0);
+    }
+
+    #[test]
+    fn sizes_past_the_end() {
+        let buffer = FragmentedBuf::new(&[1, 2, 3], vec![2, 5, 1]);
+        assert_eq!(buffer.ends, [2, 3]);
+
+        let buffer = FragmentedBuf::uniform(&[], 4);
+        assert_eq!(buffer.remaining(), 0);
+        assert!(buffer.chunk().is_empty());
+    }
+
+    #[test]
+    fn reads_span_chunks() {
+        let mut buffer = FragmentedBuf::bytewise(&[0, 0, 1, 0, 0xAB]);
+
+        assert_eq!(SafeBuf::try_get_u16_le(&mut buffer).unwrap(), 0);
+        assert_eq!(SafeBuf::try_get_u16(&mut buffer).unwrap(), 0x0100);
+        assert_eq!(
+            SafeBuf::try_get_u16(&mut buffer).unwrap_err(),
+            error::Truncated::new(2, 1)
+        );
+        assert_eq!(SafeBuf::try_get_u8(&mut buffer).unwrap(), 0xAB);
+    }
+
+    #[test]
+    fn truncating() {
+        let mut buffer = TruncatingBuf::new(FragmentedBuf::bytewise(&[1, 2, 3, 4]), 3);
+        assert_eq!(buffer.remaining(), 3);
+        assert_eq!(
+            SafeBuf::try_get_u32(&mut buffer).unwrap_err(),
+            error::Truncated::new(4, 3)
+        );
+        assert_eq!(SafeBuf::try_get_u16(&mut buffer).unwrap(), 0x0102);
+        assert_eq!(buffer.into_inner().remaining(), 2);
+
+        assert_eq!(TruncatingBuf::new(&[1, 2][..], 5).remaining(), 2);
+    }
+
+    #[test]
+    fn prefixes() {
+        let message = [1, 2, 3];
+        let lengths: Vec<_> = TruncatingBuf::prefixes(&message)
+            .map(|buffer| buffer.remaining())
+            .collect();
+        assert_eq!(lengths, [0, 1, 2]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let address = MacAddr([1, 2, 3, 4, 5, 6]);
+        assert_eq!(assert_round_trip(&address), [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Flag(bool);
+
+    impl FromBuf for Flag {
+        fn from_buf<B>(mut buffer: B) -> crate::Result<Self>
+        where
+            B: bytes::Buf,
+        {
+            match SafeBuf::try_get_u8(&mut buffer)? {
+                0 => Ok(Self(false)),
+                1 => Ok(Self(true)),
+                value => Err(Error::invalid("flag", value)),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects() {
+        assert_rejects::<Flag>(&[], error::Truncated::new(1, 0));
+        assert_rejects::<Flag>(&[2], Error::invalid("flag", 2_u8));
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong error")]
+    fn rejects_with_other_error() {
+        assert_rejects::<Flag>(&[2], error::Truncated::new(1, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected an error")]
+    fn rejects_valid_input() {
+        assert_rejects::<Flag>(&[1], error::Truncated::new(1, 0));
+    }
+}
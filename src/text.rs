@@ -0,0 +1,87 @@
+//! Decoding of binary fields embedded in text
+
+use crate::Error;
+use alloc::vec::Vec;
+
+/// Decode pairs of ASCII hex digits (of either case) into bytes
+pub(crate) fn decode_hex(text: &[u8]) -> crate::Result<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return Err(Error::invalid("hex length", text.len() as u64));
+    }
+
+    text.chunks_exact(2)
+        .map(|pair| Ok(hex_digit(pair[0])? << 4 | hex_digit(pair[1])?))
+        .collect()
+}
+
+/// The alphabet of a base64-encoded field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, using `+` and `/` (RFC 4648, section 4)
+    Standard,
+
+    /// The URL and filename safe alphabet, using `-` and `_` (RFC 4648,
+    /// section 5)
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn decode(self, symbol: u8) -> crate::Result<u32> {
+        let value = match (symbol, self) {
+            (b'A'..=b'Z', _) => symbol - b'A',
+            (b'a'..=b'z', _) => symbol - b'a' + 26,
+            (b'0'..=b'9', _) => symbol - b'0' + 52,
+            (b'+', Self::Standard) | (b'-', Self::UrlSafe) => 62,
+            (b'/', Self::Standard) | (b'_', Self::UrlSafe) => 63,
+            _ => return Err(Error::invalid("base64 character", &[symbol][..])),
+        };
+        Ok(value.into())
+    }
+}
+
+/// Decode base64, with or without padding
+///
+/// Padding must extend the text to a multiple of four characters if present,
+/// and unused bits in the final character must be zero, so that every
+/// encoding is canonical.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn decode_base64(text: &[u8], alphabet: Base64Alphabet) -> crate::Result<Vec<u8>> {
+    let data = text
+        .strip_suffix(b"==")
+        .or_else(|| text.strip_suffix(b"="))
+        .unwrap_or(text);
+    if data.len() != text.len() && text.len() % 4 != 0 {
+        return Err(Error::invalid("base64 padding", &text[data.len()..]));
+    }
+    if data.len() % 4 == 1 {
+        return Err(Error::invalid("base64 length", text.len() as u64));
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    for group in data.chunks(4) {
+        let mut bits = 0;
+        for &symbol in group {
+            bits = bits << 6 | alphabet.decode(symbol)?;
+        }
+
+        // the number of bits beyond a whole number of bytes
+        let spare = group.len() * 6 % 8;
+        if bits & ((1 << spare) - 1) != 0 {
+            return Err(Error::invalid("base64 trailing bits", group));
+        }
+        bits >>= spare;
+        for i in (0..group.len() * 6 / 8).rev() {
+            bytes.push((bits >> (i * 8)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+fn hex_digit(digit: u8) -> crate::Result<u8> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(Error::invalid("hex digit", &[digit][..])),
+    }
+}
@@ -0,0 +1,363 @@
+//! A [`bytes::Buf`] wrapper which records what each read consumed
+
+use crate::FromBuf;
+use alloc::vec::Vec;
+use bytes::Buf;
+use core::{fmt, ops::Range};
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+/// The number of bytes rendered on each line of a [`ReadTrace`]
+const BYTES_PER_LINE: usize = 8;
+
+/// A wrapper around a [`Buf`] which records the byte range consumed by each
+/// read, for debugging parsers
+///
+/// Reads run through [`Traced::read_with`] are recorded under a label, and
+/// those run through [`Traced::extract`] under the name of the type. Bytes
+/// consumed outside of these are recorded without a label. The resulting
+/// [`ReadTrace`] renders as an annotated hexdump, which makes it easy to spot
+/// a parser reading the wrong fields.
+///
+/// Every consumed byte is copied into the trace, so this is intended for
+/// tests and debugging rather than production use.
+///
+/// ```
+/// use safer_bytes::{MacAddr, SafeBuf, Traced};
+///
+/// let mut buffer = Traced::new(&[0x01, 0x00, 0x1B, 0x44, 0x11, 0x3A, 0xB7, 0xFF][..]);
+///
+/// buffer.read_with("version", |b| Ok(b.try_get_u8()?))?;
+/// buffer.extract::<MacAddr>()?;
+/// buffer.try_advance(1)?;
+///
+/// let trace = buffer.trace().to_string();
+/// assert_eq!(
+///     trace.lines().collect::<Vec<_>>(),
+///     [
+///         "00000000  01                       version",
+///         "00000001  00 1b 44 11 3a b7        safer_bytes::net::MacAddr",
+///         "00000007  ff                       (untraced)",
+///     ]
+/// );
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Traced<B> {
+    inner: B,
+    trace: ReadTrace,
+    depth: usize,
+}
+
+impl<B> Traced<B> {
+    /// Wrap a buffer, starting an empty trace
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            trace: ReadTrace::default(),
+            depth: 0,
+        }
+    }
+
+    /// The reads recorded so far
+    pub fn trace(&self) -> &ReadTrace {
+        &self.trace
+    }
+
+    /// Get a reference to the underlying buffer
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consume the wrapper, returning the underlying buffer and the trace
+    pub fn into_parts(self) -> (B, ReadTrace) {
+        (self.inner, self.trace)
+    }
+}
+
+impl<B> Traced<B>
+where
+    B: Buf,
+{
+    /// Run a parse against the buffer, recording the bytes it consumes under
+    /// `label`
+    ///
+    /// The bytes are recorded even if the parse fails, and the span is marked
+    /// as failed. Parses may be nested, in which case the inner spans are
+    /// indented beneath the outer span.
+    ///
+    /// # Errors
+    ///
+    /// This method will return any error returned by the parse.
+    pub fn read_with<T, F>(&mut self, label: &'static str, f: F) -> crate::Result<T>
+    where
+        F: FnOnce(&mut Self) -> crate::Result<T>,
+    {
+        let start = self.trace.bytes.len();
+        let depth = self.depth;
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth = depth;
+
+        self.trace.spans.push(TraceSpan {
+            range: start..self.trace.bytes.len(),
+            label: Some(label),
+            depth,
+            failed: result.is_err(),
+        });
+        result
+    }
+
+    /// Read a custom object from the buffer, recording the bytes it consumes
+    /// under the name of the type
+    ///
+    /// See [`Traced::read_with`] for details.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the type cannot be parsed from the
+    /// bytes.
+    pub fn extract<T>(&mut self) -> crate::Result<T>
+    where
+        T: FromBuf,
+    {
+        self.read_with(core::any::type_name::<T>(), |buffer| T::from_buf(buffer))
+    }
+}
+
+impl<B> Buf for Traced<B>
+where
+    B: Buf,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(cnt <= self.remaining(), "cannot advance past end of buffer");
+
+        let start = self.trace.bytes.len();
+        let mut left = cnt;
+        while left > 0 {
+            let chunk = self.inner.chunk();
+            let len = chunk.len().min(left);
+            self.trace.bytes.extend_from_slice(&chunk[..len]);
+            self.inner.advance(len);
+            left -= len;
+        }
+
+        if self.depth == 0 && cnt > 0 {
+            self.trace.push_untraced(start..start + cnt);
+        }
+    }
+}
+
+/// The bytes consumed through a [`Traced`] buffer, and the reads which
+/// consumed them
+///
+/// This is displayed as an annotated hexdump, with a line for each span (and
+/// long spans wrapped over several lines).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadTrace {
+    bytes: Vec<u8>,
+    spans: Vec<TraceSpan>,
+}
+
+impl ReadTrace {
+    /// The bytes consumed so far, in order
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The recorded spans, ordered by offset (with enclosing spans before the
+    /// spans nested within them)
+    #[must_use]
+    pub fn spans(&self) -> Vec<&TraceSpan> {
+        let mut spans: Vec<_> = self.spans.iter().collect();
+        spans.sort_by_key(|span| (span.range.start, span.depth));
+        spans
+    }
+
+    fn push_untraced(&mut self, range: Range<usize>) {
+        if let Some(last) = self.spans.last_mut() {
+            if last.label.is_none() && last.range.end == range.start {
+                last.range.end = range.end;
+                return;
+            }
+        }
+
+        self.spans.push(TraceSpan {
+            range,
+            label: None,
+            depth: 0,
+            failed: false,
+        });
+    }
+}
+
+impl fmt::Display for ReadTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for span in self.spans() {
+            let bytes = &self.bytes[span.range.clone()];
+            let mut lines = bytes.chunks(BYTES_PER_LINE);
+            let first = lines.next().unwrap_or(&[]);
+
+            write!(f, "{:08x}  ", span.range.start)?;
+            write_hex(f, first, true)?;
+            write!(f, "  {:indent$}", "", indent = span.depth * 2)?;
+            f.write_str(span.label.unwrap_or("(untraced)"))?;
+            if span.failed {
+                f.write_str(" (failed)")?;
+            }
+            f.write_str("\n")?;
+
+            for (i, line) in lines.enumerate() {
+                write!(f, "{:08x}  ", span.range.start + (i + 1) * BYTES_PER_LINE)?;
+                write_hex(f, line, false)?;
+                f.write_str("\n")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Write up to a line of bytes in hex, optionally padding to the width of a
+/// full line
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8], pad: bool) -> fmt::Result {
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{:02x}", byte)?;
+    }
+
+    if pad {
+        let width = (BYTES_PER_LINE * 3 - 1).saturating_sub((bytes.len() * 3).saturating_sub(1));
+        write!(f, "{:width$}", "", width = width)?;
+    }
+    Ok(())
+}
+
+/// A range of bytes consumed through a [`Traced`] buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceSpan {
+    range: Range<usize>,
+    label: Option<&'static str>,
+    depth: usize,
+    failed: bool,
+}
+
+impl TraceSpan {
+    /// The offsets of the bytes, relative to where tracing started
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The label of the read, or `None` if the bytes were consumed outside of
+    /// a traced read
+    #[must_use]
+    pub fn label(&self) -> Option<&'static str> {
+        self.label
+    }
+
+    /// How many traced reads enclose this one
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Whether the read failed
+    #[must_use]
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Traced;
+    use crate::{testing::FragmentedBuf, SafeBuf};
+
+    #[test]
+    fn nested_and_failed_reads() {
+        let mut buffer = Traced::new(FragmentedBuf::bytewise(&[
+            0, 10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0xFF,
+        ]));
+
+        buffer
+            .read_with("record", |b| {
+                let len = b.read_with("length", |b| Ok(SafeBuf::try_get_u16(b)?))?;
+                b.read_with("payload", |b| {
+                    Ok(SafeBuf::try_copy_to_bytes(b, len.into())?)
+                })
+            })
+            .unwrap();
+        assert!(buffer
+            .read_with("trailer", |b| Ok(SafeBuf::try_get_u16(b)?))
+            .is_err());
+        SafeBuf::try_advance(&mut buffer, 1).unwrap();
+
+        let trace = buffer.trace();
+        let labels: Vec<_> = trace
+            .spans()
+            .iter()
+            .map(|span| (span.range(), span.label(), span.depth()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                (0..12, Some("record"), 0),
+                (0..2, Some("length"), 1),
+                (2..12, Some("payload"), 1),
+                (12..12, Some("trailer"), 0),
+                (12..13, None, 0),
+            ]
+        );
+        assert_eq!(trace.bytes().len(), 13);
+
+        assert_eq!(
+            trace.to_string().lines().collect::<Vec<_>>(),
+            [
+                "00000000  00 0a 01 02 03 04 05 06  record",
+                "00000008  07 08 09 0a",
+                "00000000  00 0a                      length",
+                "00000002  01 02 03 04 05 06 07 08    payload",
+                "0000000a  09 0a",
+                "0000000c                           trailer (failed)",
+                "0000000c  ff                       (untraced)",
+            ]
+        );
+    }
+
+    #[test]
+    fn untraced_reads_coalesce() {
+        let mut buffer = Traced::new(&[1, 2, 3, 4][..]);
+        SafeBuf::try_advance(&mut buffer, 1).unwrap();
+        SafeBuf::try_get_u16(&mut buffer).unwrap();
+
+        let (inner, trace) = buffer.into_parts();
+        assert_eq!(inner, [4]);
+        assert_eq!(trace.spans().len(), 1);
+        assert_eq!(trace.spans()[0].range(), 0..3);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn peek_across_chunks() {
+        let buffer = Traced::new(bytes::Buf::chain(&[0_u8, 1][..], &[2_u8, 3][..]));
+        assert_eq!(buffer.try_peek(4).unwrap(), &[0, 1, 2, 3][..]);
+    }
+}
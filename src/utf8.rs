@@ -0,0 +1,179 @@
+//! UTF-8 validation across chunk boundaries
+
+use crate::Error;
+
+/// A streaming UTF-8 validator
+///
+/// Text read from a fragmented stream may split a code point between reads.
+/// The validator carries the bytes of an incomplete code point from one call
+/// to [`Utf8Validator::feed`] to the next, so each piece can be validated as it
+/// arrives, without concatenating the text first.
+///
+/// ```
+/// use safer_bytes::Utf8Validator;
+///
+/// let mut validator = Utf8Validator::new();
+///
+/// // "é" split across two reads
+/// validator.feed(b"caf\xC3")?;
+/// assert!(!validator.is_complete());
+/// validator.feed(b"\xA9!")?;
+///
+/// validator.finish()?;
+/// # Ok::<(), safer_bytes::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf8Validator {
+    partial: [u8; 4],
+    partial_len: usize,
+    valid_len: usize,
+}
+
+impl Utf8Validator {
+    /// Construct a new validator, at the start of the text
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of bytes validated so far, excluding the bytes of an
+    /// incomplete code point
+    #[must_use]
+    pub fn valid_len(&self) -> usize {
+        self.valid_len
+    }
+
+    /// Whether the text validated so far ends on a code point boundary
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.partial_len == 0
+    }
+
+    /// Validate the next piece of the text
+    ///
+    /// The piece may start or end part-way through a code point. The validator
+    /// is unchanged if the piece is invalid.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the text is not valid UTF-8. The
+    /// offending value is the invalid sequence.
+    pub fn feed(&mut self, bytes: &[u8]) -> crate::Result<()> {
+        let mut next = *self;
+        let rest = next.complete_partial(bytes)?;
+        next.validate(rest)?;
+        *self = next;
+        Ok(())
+    }
+
+    /// Check that the text ended on a code point boundary
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the text ended part-way through a
+    /// code point. The offending value is the incomplete sequence.
+    pub fn finish(self) -> crate::Result<()> {
+        if self.is_complete() {
+            Ok(())
+        } else {
+            Err(invalid(&self.partial[..self.partial_len]))
+        }
+    }
+
+    /// Extend an incomplete code point from the front of `bytes`, returning the
+    /// bytes which follow it
+    fn complete_partial<'a>(&mut self, bytes: &'a [u8]) -> crate::Result<&'a [u8]> {
+        if self.is_complete() {
+            return Ok(bytes);
+        }
+
+        let width = match self.partial[0] {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            _ => 4,
+        };
+        let take = (width - self.partial_len).min(bytes.len());
+        self.partial[self.partial_len..self.partial_len + take].copy_from_slice(&bytes[..take]);
+        self.partial_len += take;
+
+        let sequence = &self.partial[..self.partial_len];
+        match core::str::from_utf8(sequence) {
+            Ok(_) => {
+                self.valid_len += width;
+                self.partial_len = 0;
+            }
+            Err(e) => {
+                if let Some(len) = e.error_len() {
+                    return Err(invalid(&sequence[..len]));
+                }
+            }
+        }
+        Ok(&bytes[take..])
+    }
+
+    /// Validate bytes which start on a code point boundary
+    fn validate(&mut self, bytes: &[u8]) -> crate::Result<()> {
+        match core::str::from_utf8(bytes) {
+            Ok(_) => self.valid_len += bytes.len(),
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                if let Some(len) = e.error_len() {
+                    return Err(invalid(&rest[..len]));
+                }
+                self.valid_len += valid.len();
+                self.partial[..rest.len()].copy_from_slice(rest);
+                self.partial_len = rest.len();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid(sequence: &[u8]) -> Error {
+    Error::invalid("UTF-8 sequence", sequence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Utf8Validator;
+    use crate::Error;
+
+    #[test]
+    fn split_code_points() {
+        let text = "a\u{e9}\u{20ac}\u{1f980}z".as_bytes();
+
+        for split in 0..=text.len() {
+            for second in split..=text.len() {
+                let mut validator = Utf8Validator::new();
+                validator.feed(&text[..split]).unwrap();
+                validator.feed(&text[split..second]).unwrap();
+                validator.feed(&text[second..]).unwrap();
+                assert_eq!(validator.valid_len(), text.len());
+                validator.finish().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_sequences() {
+        let mut validator = Utf8Validator::new();
+        validator.feed(b"ab\xE2\x82").unwrap();
+        assert_eq!(validator.valid_len(), 2);
+
+        // the validator is unchanged by a failed feed
+        assert_eq!(
+            validator.feed(b"z").unwrap_err(),
+            Error::invalid("UTF-8 sequence", &b"\xE2\x82"[..])
+        );
+        assert_eq!(
+            validator.finish().unwrap_err(),
+            Error::invalid("UTF-8 sequence", &b"\xE2\x82"[..])
+        );
+
+        assert_eq!(
+            Utf8Validator::new().feed(b"ok\xFFok").unwrap_err(),
+            Error::invalid("UTF-8 sequence", &b"\xFF"[..])
+        );
+        assert!(Utf8Validator::new().feed(b"\xED\xA0\x80").is_err());
+    }
+}
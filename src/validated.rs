@@ -0,0 +1,194 @@
+//! Records which are bounds-checked once, up front
+
+use crate::{error, SubBuf};
+use bytes::{Buf, Bytes};
+use paste::paste;
+#[cfg(feature = "std")]
+use std::io::IoSlice;
+
+macro_rules! get_primitive {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a big-endian `" $t "` from the record, without a per-read bounds check"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "This method will panic if fewer than " $width " bytes remain in the record"]
+            #[inline]
+            pub fn [<get_ $t>](&mut self) -> $t {
+                self.inner.[<get_ $t>]()
+            }
+        }
+    };
+}
+
+macro_rules! get_primitive_le {
+    ($t:ty, $width:literal) => {
+        paste! {
+            #[doc = "Read a little-endian `" $t "` from the record, without a per-read bounds check"]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = ""]
+            #[doc = "This method will panic if fewer than " $width " bytes remain in the record"]
+            #[inline]
+            pub fn [<get_ $t _le>](&mut self) -> $t {
+                self.inner.[<get_ $t _le>]()
+            }
+        }
+    };
+}
+
+/// A record whose length has been validated up front
+///
+/// This is created by [`SafeBuf::validated`](crate::SafeBuf::validated). Once
+/// the record length has been checked, its fields can be read with infallible
+/// getters instead of checking (and handling an error for) every field. The
+/// getters panic rather than read past the end of the record, so the total
+/// size of the fields read must not exceed the validated length.
+///
+/// As with [`SubBuf`], the parent buffer is positioned directly after the
+/// record once this is dropped.
+///
+/// ```
+/// use safer_bytes::SafeBuf;
+///
+/// let mut buffer: &[u8] = &[0x00, 0x2A, 0x01, 0x00, 0x00, 0x00, 0xFF];
+///
+/// let mut record = buffer.validated(6)?;
+/// assert_eq!(record.get_u16(), 42);
+/// assert_eq!(record.get_u32_le(), 1);
+/// drop(record);
+///
+/// assert_eq!(buffer, &[0xFF]);
+/// # Ok::<(), safer_bytes::error::Truncated>(())
+/// ```
+#[derive(Debug)]
+pub struct ValidatedBuf<'a, B>
+where
+    B: Buf + ?Sized,
+{
+    inner: SubBuf<'a, B>,
+}
+
+impl<'a, B> ValidatedBuf<'a, B>
+where
+    B: Buf + ?Sized,
+{
+    get_primitive!(u8, 1);
+
+    get_primitive!(i8, 1);
+
+    get_primitive!(u16, 2);
+
+    get_primitive!(i16, 2);
+
+    get_primitive!(u32, 4);
+
+    get_primitive!(i32, 4);
+
+    get_primitive!(u64, 8);
+
+    get_primitive!(i64, 8);
+
+    get_primitive!(u128, 16);
+
+    get_primitive!(i128, 16);
+
+    get_primitive_le!(u16, 2);
+
+    get_primitive_le!(i16, 2);
+
+    get_primitive_le!(u32, 4);
+
+    get_primitive_le!(i32, 4);
+
+    get_primitive_le!(u64, 8);
+
+    get_primitive_le!(i64, 8);
+
+    get_primitive_le!(u128, 16);
+
+    get_primitive_le!(i128, 16);
+
+    pub(crate) fn new(inner: SubBuf<'a, B>) -> Self {
+        Self { inner }
+    }
+
+    /// The number of bytes remaining in the record
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    /// Whether the record has been fully read
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Consume the record, checking that it was fully read
+    ///
+    /// The parent is advanced past the end of the record regardless of the
+    /// outcome.
+    ///
+    /// # Errors
+    ///
+    /// This method will return [`error::ExtraneousBytes`] if there are bytes
+    /// left in the record.
+    pub fn finish(self) -> core::result::Result<(), error::ExtraneousBytes> {
+        self.inner.finish()
+    }
+}
+
+impl<B> Buf for ValidatedBuf<'_, B>
+where
+    B: Buf + ?Sized,
+{
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        self.inner.chunk()
+    }
+
+    #[cfg(feature = "std")]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        self.inner.chunks_vectored(dst)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.inner.advance(cnt);
+    }
+
+    fn copy_to_bytes(&mut self, len: usize) -> Bytes {
+        self.inner.copy_to_bytes(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SafeBuf;
+
+    #[test]
+    fn validated() {
+        let mut buffer = bytes::Buf::chain(&[0_u8, 1, 2][..], &[3, 4, 5][..]);
+
+        let mut record = buffer.validated(5).unwrap();
+        assert_eq!(record.get_u8(), 0);
+        assert_eq!(record.get_u32_le(), 0x0403_0201);
+        assert!(record.finish().is_ok());
+
+        assert_eq!(bytes::Buf::remaining(&buffer), 1);
+        assert!(buffer.validated(2).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn overrun() {
+        let mut buffer: &[u8] = &[0, 1, 2, 3];
+
+        let mut record = buffer.validated(2).unwrap();
+        record.get_u32();
+    }
+}
@@ -0,0 +1,15 @@
+//! Helpers for reading common binary wire formats
+//!
+//! These are building blocks for hand-rolled (or partial) parsers, rather than
+//! complete implementations of each format. Unless noted otherwise, each reader
+//! leaves the buffer untouched if it fails because the buffer is truncated.
+
+pub mod cbor;
+pub mod der;
+pub mod dns;
+pub mod leb128;
+pub mod msgpack;
+pub mod protobuf;
+pub mod rlp;
+pub mod tlv;
+pub mod wasm;
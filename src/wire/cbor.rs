@@ -0,0 +1,231 @@
+//! CBOR (RFC 8949) data item heads
+//!
+//! Every CBOR data item starts with a head: an initial byte holding the
+//! [`MajorType`] and some additional information, optionally followed by an
+//! argument of 1, 2, 4 or 8 bytes. [`try_get_head`] reads and validates a
+//! head, leaving the item's content (if any) in the buffer.
+//!
+//! ```
+//! use safer_bytes::wire::cbor::{self, MajorType};
+//!
+//! // the text string "hi"
+//! let mut buffer: &[u8] = &[0x62, b'h', b'i'];
+//!
+//! let head = cbor::try_get_head(&mut buffer)?;
+//! assert_eq!(head.major_type, MajorType::TextString);
+//! assert_eq!(head.value, Some(2));
+//! assert_eq!(buffer, b"hi");
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{Error, SafeBuf};
+use bytes::Buf;
+
+/// The additional information value marking an indefinite length (or, for
+/// [`MajorType::Simple`], a "break")
+pub const INDEFINITE: u8 = 31;
+
+/// The type of a CBOR data item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MajorType {
+    /// An unsigned integer (major type 0)
+    Unsigned,
+
+    /// A negative integer, encoded as `-1 - value` (major type 1)
+    Negative,
+
+    /// A byte string (major type 2)
+    ByteString,
+
+    /// A UTF-8 text string (major type 3)
+    TextString,
+
+    /// An array of data items (major type 4)
+    Array,
+
+    /// A map of pairs of data items (major type 5)
+    Map,
+
+    /// A tagged data item (major type 6)
+    Tag,
+
+    /// A simple value, float or "break" (major type 7)
+    Simple,
+}
+
+impl MajorType {
+    fn from_initial_byte(byte: u8) -> Self {
+        match byte >> 5 {
+            0 => Self::Unsigned,
+            1 => Self::Negative,
+            2 => Self::ByteString,
+            3 => Self::TextString,
+            4 => Self::Array,
+            5 => Self::Map,
+            6 => Self::Tag,
+            _ => Self::Simple,
+        }
+    }
+}
+
+/// The head of a CBOR data item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Head {
+    /// The type of the data item
+    pub major_type: MajorType,
+
+    /// The low 5 bits of the initial byte
+    pub additional_info: u8,
+
+    /// The argument of the head
+    ///
+    /// This is the value of an integer, the length of a string, array or map,
+    /// the tag number, or the simple value (or raw bits of a float). It is
+    /// `None` for indefinite-length items and "break".
+    pub value: Option<u64>,
+}
+
+impl Head {
+    /// The number of bytes the head occupies
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        1 + argument_len(self.additional_info)
+    }
+
+    /// Whether this is the "break" marker which terminates an
+    /// indefinite-length item
+    #[must_use]
+    pub fn is_break(&self) -> bool {
+        self.major_type == MajorType::Simple && self.additional_info == INDEFINITE
+    }
+}
+
+/// The number of argument bytes following an initial byte with the given
+/// additional information
+fn argument_len(additional_info: u8) -> usize {
+    match additional_info {
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => 0,
+    }
+}
+
+/// Read the head of a data item
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, if the
+/// additional information is one of the reserved values (28 to 30), if an
+/// indefinite length is used with a major type which doesn't allow it, or if a
+/// simple value below 32 uses the two-byte encoding
+pub fn try_get_head<B>(buffer: &mut B) -> crate::Result<Head>
+where
+    B: Buf,
+{
+    let initial = buffer.try_get_u8_at(0)?;
+    let major_type = MajorType::from_initial_byte(initial);
+    let additional_info = initial & 0x1F;
+
+    let value = match additional_info {
+        0..=23 => Some(u64::from(additional_info)),
+        24 => {
+            let value = buffer.try_get_u8_at(1)?;
+            // simple values below 32 must use the one-byte encoding
+            // (RFC 8949, section 3.3)
+            if major_type == MajorType::Simple && value < 32 {
+                return Err(Error::invalid("CBOR simple value", value));
+            }
+            Some(value.into())
+        }
+        25 => Some(buffer.try_get_u16_at(1)?.into()),
+        26 => Some(buffer.try_get_u32_at(1)?.into()),
+        27 => Some(buffer.try_get_u64_at(1)?),
+        INDEFINITE => match major_type {
+            MajorType::ByteString
+            | MajorType::TextString
+            | MajorType::Array
+            | MajorType::Map
+            | MajorType::Simple => None,
+            _ => return Err(Error::invalid("CBOR initial byte", initial)),
+        },
+        _ => {
+            return Err(Error::invalid(
+                "CBOR additional information",
+                additional_info,
+            ))
+        }
+    };
+
+    let head = Head {
+        major_type,
+        additional_info,
+        value,
+    };
+    buffer.advance(head.encoded_len());
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_head, Head, MajorType};
+    use crate::Error;
+
+    #[test]
+    fn heads() {
+        let mut buffer: &[u8] = &[0x17, 0x38, 0x18, 0x19, 0x01, 0x00, 0xBF, 0xFF];
+
+        assert_eq!(try_get_head(&mut buffer).unwrap().value, Some(23));
+
+        let head = try_get_head(&mut buffer).unwrap();
+        assert_eq!(head.major_type, MajorType::Negative);
+        assert_eq!(head.value, Some(24));
+
+        assert_eq!(try_get_head(&mut buffer).unwrap().value, Some(256));
+
+        let head = try_get_head(&mut buffer).unwrap();
+        assert_eq!(
+            head,
+            Head {
+                major_type: MajorType::Map,
+                additional_info: 31,
+                value: None
+            }
+        );
+
+        assert!(try_get_head(&mut buffer).unwrap().is_break());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buffer: &[u8] = &[0x1A, 0x00, 0x01];
+        assert!(matches!(
+            try_get_head(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), 3);
+    }
+
+    #[test]
+    fn reserved() {
+        let mut reserved: &[u8] = &[0x1C];
+        assert!(try_get_head(&mut reserved).is_err());
+
+        // indefinite-length unsigned integer
+        let mut indefinite: &[u8] = &[0x1F];
+        assert!(try_get_head(&mut indefinite).is_err());
+
+        // two-byte encodings of simple values below 32 are not well-formed
+        let mut simple: &[u8] = &[0xF8, 0x1F];
+        assert_eq!(
+            try_get_head(&mut simple).unwrap_err(),
+            Error::invalid("CBOR simple value", 0x1F_u8)
+        );
+        assert_eq!(simple.len(), 2);
+
+        let mut simple: &[u8] = &[0xF8, 0x20];
+        assert_eq!(try_get_head(&mut simple).unwrap().value, Some(32));
+    }
+}
@@ -0,0 +1,233 @@
+//! ASN.1 Distinguished Encoding Rules (DER) tag-length-value items
+//!
+//! [`try_get_tlv`] reads the identifier and length of a DER item, and returns
+//! its value as a bounded [`SubBuf`], so that constructed values can be parsed
+//! recursively without copying and without reading past the end of the item.
+//!
+//! ```
+//! use safer_bytes::{
+//!     wire::der::{self, Class},
+//!     SafeBuf,
+//! };
+//!
+//! // SEQUENCE { INTEGER 5 }
+//! let mut buffer: &[u8] = &[0x30, 0x03, 0x02, 0x01, 0x05];
+//!
+//! let (tag, mut sequence) = der::try_get_tlv(&mut buffer)?;
+//! assert_eq!(
+//!     (tag.class, tag.constructed, tag.number),
+//!     (Class::Universal, true, 16)
+//! );
+//!
+//! let (tag, mut integer) = der::try_get_tlv(&mut sequence)?;
+//! assert_eq!(tag.number, 2);
+//! assert_eq!(integer.try_get_u8()?, 5);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, Error, SafeBuf, SubBuf};
+use bytes::Buf;
+use core::convert::TryFrom;
+
+/// The maximum number of bytes in an identifier (the tag number is limited to
+/// a `u32`)
+const MAX_IDENTIFIER_LEN: usize = 6;
+
+/// The maximum number of bytes in a length (the length is limited to a `u64`)
+const MAX_LENGTH_LEN: usize = 9;
+
+/// The class of a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// Types defined by ASN.1 itself
+    Universal,
+
+    /// Types defined by an application
+    Application,
+
+    /// Types whose meaning depends on their context
+    ContextSpecific,
+
+    /// Types defined privately
+    Private,
+}
+
+/// The identifier of a DER item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag {
+    /// The class of the tag
+    pub class: Class,
+
+    /// Whether the value is itself a sequence of DER items
+    pub constructed: bool,
+
+    /// The tag number
+    pub number: u32,
+}
+
+/// Read a DER item, returning its tag and its value
+///
+/// The value is a view over the next bytes of the buffer. Once it is dropped,
+/// the buffer is positioned directly after the item.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// identifier or length is not valid DER (including non-minimal encodings,
+/// indefinite lengths, and values which overflow)
+pub fn try_get_tlv<B>(buffer: &mut B) -> crate::Result<(Tag, SubBuf<'_, B>)>
+where
+    B: Buf,
+{
+    let mut header = [0; MAX_IDENTIFIER_LEN + MAX_LENGTH_LEN];
+    let peeked = buffer.remaining().min(header.len());
+    buffer.try_peek_to_slice(&mut header[..peeked])?;
+    let mut header = &header[..peeked];
+
+    let tag = get_tag(&mut header)?;
+    let len = get_len(&mut header)?;
+    let header_len = peeked - header.len();
+
+    let len = usize::try_from(len).map_err(|_| Error::invalid("DER length", len))?;
+    error::Truncated::check(header_len.saturating_add(len), buffer.remaining())?;
+
+    buffer.advance(header_len);
+    Ok((tag, buffer.try_take_buf(len)?))
+}
+
+fn get_tag(header: &mut &[u8]) -> crate::Result<Tag> {
+    let first = SafeBuf::try_get_u8(header)?;
+
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = first & 0x20 != 0;
+
+    let mut number = u32::from(first & 0x1F);
+    if number == 0x1F {
+        number = 0;
+        for i in 0.. {
+            let byte = SafeBuf::try_get_u8(header)?;
+            if i == 0 && byte == 0x80 {
+                return Err(Error::invalid("DER tag number", byte));
+            }
+            if number > u32::MAX >> 7 {
+                return Err(Error::invalid("DER tag number", u64::from(number) << 7));
+            }
+
+            number = (number << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        if number < 0x1F {
+            return Err(Error::invalid("DER tag number", number));
+        }
+    }
+
+    Ok(Tag {
+        class,
+        constructed,
+        number,
+    })
+}
+
+fn get_len(header: &mut &[u8]) -> crate::Result<u64> {
+    let first = SafeBuf::try_get_u8(header)?;
+    if first < 0x80 {
+        return Ok(first.into());
+    }
+
+    let count = usize::from(first & 0x7F);
+    if count == 0 || count > MAX_LENGTH_LEN - 1 {
+        // indefinite (BER only), reserved, or too large to represent
+        return Err(Error::invalid("DER length", first));
+    }
+
+    let mut len = 0;
+    for _ in 0..count {
+        len = (len << 8) | u64::from(SafeBuf::try_get_u8(header)?);
+    }
+
+    if len < 0x80 || len >> (8 * (count - 1)) == 0 {
+        return Err(Error::invalid("DER length", len));
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_tlv, Class, Tag};
+    use crate::{Error, SafeBuf};
+
+    #[test]
+    fn multi_byte_tag() {
+        let mut buffer: &[u8] = &[0xBF, 0x81, 0x00, 0x01, 0xAA, 0xBB];
+
+        let (tag, value) = try_get_tlv(&mut buffer).unwrap();
+        assert_eq!(
+            tag,
+            Tag {
+                class: Class::ContextSpecific,
+                constructed: true,
+                number: 128,
+            }
+        );
+        drop(value);
+        assert_eq!(buffer, &[0xBB]);
+
+        // tag number which fits in the first byte
+        let mut buffer: &[u8] = &[0x1F, 0x1E, 0x00];
+        assert!(try_get_tlv(&mut buffer).is_err());
+
+        // leading zero in the tag number
+        let mut buffer: &[u8] = &[0x1F, 0x80, 0x20, 0x00];
+        assert!(try_get_tlv(&mut buffer).is_err());
+
+        // tag number which overflows
+        let mut buffer: &[u8] = &[0x1F, 0x90, 0x80, 0x80, 0x80, 0x00, 0x00];
+        assert!(try_get_tlv(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn long_length() {
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend_from_slice(&[0xAA; 0x80]);
+        let mut buffer = &long[..];
+
+        let (_, mut value) = try_get_tlv(&mut buffer).unwrap();
+        assert_eq!(value.try_copy_to_bytes(0x80).unwrap().len(), 0x80);
+        drop(value);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn invalid_length() {
+        for header in &[
+            &[0x04, 0x80][..],
+            &[0x04, 0x81, 0x7F],
+            &[0x04, 0x82, 0x00, 0x80],
+            &[0x04, 0xFF],
+        ] {
+            let mut buffer = *header;
+            assert!(matches!(
+                try_get_tlv(&mut buffer).unwrap_err(),
+                Error::InvalidValue(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buffer: &[u8] = &[0x04, 0x03, 0xAA];
+        assert!(matches!(
+            try_get_tlv(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), 3);
+    }
+}
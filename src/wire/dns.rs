@@ -0,0 +1,257 @@
+//! DNS domain names (RFC 1035)
+//!
+//! A domain name is encoded as a sequence of length-prefixed labels, ending
+//! either with the empty root label or with a compression pointer to an earlier
+//! occurrence of the rest of the name in the same message. [`try_take_name`]
+//! decodes a name, following compression pointers within the whole message.
+//!
+//! ```
+//! use safer_bytes::wire::dns;
+//!
+//! // "example.com" at offset 0, then "www" + a pointer to offset 0
+//! let message: &[u8] = b"\x07example\x03com\x00\x03www\xC0\x00";
+//! let mut buffer = &message[13..];
+//!
+//! let name = dns::try_take_name(&mut buffer, message)?;
+//! assert_eq!(name.to_string(), "www.example.com.");
+//! assert!(buffer.is_empty());
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{Error, SafeBuf};
+use alloc::vec::Vec;
+use bytes::{Buf, Bytes};
+use core::fmt;
+
+/// The maximum length of an encoded name, including length bytes and the root
+/// label
+pub const MAX_NAME_LEN: usize = 255;
+
+/// The maximum length of a single label
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// A decoded domain name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Name {
+    labels: Vec<Bytes>,
+}
+
+impl Name {
+    /// The labels of the name, excluding the empty root label
+    #[must_use]
+    pub fn labels(&self) -> &[Bytes] {
+        &self.labels
+    }
+
+    /// Whether this is the root name
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The length of the name once encoded without compression
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        self.labels
+            .iter()
+            .map(|label| label.len() + 1)
+            .sum::<usize>()
+            + 1
+    }
+}
+
+impl fmt::Display for Name {
+    /// Formats the name in presentation format, with a trailing dot
+    ///
+    /// Dots and backslashes within labels are escaped with a backslash, and
+    /// other non-printable bytes are escaped as `\DDD`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+
+        for label in &self.labels {
+            for &byte in label {
+                match byte {
+                    b'.' | b'\\' => write!(f, "\\{}", byte as char)?,
+                    0x21..=0x7E => write!(f, "{}", byte as char)?,
+                    _ => write!(f, "\\{:03}", byte)?,
+                }
+            }
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the labels are currently being read from
+#[derive(Clone, Copy)]
+enum Source {
+    /// At an offset from the front of the buffer
+    Buffer(usize),
+
+    /// At an offset into the message, having followed a pointer
+    Message(usize),
+}
+
+/// Read a domain name, following any compression pointers
+///
+/// `message` is the whole DNS message, which compression pointers refer into.
+/// Only the bytes of the name up to and including the root label or first
+/// pointer are consumed from the buffer.
+///
+/// Each pointer must point strictly before the target of the previous pointer,
+/// which rules out loops.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), if a pointer is out of bounds or would loop, if a
+/// label uses a reserved type, or if the name exceeds [`MAX_NAME_LEN`] bytes
+pub fn try_take_name<B>(buffer: &mut B, message: &[u8]) -> crate::Result<Name>
+where
+    B: Buf,
+{
+    let mut labels = Vec::new();
+    let mut name_len = 1;
+    let mut source = Source::Buffer(0);
+    let mut consumed = 0;
+
+    loop {
+        let len = match source {
+            Source::Buffer(offset) => buffer.try_get_u8_at(offset)?,
+            Source::Message(offset) => message.try_get_u8_at(offset)?,
+        };
+
+        match len & 0xC0 {
+            0x00 if len == 0 => {
+                if let Source::Buffer(offset) = source {
+                    consumed = offset + 1;
+                }
+                break;
+            }
+            0x00 => {
+                let len = usize::from(len);
+                name_len += len + 1;
+                if name_len > MAX_NAME_LEN {
+                    return Err(Error::invalid("DNS name length", name_len as u64));
+                }
+
+                let label = match source {
+                    Source::Buffer(offset) => buffer.try_peek_at(offset + 1, len)?,
+                    Source::Message(offset) => message.try_peek_at(offset + 1, len)?,
+                };
+                labels.push(label);
+
+                source = match source {
+                    Source::Buffer(offset) => Source::Buffer(offset + len + 1),
+                    Source::Message(offset) => Source::Message(offset + len + 1),
+                };
+            }
+            0xC0 => {
+                let pointer = match source {
+                    Source::Buffer(offset) => {
+                        let pointer = buffer.try_get_u16_at(offset)?;
+                        consumed = offset + 2;
+                        pointer
+                    }
+                    Source::Message(offset) => message.try_get_u16_at(offset)?,
+                };
+                let target = usize::from(pointer & 0x3FFF);
+
+                let in_bounds = target < message.len();
+                let backwards = match source {
+                    Source::Buffer(_) => true,
+                    Source::Message(previous) => target < previous,
+                };
+                if !in_bounds || !backwards {
+                    return Err(Error::invalid("DNS compression pointer", pointer));
+                }
+
+                source = Source::Message(target);
+            }
+            _ => return Err(Error::invalid("DNS label type", len)),
+        }
+    }
+
+    buffer.advance(consumed);
+
+    Ok(Name { labels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_take_name, MAX_NAME_LEN};
+    use crate::Error;
+
+    #[test]
+    fn uncompressed() {
+        let message: &[u8] = b"\x03foo\x03bar\x00\xAA";
+        let mut buffer = message;
+
+        let name = try_take_name(&mut buffer, message).unwrap();
+        assert_eq!(name.labels(), &[&b"foo"[..], &b"bar"[..]]);
+        assert_eq!(name.encoded_len(), 9);
+        assert_eq!(buffer, &[0xAA]);
+
+        let mut root: &[u8] = &[0];
+        assert_eq!(try_take_name(&mut root, message).unwrap().to_string(), ".");
+    }
+
+    #[test]
+    fn escaping() {
+        let message: &[u8] = b"\x03a.\x01\x00";
+        let mut buffer = message;
+        assert_eq!(
+            try_take_name(&mut buffer, message).unwrap().to_string(),
+            "a\\.\\001."
+        );
+    }
+
+    #[test]
+    fn pointer_loops() {
+        // a pointer to itself
+        let message: &[u8] = &[0xC0, 0x00];
+        let mut buffer = message;
+        assert!(matches!(
+            try_take_name(&mut buffer, message).unwrap_err(),
+            Error::InvalidValue(_)
+        ));
+
+        // two pointers to each other
+        let message: &[u8] = &[0xC0, 0x02, 0xC0, 0x00];
+        let mut buffer = message;
+        assert!(try_take_name(&mut buffer, message).is_err());
+
+        // out of bounds
+        let message: &[u8] = &[0xC0, 0x10];
+        let mut buffer = message;
+        assert!(try_take_name(&mut buffer, message).is_err());
+    }
+
+    #[test]
+    fn max_len() {
+        // 4 labels of 63 bytes is 257 bytes once encoded
+        let mut message = Vec::new();
+        for _ in 0..4 {
+            message.push(63);
+            message.extend_from_slice(&[b'a'; 63]);
+        }
+        message.push(0);
+        assert!(message.len() > MAX_NAME_LEN);
+
+        let mut buffer = &message[..];
+        assert!(try_take_name(&mut buffer, &message).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let message: &[u8] = b"\x03foo\x03ba";
+        let mut buffer = message;
+        assert!(matches!(
+            try_take_name(&mut buffer, message).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), message.len());
+    }
+}
@@ -0,0 +1,206 @@
+//! Little Endian Base 128 (LEB128) variable-length integers
+//!
+//! Each byte holds 7 bits of the value, least significant first, with the high
+//! bit set on every byte but the last. These readers reject encodings which
+//! are longer than necessary for the integer width, or whose final byte has
+//! bits set beyond that width.
+//!
+//! ```
+//! use safer_bytes::wire::leb128;
+//!
+//! let mut buffer: &[u8] = &[0xE5, 0x8E, 0x26, 0x7F];
+//!
+//! assert_eq!(leb128::try_get_u32(&mut buffer)?, 624_485);
+//! assert_eq!(leb128::try_get_i32(&mut buffer)?, -1);
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, Error, SafeBuf};
+use bytes::Buf;
+
+/// The maximum number of bytes in a LEB128 encoding of a 64-bit integer
+const MAX_LEN: usize = 10;
+
+/// Peek the bytes of a LEB128 encoding of an integer `bits` wide, returning
+/// them along with their number
+fn peek<B>(buffer: &B, bits: usize) -> crate::Result<([u8; MAX_LEN], usize)>
+where
+    B: Buf,
+{
+    let max_len = (bits + 6) / 7;
+    let mut bytes = [0; MAX_LEN];
+    let peeked = buffer.remaining().min(max_len);
+    buffer.try_peek_to_slice(&mut bytes[..peeked])?;
+
+    match bytes[..peeked].iter().position(|byte| byte & 0x80 == 0) {
+        Some(last) => Ok((bytes, last + 1)),
+        None if peeked == max_len => Err(Error::invalid("LEB128 integer", &bytes[..peeked])),
+        None => Err(error::Truncated::new(peeked + 1, peeked).into()),
+    }
+}
+
+/// Read an unsigned integer `bits` wide
+fn get_unsigned<B>(buffer: &mut B, bits: usize) -> crate::Result<u64>
+where
+    B: Buf,
+{
+    let (bytes, len) = peek(buffer, bits)?;
+    let bytes = &bytes[..len];
+
+    let read_bits = 7 * len;
+    if read_bits > bits && bytes[len - 1] >> (7 - (read_bits - bits)) != 0 {
+        return Err(Error::invalid("LEB128 integer", bytes));
+    }
+
+    let value = bytes.iter().enumerate().fold(0, |value, (i, &byte)| {
+        value | u64::from(byte & 0x7F) << (7 * i)
+    });
+    buffer.advance(len);
+    Ok(value)
+}
+
+/// Read a signed integer `bits` wide
+fn get_signed<B>(buffer: &mut B, bits: usize) -> crate::Result<i64>
+where
+    B: Buf,
+{
+    let (bytes, len) = peek(buffer, bits)?;
+    let bytes = &bytes[..len];
+
+    let last = bytes[len - 1];
+    let read_bits = 7 * len;
+    if read_bits > bits {
+        // the bits beyond the width (and the sign bit) must be a sign extension
+        let used = 7 - (read_bits - bits);
+        let extension = last >> (used - 1);
+        if extension != 0 && extension != 0x7F >> (used - 1) {
+            return Err(Error::invalid("LEB128 integer", bytes));
+        }
+    }
+
+    let mut value = bytes.iter().enumerate().fold(0, |value, (i, &byte)| {
+        value | i64::from(byte & 0x7F) << (7 * i)
+    });
+    if read_bits < 64 && last & 0x40 != 0 {
+        value |= -1 << read_bits;
+    }
+    buffer.advance(len);
+    Ok(value)
+}
+
+/// Read an unsigned LEB128 integer of up to 32 bits
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the encoding is too long or overflows
+#[allow(clippy::cast_possible_truncation)]
+pub fn try_get_u32<B>(buffer: &mut B) -> crate::Result<u32>
+where
+    B: Buf,
+{
+    get_unsigned(buffer, 32).map(|value| value as u32)
+}
+
+/// Read an unsigned LEB128 integer of up to 64 bits
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the encoding is too long or overflows
+pub fn try_get_u64<B>(buffer: &mut B) -> crate::Result<u64>
+where
+    B: Buf,
+{
+    get_unsigned(buffer, 64)
+}
+
+/// Read a signed LEB128 integer of up to 32 bits
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the encoding is too long or overflows
+#[allow(clippy::cast_possible_truncation)]
+pub fn try_get_i32<B>(buffer: &mut B) -> crate::Result<i32>
+where
+    B: Buf,
+{
+    get_signed(buffer, 32).map(|value| value as i32)
+}
+
+/// Read a signed LEB128 integer of up to 64 bits
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the encoding is too long or overflows
+pub fn try_get_i64<B>(buffer: &mut B) -> crate::Result<i64>
+where
+    B: Buf,
+{
+    get_signed(buffer, 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_i32, try_get_i64, try_get_u32, try_get_u64};
+    use crate::Error;
+
+    #[test]
+    fn unsigned() {
+        let mut buffer: &[u8] = &[0x00, 0x7F, 0x80, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+        assert_eq!(try_get_u32(&mut buffer).unwrap(), 0);
+        assert_eq!(try_get_u32(&mut buffer).unwrap(), 127);
+        assert_eq!(try_get_u32(&mut buffer).unwrap(), 128);
+        assert_eq!(try_get_u32(&mut buffer).unwrap(), u32::MAX);
+
+        let mut buffer: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+        assert_eq!(try_get_u64(&mut buffer).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn signed() {
+        let mut buffer: &[u8] = &[0x7F, 0x80, 0x7F, 0x3F, 0xC0, 0x00];
+        assert_eq!(try_get_i32(&mut buffer).unwrap(), -1);
+        assert_eq!(try_get_i32(&mut buffer).unwrap(), -128);
+        assert_eq!(try_get_i32(&mut buffer).unwrap(), 63);
+        assert_eq!(try_get_i32(&mut buffer).unwrap(), 64);
+
+        let mut buffer: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x78];
+        assert_eq!(try_get_i32(&mut buffer).unwrap(), i32::MIN);
+
+        let mut buffer: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7F];
+        assert_eq!(try_get_i64(&mut buffer).unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn overflow() {
+        for encoding in &[
+            // 33 bits
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0x1F][..],
+            // too long
+            &[0x80, 0x80, 0x80, 0x80, 0x80, 0x00],
+        ] {
+            let mut buffer = *encoding;
+            assert!(matches!(
+                try_get_u32(&mut buffer).unwrap_err(),
+                Error::InvalidValue(_)
+            ));
+        }
+
+        // not a sign extension
+        let mut buffer: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x70];
+        assert!(try_get_i32(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buffer: &[u8] = &[0x80, 0x80];
+        assert!(matches!(
+            try_get_u64(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), 2);
+    }
+}
@@ -0,0 +1,322 @@
+//! MessagePack headers
+//!
+//! Every MessagePack value starts with a marker byte, which may be followed by
+//! a fixed-size payload (for integers and floats) or a length (for strings,
+//! binary data, arrays, maps and extensions). [`try_get_header`] reads the
+//! marker and anything fixed-size which follows it, leaving the contents of
+//! variable-length values in the buffer.
+//!
+//! ```
+//! use safer_bytes::wire::msgpack::{self, Header};
+//!
+//! // ["hi", -1]
+//! let mut buffer: &[u8] = &[0x92, 0xA2, b'h', b'i', 0xFF];
+//!
+//! assert_eq!(msgpack::try_get_array_len(&mut buffer)?, 2);
+//! assert_eq!(msgpack::try_get_str(&mut buffer)?, &b"hi"[..]);
+//! assert_eq!(msgpack::try_get_header(&mut buffer)?, Header::Int(-1));
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{Error, SafeBuf};
+use bytes::{Buf, Bytes};
+use paste::paste;
+
+/// The maximum length of a header (a `uint 64`, `int 64` or `float 64` marker,
+/// and its value)
+const MAX_HEADER_LEN: usize = 9;
+
+/// The header of a MessagePack value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Header {
+    /// `nil`
+    Nil,
+
+    /// `true` or `false`
+    Bool(bool),
+
+    /// A non-negative integer (positive fixint, or `uint 8` to `uint 64`)
+    Uint(u64),
+
+    /// A signed integer (negative fixint, or `int 8` to `int 64`)
+    Int(i64),
+
+    /// A single-precision float
+    F32(f32),
+
+    /// A double-precision float
+    F64(f64),
+
+    /// A string of the given length in bytes
+    Str(u32),
+
+    /// Binary data of the given length
+    Bin(u32),
+
+    /// An array with the given number of elements
+    Array(u32),
+
+    /// A map with the given number of key-value pairs
+    Map(u32),
+
+    /// Extension data of the given type and length
+    Ext {
+        /// The application-defined extension type
+        type_id: i8,
+
+        /// The length of the extension data
+        len: u32,
+    },
+}
+
+/// Read the header of a value
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// marker is the reserved value `0xC1`
+pub fn try_get_header<B>(buffer: &mut B) -> crate::Result<Header>
+where
+    B: Buf,
+{
+    let (bytes, len) = peek_header(buffer)?;
+    let mut peek = &bytes[..len];
+    let header = get_header(&mut peek)?;
+
+    buffer.advance(len - peek.len());
+    Ok(header)
+}
+
+/// Copy the bytes which may make up a header, without advancing the buffer
+fn peek_header<B>(buffer: &B) -> crate::Result<([u8; MAX_HEADER_LEN], usize)>
+where
+    B: Buf,
+{
+    let mut bytes = [0; MAX_HEADER_LEN];
+    let len = buffer.remaining().min(MAX_HEADER_LEN);
+    buffer.try_peek_to_slice(&mut bytes[..len])?;
+    Ok((bytes, len))
+}
+
+fn get_header(buffer: &mut &[u8]) -> crate::Result<Header> {
+    let marker = SafeBuf::try_get_u8(buffer)?;
+
+    Ok(match marker {
+        0x00..=0x7F => Header::Uint(marker.into()),
+        0x80..=0x8F => Header::Map((marker & 0x0F).into()),
+        0x90..=0x9F => Header::Array((marker & 0x0F).into()),
+        0xA0..=0xBF => Header::Str((marker & 0x1F).into()),
+        0xC0 => Header::Nil,
+        0xC1 => return Err(Error::invalid("MessagePack marker", marker)),
+        0xC2 => Header::Bool(false),
+        0xC3 => Header::Bool(true),
+        0xC4 => Header::Bin(SafeBuf::try_get_u8(buffer)?.into()),
+        0xC5 => Header::Bin(SafeBuf::try_get_u16(buffer)?.into()),
+        0xC6 => Header::Bin(SafeBuf::try_get_u32(buffer)?),
+        0xC7 => get_ext(SafeBuf::try_get_u8(buffer)?.into(), buffer)?,
+        0xC8 => get_ext(SafeBuf::try_get_u16(buffer)?.into(), buffer)?,
+        0xC9 => get_ext(SafeBuf::try_get_u32(buffer)?, buffer)?,
+        0xCA => Header::F32(f32::from_bits(SafeBuf::try_get_u32(buffer)?)),
+        0xCB => Header::F64(f64::from_bits(SafeBuf::try_get_u64(buffer)?)),
+        0xCC => Header::Uint(SafeBuf::try_get_u8(buffer)?.into()),
+        0xCD => Header::Uint(SafeBuf::try_get_u16(buffer)?.into()),
+        0xCE => Header::Uint(SafeBuf::try_get_u32(buffer)?.into()),
+        0xCF => Header::Uint(SafeBuf::try_get_u64(buffer)?),
+        0xD0 => Header::Int(SafeBuf::try_get_i8(buffer)?.into()),
+        0xD1 => Header::Int(SafeBuf::try_get_i16(buffer)?.into()),
+        0xD2 => Header::Int(SafeBuf::try_get_i32(buffer)?.into()),
+        0xD3 => Header::Int(SafeBuf::try_get_i64(buffer)?),
+        0xD4 => get_ext(1, buffer)?,
+        0xD5 => get_ext(2, buffer)?,
+        0xD6 => get_ext(4, buffer)?,
+        0xD7 => get_ext(8, buffer)?,
+        0xD8 => get_ext(16, buffer)?,
+        0xD9 => Header::Str(SafeBuf::try_get_u8(buffer)?.into()),
+        0xDA => Header::Str(SafeBuf::try_get_u16(buffer)?.into()),
+        0xDB => Header::Str(SafeBuf::try_get_u32(buffer)?),
+        0xDC => Header::Array(SafeBuf::try_get_u16(buffer)?.into()),
+        0xDD => Header::Array(SafeBuf::try_get_u32(buffer)?),
+        0xDE => Header::Map(SafeBuf::try_get_u16(buffer)?.into()),
+        0xDF => Header::Map(SafeBuf::try_get_u32(buffer)?),
+        #[allow(clippy::cast_possible_wrap)]
+        0xE0..=0xFF => Header::Int((marker as i8).into()),
+    })
+}
+
+fn get_ext(len: u32, buffer: &mut &[u8]) -> crate::Result<Header> {
+    Ok(Header::Ext {
+        type_id: SafeBuf::try_get_i8(buffer)?,
+        len,
+    })
+}
+
+macro_rules! get_len {
+    ($name:ident, $variant:ident, $what:literal) => {
+        paste! {
+            #[doc = "Read the header of " $what ", returning its length"]
+            #[doc = ""]
+            #[doc = "# Errors"]
+            #[doc = ""]
+            #[doc = "This method will return an error if the buffer is truncated, or if the next value is not " $what]
+            pub fn $name<B>(buffer: &mut B) -> crate::Result<u32>
+            where
+                B: Buf,
+            {
+                let (bytes, header_len) = peek_header(buffer)?;
+                let mut peek = &bytes[..header_len];
+                match get_header(&mut peek)? {
+                    Header::$variant(len) => {
+                        buffer.advance(header_len - peek.len());
+                        Ok(len)
+                    }
+                    _ => Err(Error::invalid(
+                        concat!("MessagePack marker (expected ", $what, ")"),
+                        buffer.try_get_u8_at(0)?,
+                    )),
+                }
+            }
+        }
+    };
+}
+
+get_len!(try_get_str_len, Str, "a string");
+get_len!(try_get_bin_len, Bin, "binary data");
+get_len!(try_get_array_len, Array, "an array");
+get_len!(try_get_map_len, Map, "a map");
+
+/// Read a string, returning its (unvalidated) UTF-8 bytes
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the next
+/// value is not a string
+pub fn try_get_str<B>(buffer: &mut B) -> crate::Result<Bytes>
+where
+    B: Buf,
+{
+    get_contents(buffer, |header| try_get_str_len(header))
+}
+
+/// Read binary data
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the next
+/// value is not binary data
+pub fn try_get_bin<B>(buffer: &mut B) -> crate::Result<Bytes>
+where
+    B: Buf,
+{
+    get_contents(buffer, |header| try_get_bin_len(header))
+}
+
+/// Read a length header followed by that many bytes, leaving the buffer
+/// untouched if either is truncated
+fn get_contents<B, F>(buffer: &mut B, get_len: F) -> crate::Result<Bytes>
+where
+    B: Buf,
+    F: FnOnce(&mut &[u8]) -> crate::Result<u32>,
+{
+    let (bytes, peek_len) = peek_header(buffer)?;
+    let mut peek = &bytes[..peek_len];
+    let len = get_len(&mut peek)? as usize;
+    let header_len = peek_len - peek.len();
+
+    crate::error::Truncated::check(header_len.saturating_add(len), buffer.remaining())?;
+    buffer.advance(header_len);
+    Ok(buffer.copy_to_bytes(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        try_get_array_len, try_get_bin, try_get_header, try_get_map_len, try_get_str, Header,
+    };
+    use crate::Error;
+
+    #[test]
+    fn headers() {
+        let mut buffer: &[u8] = &[
+            0x7F, 0xE0, 0xC0, 0xC3, 0xCD, 0x01, 0x00, 0xD0, 0x80, 0xCA, 0x3F, 0x80, 0x00, 0x00,
+            0xD5, 0x05, 0xDE, 0x00, 0x10,
+        ];
+
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Uint(127));
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Int(-32));
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Nil);
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Bool(true));
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Uint(256));
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Int(-128));
+        assert_eq!(try_get_header(&mut buffer).unwrap(), Header::F32(1.0));
+        assert_eq!(
+            try_get_header(&mut buffer).unwrap(),
+            Header::Ext { type_id: 5, len: 2 }
+        );
+        assert_eq!(try_get_map_len(&mut buffer).unwrap(), 16);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn eight_byte_headers() {
+        fn encoded(marker: u8, value: [u8; 8]) -> Vec<u8> {
+            [&[marker][..], &value[..]].concat()
+        }
+
+        for &value in &[0, 1, u64::MAX] {
+            let bytes = encoded(0xCF, value.to_be_bytes());
+            let mut buffer = &bytes[..];
+            assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Uint(value));
+            assert!(buffer.is_empty());
+        }
+
+        for &value in &[i64::MIN, -1, i64::MAX] {
+            let bytes = encoded(0xD3, value.to_be_bytes());
+            let mut buffer = &bytes[..];
+            assert_eq!(try_get_header(&mut buffer).unwrap(), Header::Int(value));
+            assert!(buffer.is_empty());
+        }
+
+        for &value in &[0.0, -1.5, f64::MAX] {
+            let bytes = encoded(0xCB, value.to_bits().to_be_bytes());
+            let mut buffer = &bytes[..];
+            assert_eq!(try_get_header(&mut buffer).unwrap(), Header::F64(value));
+            assert!(buffer.is_empty());
+        }
+
+        let mut truncated = &encoded(0xCF, [0; 8])[..8];
+        assert!(matches!(
+            try_get_header(&mut truncated).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(truncated.len(), 8);
+    }
+
+    #[test]
+    fn contents() {
+        let mut buffer: &[u8] = &[0xC4, 0x02, 0xCA, 0xFE, 0xA1, b'x'];
+
+        assert!(try_get_str(&mut buffer).is_err());
+        assert_eq!(try_get_bin(&mut buffer).unwrap(), &[0xCA, 0xFE][..]);
+        assert_eq!(try_get_str(&mut buffer).unwrap(), &b"x"[..]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut reserved: &[u8] = &[0xC1];
+        assert!(try_get_header(&mut reserved).is_err());
+
+        let mut truncated: &[u8] = &[0xDC, 0x00];
+        assert!(matches!(
+            try_get_array_len(&mut truncated).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(truncated.len(), 2);
+
+        let mut truncated: &[u8] = &[0xA3, b'a', b'b'];
+        assert!(matches!(
+            try_get_str(&mut truncated).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(truncated.len(), 3);
+    }
+}
@@ -0,0 +1,324 @@
+//! Protocol Buffers wire format
+//!
+//! A protobuf message is a sequence of fields, each introduced by a tag which
+//! combines the field number and the [`WireType`] of the value which follows.
+//! [`try_get_tag`] reads a tag, and [`try_skip_field`] skips over the value of
+//! a field which isn't of interest.
+//!
+//! ```
+//! use safer_bytes::wire::protobuf::{self, WireType};
+//!
+//! // field 1 = 150, field 2 = "hi"
+//! let mut buffer: &[u8] = &[0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i'];
+//!
+//! assert_eq!(protobuf::try_get_tag(&mut buffer)?, (1, WireType::Varint));
+//! assert_eq!(protobuf::try_get_varint(&mut buffer)?, 150);
+//!
+//! let (_, wire_type) = protobuf::try_get_tag(&mut buffer)?;
+//! protobuf::try_skip_field(&mut buffer, wire_type)?;
+//! assert!(buffer.is_empty());
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, Error, SafeBuf};
+use alloc::vec::Vec;
+use bytes::{Buf, Bytes};
+use core::convert::TryFrom;
+
+/// The maximum length of an encoded varint
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// The largest valid field number
+pub const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+
+/// The maximum nesting depth of groups which [`try_skip_field`] will skip
+pub const MAX_GROUP_DEPTH: usize = 64;
+
+/// The encoding of a field's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireType {
+    /// A variable-length integer (`int32`, `uint64`, `sint32`, `bool`, `enum`,
+    /// ...)
+    Varint,
+
+    /// A fixed 8-byte value (`fixed64`, `sfixed64`, `double`)
+    I64,
+
+    /// A varint length followed by that many bytes (`string`, `bytes`,
+    /// embedded messages, packed repeated fields)
+    Len,
+
+    /// The start of a group (deprecated)
+    StartGroup,
+
+    /// The end of a group (deprecated)
+    EndGroup,
+
+    /// A fixed 4-byte value (`fixed32`, `sfixed32`, `float`)
+    I32,
+}
+
+impl WireType {
+    /// The numeric value of the wire type, as it appears in a tag
+    #[must_use]
+    pub fn value(self) -> u8 {
+        match self {
+            Self::Varint => 0,
+            Self::I64 => 1,
+            Self::Len => 2,
+            Self::StartGroup => 3,
+            Self::EndGroup => 4,
+            Self::I32 => 5,
+        }
+    }
+}
+
+impl TryFrom<u8> for WireType {
+    type Error = Error;
+
+    fn try_from(value: u8) -> crate::Result<Self> {
+        Ok(match value {
+            0 => Self::Varint,
+            1 => Self::I64,
+            2 => Self::Len,
+            3 => Self::StartGroup,
+            4 => Self::EndGroup,
+            5 => Self::I32,
+            _ => return Err(Error::invalid("protobuf wire type", value)),
+        })
+    }
+}
+
+/// Read a varint
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// varint is longer than [`MAX_VARINT_LEN`] bytes or overflows a `u64`
+pub fn try_get_varint<B>(buffer: &mut B) -> crate::Result<u64>
+where
+    B: Buf,
+{
+    let mut bytes = [0; MAX_VARINT_LEN];
+    let len = buffer.remaining().min(MAX_VARINT_LEN);
+    buffer.try_peek_to_slice(&mut bytes[..len])?;
+
+    let mut value = 0;
+    for (i, &byte) in bytes[..len].iter().enumerate() {
+        if i == MAX_VARINT_LEN - 1 && byte > 1 {
+            return Err(Error::invalid("protobuf varint", &bytes[..]));
+        }
+
+        value |= u64::from(byte & 0x7F) << (7 * i);
+
+        if byte & 0x80 == 0 {
+            buffer.advance(i + 1);
+            return Ok(value);
+        }
+    }
+
+    if len == MAX_VARINT_LEN {
+        Err(Error::invalid("protobuf varint", &bytes[..]))
+    } else {
+        Err(error::Truncated::new(len + 1, len).into())
+    }
+}
+
+/// Decode a zigzag-encoded signed integer (as used by `sint32` and `sint64`)
+#[must_use]
+#[allow(clippy::cast_possible_wrap)]
+pub fn decode_zigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// Read a tag, returning the field number and wire type
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the tag
+/// contains an invalid field number or wire type
+pub fn try_get_tag<B>(buffer: &mut B) -> crate::Result<(u32, WireType)>
+where
+    B: Buf,
+{
+    let mut bytes = [0; MAX_VARINT_LEN];
+    let len = buffer.remaining().min(MAX_VARINT_LEN);
+    buffer.try_peek_to_slice(&mut bytes[..len])?;
+
+    let mut peek = &bytes[..len];
+    let tag = try_get_varint(&mut peek)?;
+
+    let wire_type = WireType::try_from((tag & 0x07) as u8)?;
+    let field_number = u32::try_from(tag >> 3)
+        .ok()
+        .filter(|&n| (1..=MAX_FIELD_NUMBER).contains(&n))
+        .ok_or_else(|| Error::invalid("protobuf field number", tag >> 3))?;
+
+    buffer.advance(len - peek.len());
+    Ok((field_number, wire_type))
+}
+
+/// Read the value of a length-delimited field
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// length is invalid
+pub fn try_get_len_delimited<B>(buffer: &mut B) -> crate::Result<Bytes>
+where
+    B: Buf,
+{
+    let mut bytes = [0; MAX_VARINT_LEN];
+    let peek_len = buffer.remaining().min(MAX_VARINT_LEN);
+    buffer.try_peek_to_slice(&mut bytes[..peek_len])?;
+
+    let mut peek = &bytes[..peek_len];
+    let len = try_get_varint(&mut peek)?;
+    let prefix_len = peek_len - peek.len();
+
+    let len = usize::try_from(len).map_err(|_| Error::invalid("protobuf length", len))?;
+    error::Truncated::check(prefix_len.saturating_add(len), buffer.remaining())?;
+
+    buffer.advance(prefix_len);
+    Ok(buffer.copy_to_bytes(len))
+}
+
+/// Skip over the value of a field, given its wire type
+///
+/// For [`WireType::StartGroup`], this skips every field up to and including the
+/// matching [`WireType::EndGroup`], to a maximum nesting depth of
+/// [`MAX_GROUP_DEPTH`]. If this fails part-way through a group, the buffer is
+/// left part-way through the group too.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, if the value
+/// is invalid, or if the wire type is [`WireType::EndGroup`] (which has no
+/// value, and cannot be skipped without a matching start).
+pub fn try_skip_field<B>(buffer: &mut B, wire_type: WireType) -> crate::Result<()>
+where
+    B: Buf,
+{
+    match wire_type {
+        WireType::Varint => try_get_varint(buffer).map(drop),
+        WireType::I64 => Ok(buffer.try_advance(8)?),
+        WireType::I32 => Ok(buffer.try_advance(4)?),
+        WireType::Len => try_get_len_delimited(buffer).map(drop),
+        WireType::StartGroup => skip_group(buffer),
+        WireType::EndGroup => Err(Error::invalid(
+            "protobuf wire type",
+            WireType::EndGroup.value(),
+        )),
+    }
+}
+
+/// Skip the fields of a group, up to and including its end tag
+fn skip_group<B>(buffer: &mut B) -> crate::Result<()>
+where
+    B: Buf,
+{
+    // field numbers of the nested groups opened within this group
+    let mut open = Vec::new();
+
+    loop {
+        match try_get_tag(buffer)? {
+            (_, WireType::EndGroup) if open.is_empty() => return Ok(()),
+            (field_number, WireType::EndGroup) => {
+                if open.pop() != Some(field_number) {
+                    return Err(Error::invalid("protobuf group end", field_number));
+                }
+            }
+            (field_number, WireType::StartGroup) => {
+                if open.len() + 1 >= MAX_GROUP_DEPTH {
+                    return Err(
+                        error::DepthLimitExceeded::new("protobuf group", MAX_GROUP_DEPTH).into(),
+                    );
+                }
+                open.push(field_number);
+            }
+            (_, wire_type) => try_skip_field(buffer, wire_type)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_tag, try_get_varint, try_skip_field, WireType};
+    use crate::Error;
+
+    #[test]
+    fn varint() {
+        let mut buffer: &[u8] = &[0x96, 0x01, 0x00];
+        assert_eq!(try_get_varint(&mut buffer).unwrap(), 150);
+        assert_eq!(try_get_varint(&mut buffer).unwrap(), 0);
+
+        let mut max: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+        assert_eq!(try_get_varint(&mut max).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn varint_errors() {
+        let mut truncated: &[u8] = &[0x96];
+        assert!(matches!(
+            try_get_varint(&mut truncated).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(truncated, &[0x96]);
+
+        let mut overflow: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02];
+        assert!(matches!(
+            try_get_varint(&mut overflow).unwrap_err(),
+            Error::InvalidValue(_)
+        ));
+
+        let mut too_long: &[u8] = &[0x80; 11];
+        assert!(matches!(
+            try_get_varint(&mut too_long).unwrap_err(),
+            Error::InvalidValue(_)
+        ));
+    }
+
+    #[test]
+    fn tag() {
+        let mut buffer: &[u8] = &[0x0D, 0x00];
+        assert_eq!(try_get_tag(&mut buffer).unwrap(), (1, WireType::I32));
+        assert_eq!(buffer, &[0x00]);
+
+        // field number zero
+        assert!(try_get_tag(&mut buffer).is_err());
+
+        // wire type 7
+        let mut buffer: &[u8] = &[0x0F];
+        assert!(try_get_tag(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn skip_field() {
+        let mut buffer: &[u8] = &[0x96, 0x01, 0xAA];
+        try_skip_field(&mut buffer, WireType::Varint).unwrap();
+        assert_eq!(buffer, &[0xAA]);
+
+        let mut buffer: &[u8] = &[0x03, 1, 2, 3, 0xAA];
+        try_skip_field(&mut buffer, WireType::Len).unwrap();
+        assert_eq!(buffer, &[0xAA]);
+
+        let mut buffer: &[u8] = &[0x05, 1, 2];
+        assert!(try_skip_field(&mut buffer, WireType::Len).is_err());
+        assert_eq!(buffer, &[0x05, 1, 2]);
+    }
+
+    #[test]
+    fn skip_group() {
+        // field 2 (varint), nested group 3 { field 1 (i32) }, end of group 1
+        let mut buffer: &[u8] = &[0x10, 0x01, 0x1B, 0x0D, 0, 0, 0, 0, 0x1C, 0x0C, 0xAA];
+        try_skip_field(&mut buffer, WireType::StartGroup).unwrap();
+        assert_eq!(buffer, &[0xAA]);
+
+        // nested group 3 closed as group 4
+        let mut buffer: &[u8] = &[0x1B, 0x24, 0x0C];
+        assert!(try_skip_field(&mut buffer, WireType::StartGroup).is_err());
+
+        let mut buffer: &[u8] = &[];
+        assert!(try_skip_field(&mut buffer, WireType::EndGroup).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Recursive Length Prefix (RLP) encoding, as used by Ethereum
+//!
+//! An RLP item is either a byte string or a list of items. [`try_get_item`]
+//! reads one item, returning a byte string directly, or a list as a bounded
+//! [`SubBuf`] over its encoded items. Only canonical encodings are accepted,
+//! so every value has exactly one valid encoding.
+//!
+//! ```
+//! use safer_bytes::wire::rlp::{self, Item};
+//!
+//! // ["cat", "dog"]
+//! let mut buffer: &[u8] = &[0xC8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g'];
+//!
+//! if let Item::List(mut list) = rlp::try_get_item(&mut buffer)? {
+//!     assert!(matches!(rlp::try_get_item(&mut list)?, Item::Bytes(b) if b == "cat"));
+//!     assert!(matches!(rlp::try_get_item(&mut list)?, Item::Bytes(b) if b == "dog"));
+//! }
+//! assert!(buffer.is_empty());
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, Error, SafeBuf, SubBuf};
+use bytes::{Buf, Bytes};
+use core::convert::TryFrom;
+
+/// The longest payload which can use a single-byte prefix
+const MAX_SHORT_LEN: u64 = 55;
+
+/// An RLP item
+#[derive(Debug)]
+pub enum Item<'a, B>
+where
+    B: Buf,
+{
+    /// A byte string
+    Bytes(Bytes),
+
+    /// A list, as a view over its encoded items
+    ///
+    /// Once this is dropped, the buffer is positioned directly after the list.
+    List(SubBuf<'a, B>),
+}
+
+/// Read an item
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the item
+/// is not canonically encoded (a single byte below `0x80` which is not encoded
+/// as itself, a length which could have used a shorter form, or a length with
+/// leading zeros)
+pub fn try_get_item<B>(buffer: &mut B) -> crate::Result<Item<'_, B>>
+where
+    B: Buf,
+{
+    let prefix = buffer.try_get_u8_at(0)?;
+
+    let (is_list, header_len, len) = match prefix {
+        0x00..=0x7F => return Ok(Item::Bytes(buffer.copy_to_bytes(1))),
+        0x80..=0xB7 => (false, 1, u64::from(prefix - 0x80)),
+        0xB8..=0xBF => long_len(buffer, prefix - 0xB7).map(|(h, l)| (false, h, l))?,
+        0xC0..=0xF7 => (true, 1, u64::from(prefix - 0xC0)),
+        0xF8..=0xFF => long_len(buffer, prefix - 0xF7).map(|(h, l)| (true, h, l))?,
+    };
+
+    let len = usize::try_from(len).map_err(|_| Error::invalid("RLP length", len))?;
+    error::Truncated::check(header_len.saturating_add(len), buffer.remaining())?;
+
+    if !is_list && len == 1 && buffer.try_get_u8_at(1)? < 0x80 {
+        return Err(Error::invalid("RLP single byte", buffer.try_get_u8_at(1)?));
+    }
+
+    buffer.advance(header_len);
+    Ok(if is_list {
+        Item::List(buffer.try_take_buf(len)?)
+    } else {
+        Item::Bytes(buffer.copy_to_bytes(len))
+    })
+}
+
+/// Peek a long-form length of `len_len` bytes, returning the total header
+/// length and the payload length
+fn long_len<B>(buffer: &B, len_len: u8) -> crate::Result<(usize, u64)>
+where
+    B: Buf,
+{
+    let mut bytes = [0; 8];
+    let bytes = &mut bytes[..usize::from(len_len)];
+    buffer.try_peek_at_to_slice(1, bytes)?;
+
+    if bytes[0] == 0 {
+        return Err(Error::invalid("RLP length", &bytes[..]));
+    }
+
+    let len = bytes
+        .iter()
+        .fold(0, |len, &byte| (len << 8) | u64::from(byte));
+    if len <= MAX_SHORT_LEN {
+        return Err(Error::invalid("RLP length", len));
+    }
+
+    Ok((1 + bytes.len(), len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_item, Item};
+    use crate::Error;
+
+    fn bytes(buffer: &mut &[u8]) -> crate::Result<Vec<u8>> {
+        match try_get_item(buffer)? {
+            Item::Bytes(bytes) => Ok(bytes.to_vec()),
+            Item::List(_) => panic!("expected bytes"),
+        }
+    }
+
+    #[test]
+    fn strings() {
+        let mut long = vec![0xB8, 56];
+        long.extend_from_slice(&[0xAA; 56]);
+
+        let mut buffer: &[u8] = &[0x00, 0x80, 0x81, 0x80];
+        assert_eq!(bytes(&mut buffer).unwrap(), vec![0x00]);
+        assert_eq!(bytes(&mut buffer).unwrap(), vec![]);
+        assert_eq!(bytes(&mut buffer).unwrap(), vec![0x80]);
+
+        let mut buffer = &long[..];
+        assert_eq!(bytes(&mut buffer).unwrap(), vec![0xAA; 56]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn nested_lists() {
+        // [[], [[]]]
+        let mut buffer: &[u8] = &[0xC3, 0xC0, 0xC1, 0xC0, 0x01];
+
+        match try_get_item(&mut buffer).unwrap() {
+            Item::List(mut list) => {
+                assert!(matches!(try_get_item(&mut list).unwrap(), Item::List(_)));
+                assert!(matches!(try_get_item(&mut list).unwrap(), Item::List(_)));
+                assert!(try_get_item(&mut list).is_err());
+            }
+            Item::Bytes(_) => panic!("expected a list"),
+        }
+        assert_eq!(buffer, &[0x01]);
+    }
+
+    #[test]
+    fn non_canonical() {
+        for encoding in &[
+            // single byte which should be encoded as itself
+            &[0x81, 0x7F][..],
+            // long form for a short string
+            &[0xB8, 0x01, 0xAA],
+            // leading zero in the length
+            &[0xB9, 0x00, 0x38],
+            // long form for a short list
+            &[0xF8, 0x00],
+        ] {
+            let mut buffer = *encoding;
+            assert!(matches!(
+                try_get_item(&mut buffer).unwrap_err(),
+                Error::InvalidValue(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let mut buffer: &[u8] = &[0x83, b'c', b'a'];
+        assert!(matches!(
+            try_get_item(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), 3);
+    }
+}
@@ -0,0 +1,229 @@
+//! Generic Type-Length-Value records
+//!
+//! A great many protocols frame their records as a tag, followed by a length,
+//! followed by that many bytes of value. They differ only in the widths and
+//! byte orders of the tag and length, which are described here by the
+//! [`Field`] trait. [`SafeBuf::try_get_tlv`] reads a record given the types of
+//! its tag and length fields, and [`SafeBuf::tlv_records`] iterates over the
+//! records in a region.
+//!
+//! ```
+//! use safer_bytes::{
+//!     wire::tlv::{Be, Le},
+//!     SafeBuf,
+//! };
+//!
+//! // a one-byte tag, and a big-endian two-byte length
+//! let mut buffer = &[0x01, 0x00, 0x02, 0xAA, 0xBB, 0xFF][..];
+//! let (tag, value) = buffer.try_get_tlv::<u8, Be<u16>>(1024)?;
+//! assert_eq!((tag, &value[..]), (1, &[0xAA, 0xBB][..]));
+//!
+//! // a little-endian two-byte tag, and a little-endian four-byte length
+//! let mut buffer = &[0x34, 0x12, 0x01, 0x00, 0x00, 0x00, 0xCC][..];
+//! let (tag, value) = buffer.try_get_tlv::<Le<u16>, Le<u32>>(1024)?;
+//! assert_eq!((tag, &value[..]), (0x1234, &[0xCC][..]));
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use crate::{error, SafeBuf};
+use bytes::{Buf, Bytes};
+use core::{convert::TryInto, fmt, marker::PhantomData};
+
+/// A fixed-width integer field, such as the tag or length of a TLV record
+pub trait Field {
+    /// The value of the field
+    type Value;
+
+    /// The width of the field, in bytes
+    const WIDTH: usize;
+
+    /// Read the field, starting `offset` bytes from the front of the buffer,
+    /// without advancing the buffer
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the number of bytes remaining in the
+    /// buffer is insufficent
+    fn peek<B>(buffer: &B, offset: usize) -> Result<Self::Value, error::Truncated>
+    where
+        B: Buf;
+}
+
+/// A big-endian integer field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Be<T>(PhantomData<T>);
+
+/// A little-endian integer field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Le<T>(PhantomData<T>);
+
+impl Field for u8 {
+    type Value = Self;
+
+    const WIDTH: usize = 1;
+
+    fn peek<B>(buffer: &B, offset: usize) -> Result<Self, error::Truncated>
+    where
+        B: Buf,
+    {
+        buffer.try_get_u8_at(offset)
+    }
+}
+
+macro_rules! fields {
+    ($($t:ty: $width:literal),*) => {
+        paste::paste! {
+            $(
+                impl Field for Be<$t> {
+                    type Value = $t;
+                    const WIDTH: usize = $width;
+
+                    fn peek<B>(buffer: &B, offset: usize) -> Result<$t, error::Truncated>
+                    where
+                        B: Buf,
+                    {
+                        buffer.[<try_get_ $t _at>](offset)
+                    }
+                }
+
+                impl Field for Le<$t> {
+                    type Value = $t;
+                    const WIDTH: usize = $width;
+
+                    fn peek<B>(buffer: &B, offset: usize) -> Result<$t, error::Truncated>
+                    where
+                        B: Buf,
+                    {
+                        buffer.[<try_get_ $t _le_at>](offset)
+                    }
+                }
+            )*
+        }
+    };
+}
+
+fields!(u16: 2, u32: 4, u64: 8);
+
+/// An iterator over the TLV records in a region
+///
+/// This is created by [`SafeBuf::tlv_records`]. The iterator ends once the
+/// region is exhausted. If the region ends part-way through a record (or a
+/// record is otherwise invalid), the error is yielded, and the iterator ends.
+pub struct Records<T, L, B> {
+    buffer: B,
+    max_len: usize,
+    failed: bool,
+    _fields: PhantomData<fn() -> (T, L)>,
+}
+
+impl<T, L, B> Records<T, L, B> {
+    pub(crate) fn new(buffer: B, max_len: usize) -> Self {
+        Self {
+            buffer,
+            max_len,
+            failed: false,
+            _fields: PhantomData,
+        }
+    }
+
+    /// Consume the iterator, returning the underlying buffer
+    pub fn into_inner(self) -> B {
+        self.buffer
+    }
+}
+
+impl<T, L, B> fmt::Debug for Records<T, L, B>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Records")
+            .field("buffer", &self.buffer)
+            .field("max_len", &self.max_len)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+impl<T, L, B> Iterator for Records<T, L, B>
+where
+    T: Field,
+    L: Field,
+    L::Value: TryInto<usize>,
+    B: Buf,
+{
+    type Item = crate::Result<(T::Value, Bytes)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || !self.buffer.has_remaining() {
+            return None;
+        }
+
+        let record = self.buffer.try_get_tlv::<T, L>(self.max_len);
+        self.failed = record.is_err();
+        Some(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Be, Le};
+    use crate::{error, SafeBuf};
+
+    #[test]
+    fn try_get_tlv() {
+        let mut buffer = &[0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 1, 0xAA][..];
+
+        let (tag, value) = buffer.try_get_tlv::<Be<u32>, Be<u64>>(16).unwrap();
+        assert_eq!(tag, 7);
+        assert_eq!(value, &[0xAA][..]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        let mut buffer = &[1, 0x05, 0x00, 0xAA][..];
+        assert_eq!(
+            buffer.try_get_tlv::<u8, Le<u16>>(4).unwrap_err(),
+            error::LimitExceeded::new("TLV length", 5, 4).into()
+        );
+        assert_eq!(
+            buffer.try_get_tlv::<u8, Le<u16>>(16).unwrap_err(),
+            error::Truncated::new(8, 4).into()
+        );
+        assert_eq!(
+            buffer.try_get_tlv::<Be<u64>, u8>(16).unwrap_err(),
+            error::Truncated::new(8, 4).into()
+        );
+        assert_eq!(buffer.len(), 4);
+    }
+
+    #[test]
+    fn records() {
+        let mut buffer = &[1, 1, 0xAA, 2, 0, 3, 2, 0xBB, 0xCC][..];
+
+        let records: Vec<_> = (&mut buffer)
+            .tlv_records::<u8, u8>(16)
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            [
+                (1, vec![0xAA].into()),
+                (2, vec![].into()),
+                (3, vec![0xBB, 0xCC].into())
+            ]
+        );
+        assert!(buffer.is_empty());
+
+        // the region ends part-way through a record
+        let mut records = (&[1, 1, 0xAA, 2, 2, 0xBB][..]).tlv_records::<u8, u8>(16);
+        assert_eq!(records.next().unwrap().unwrap().0, 1);
+        assert_eq!(
+            records.next().unwrap().unwrap_err(),
+            error::Truncated::new(4, 3).into()
+        );
+        assert!(records.next().is_none());
+        assert_eq!(records.into_inner().len(), 3);
+    }
+}
@@ -0,0 +1,200 @@
+//! WebAssembly binary format
+//!
+//! Helpers for the building blocks of a `.wasm` module: the preamble, section
+//! headers, vector lengths and names. Integers are encoded as unsigned
+//! [LEB128](super::leb128).
+//!
+//! ```
+//! use safer_bytes::wire::wasm;
+//!
+//! // an empty module, with a custom section named "hi"
+//! let mut buffer: &[u8] = b"\0asm\x01\0\0\0\x00\x03\x02hi";
+//!
+//! wasm::try_get_preamble(&mut buffer)?;
+//! let header = wasm::try_get_section_header(&mut buffer)?;
+//! assert_eq!((header.id, header.size), (0, 3));
+//! assert_eq!(wasm::try_take_name(&mut buffer)?, "hi");
+//! # Ok::<(), safer_bytes::Error>(())
+//! ```
+
+use super::leb128;
+use crate::{error, Error, SafeBuf};
+use alloc::string::String;
+use bytes::Buf;
+
+/// The magic number at the start of every module
+pub const MAGIC: [u8; 4] = *b"\0asm";
+
+/// The binary format version supported by [`try_get_preamble`]
+pub const VERSION: u32 = 1;
+
+/// The maximum length of a LEB128-encoded `u32`
+const MAX_U32_LEN: usize = 5;
+
+/// The header of a section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SectionHeader {
+    /// The section ID (`0` for custom sections)
+    pub id: u8,
+
+    /// The size of the section contents, in bytes
+    pub size: u32,
+}
+
+/// Read and check the module preamble (the magic number and version)
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated, or if the
+/// magic number or version is not recognised
+pub fn try_get_preamble<B>(buffer: &mut B) -> crate::Result<()>
+where
+    B: Buf,
+{
+    let magic: [u8; 4] = buffer.try_peek_const()?;
+    if magic != MAGIC {
+        return Err(Error::invalid("WebAssembly magic number", magic));
+    }
+
+    let version = buffer.try_get_u32_le_at(4)?;
+    if version != VERSION {
+        return Err(Error::invalid("WebAssembly version", version));
+    }
+
+    buffer.advance(8);
+    Ok(())
+}
+
+/// Read a section header
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the size is not a valid `u32`
+pub fn try_get_section_header<B>(buffer: &mut B) -> crate::Result<SectionHeader>
+where
+    B: Buf,
+{
+    let id = buffer.try_get_u8_at(0)?;
+    let (size, end) = peek_u32(buffer, 1)?;
+
+    buffer.advance(end);
+    Ok(SectionHeader { id, size })
+}
+
+/// Read the length of a vector, checking it against a limit
+///
+/// Checking the length before allocating space for the elements prevents a
+/// corrupt module from causing a huge allocation.
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), if the length is not a valid `u32`, or if it exceeds
+/// `max`
+pub fn try_get_vec_len<B>(buffer: &mut B, max: u32) -> crate::Result<u32>
+where
+    B: Buf,
+{
+    let (len, prefix_len) = peek_u32(buffer, 0)?;
+    error::LimitExceeded::check("WebAssembly vector length", len as usize, max as usize)?;
+
+    buffer.advance(prefix_len);
+    Ok(len)
+}
+
+/// Read a name (a length-prefixed UTF-8 string)
+///
+/// # Errors
+///
+/// This method will return an error if the buffer is truncated (in which case
+/// it is left untouched), or if the name is not valid UTF-8
+pub fn try_take_name<B>(buffer: &mut B) -> crate::Result<String>
+where
+    B: Buf,
+{
+    let (len, prefix_len) = peek_u32(buffer, 0)?;
+    let len = len as usize;
+    error::Truncated::check(prefix_len.saturating_add(len), buffer.remaining())?;
+
+    let bytes = buffer.try_peek_at(prefix_len, len)?;
+    let name = core::str::from_utf8(&bytes)
+        .map_err(|_| Error::invalid("WebAssembly name", bytes.clone()))?
+        .into();
+
+    buffer.advance(prefix_len + len);
+    Ok(name)
+}
+
+/// Read a `u32` starting `offset` bytes from the front of the buffer, without
+/// advancing it. Returns the value, and the offset of the byte which follows
+/// it.
+fn peek_u32<B>(buffer: &B, offset: usize) -> crate::Result<(u32, usize)>
+where
+    B: Buf,
+{
+    let mut bytes = [0; MAX_U32_LEN];
+    let len = buffer.remaining().saturating_sub(offset).min(MAX_U32_LEN);
+    buffer.try_peek_at_to_slice(offset, &mut bytes[..len])?;
+
+    let mut peek = &bytes[..len];
+    let value = leb128::try_get_u32(&mut peek)?;
+    Ok((value, offset + len - peek.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_get_preamble, try_get_section_header, try_get_vec_len, try_take_name};
+    use crate::{error, Error};
+
+    #[test]
+    fn preamble() {
+        let mut buffer: &[u8] = b"\0asm\x01\0\0\0";
+        try_get_preamble(&mut buffer).unwrap();
+        assert!(buffer.is_empty());
+
+        let mut buffer: &[u8] = b"\0asm\x02\0\0\0";
+        assert!(try_get_preamble(&mut buffer).is_err());
+    }
+
+    #[test]
+    fn section_header() {
+        let mut buffer: &[u8] = &[0x01, 0x80, 0x01, 0xAA];
+        let header = try_get_section_header(&mut buffer).unwrap();
+        assert_eq!((header.id, header.size), (1, 128));
+        assert_eq!(buffer, &[0xAA]);
+
+        let mut buffer: &[u8] = &[0x01, 0x80];
+        assert!(matches!(
+            try_get_section_header(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+    }
+
+    #[test]
+    fn vec_len() {
+        let mut buffer: &[u8] = &[0x10, 0x11];
+        assert_eq!(try_get_vec_len(&mut buffer, 16).unwrap(), 16);
+        assert_eq!(
+            try_get_vec_len(&mut buffer, 16).unwrap_err(),
+            error::LimitExceeded::new("WebAssembly vector length", 17, 16).into()
+        );
+        assert_eq!(buffer, &[0x11]);
+    }
+
+    #[test]
+    fn name() {
+        let mut buffer: &[u8] = &[0x02, 0xFF, 0xFE];
+        assert!(matches!(
+            try_take_name(&mut buffer).unwrap_err(),
+            Error::InvalidValue(_)
+        ));
+
+        let mut buffer: &[u8] = &[0x03, b'a', b'b'];
+        assert!(matches!(
+            try_take_name(&mut buffer).unwrap_err(),
+            Error::Truncated(_)
+        ));
+        assert_eq!(buffer.len(), 3);
+    }
+}